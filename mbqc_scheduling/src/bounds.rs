@@ -0,0 +1,470 @@
+/*!
+Lower bounds on the costs of any path, which can be calculated without running the
+search.
+
+The bounds are used by the [search](crate::search) for pruning and to stop early, and to
+determine the [Optimality](crate::interface::Optimality) of the found paths.
+
+There is no separator-based bound on the [space]: when half of the measured nodes have
+been measured, their unmeasured neighbors are in memory, so the minimum vertex boundary of
+the balanced node subsets is a lower bound. However, computing it is NP-hard (like the
+minimum bisection), and its cheap lower bounds, e.g., spectral ones, would need
+eigenvalue computations, while they are often weaker than the [degeneracy] bound. For
+small instances, the [order] bound is exact for measuring one node after the other, so
+it is at least as strong as any separator-based bound.
+*/
+
+use std::cmp;
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    interface::Constraints,
+    scheduler::{
+        space::{Objective, RefSpacialGraph},
+        time::RefPartialOrderGraph,
+    },
+    search,
+};
+
+/// Up to that many measured (i.e., non-output) nodes, the [order] bound is calculated
+/// (it scales exponentially with the number of nodes).
+pub const MAX_ORDER_NODES: usize = 18;
+
+/// Lower bounds on the costs of any path, cf. [bounds].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Bounds {
+    /// Lower bound on the time cost, cf. [time].
+    pub time: usize,
+    /// Lower bound on the space cost, cf. [space].
+    pub space: usize,
+    /// Lower bound on the space-time volume, cf. [volume].
+    pub volume: usize,
+    /// Lower bound on the qubit idle time, cf. [idle].
+    pub idle: usize,
+}
+
+impl Bounds {
+    /// Get the bound on the cost according to the `objective`.
+    pub fn cost(&self, objective: Objective) -> usize {
+        match objective {
+            Objective::Space => self.space,
+            Objective::Volume => self.volume,
+            Objective::Idle => self.idle,
+        }
+    }
+}
+
+/// Calculate all the lower [Bounds]. Regarding the parameters, cf.
+/// [interface::run](crate::interface::run).
+pub fn bounds(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+) -> Bounds {
+    let outputs = search::all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let space = space(spacial_graph, weights, &outputs);
+    // the volume is at least the memory in any step; the space cost, however, also
+    // counts the outputs that are never initialized, so in this case, only the bounds on
+    // the memory in a step carry over
+    let is_output = is_output(spacial_graph.len(), &outputs);
+    let initialized = |bit: &usize| is_initialized(spacial_graph, &is_output, *bit);
+    let step_memory = if outputs.iter().all(initialized) {
+        space
+    } else {
+        cmp::max(
+            degeneracy(spacial_graph, weights, &outputs),
+            order(spacial_graph, weights, &outputs).unwrap_or(0),
+        )
+    };
+    Bounds {
+        time: time(spacial_graph, &outputs, time_ordering, constraints),
+        space,
+        volume: cmp::max(volume(spacial_graph, weights, &outputs), step_memory),
+        idle: idle(spacial_graph, &outputs, time_ordering, constraints),
+    }
+}
+
+// the dependency edges between measured nodes, together with their latencies; this
+// includes the implied dependencies with a specific latency, cf. [Constraints]
+fn dependency_edges<'l>(
+    is_output: &'l [bool],
+    time_ordering: RefPartialOrderGraph<'l>,
+    constraints: &'l Constraints,
+) -> impl Iterator<Item = (usize, usize, usize)> + 'l {
+    let mut edge_latencies: HashMap<usize, Vec<(usize, usize)>> = HashMap::new();
+    for (dep, bit, latency) in constraints.edge_latencies.iter() {
+        if !is_output[*dep] {
+            edge_latencies.entry(*bit).or_default().push((*dep, *latency));
+        }
+    }
+    time_ordering
+        .iter()
+        .flatten()
+        .filter(|(bit, _)| !is_output[*bit])
+        .flat_map(move |(bit, deps)| {
+            let specific = edge_latencies.get(bit).map(Vec::as_slice).unwrap_or_default();
+            let direct = deps.iter().filter(|dep| !is_output[**dep]).map(|dep| {
+                let latency = specific
+                    .iter()
+                    .rfind(|(specific_dep, _)| specific_dep == dep)
+                    .map(|(_, latency)| *latency)
+                    .unwrap_or(constraints.latency);
+                (*dep, latency)
+            });
+            let implied = specific.iter().filter(|(dep, _)| !deps.contains(dep)).copied();
+            direct
+                .chain(implied)
+                .map(|(dep, latency)| (dep, *bit, latency))
+                .collect::<Vec<_>>()
+        })
+}
+
+// an output is initialized when one of its neighbors is measured
+fn is_initialized(graph: RefSpacialGraph, is_output: &[bool], bit: usize) -> bool {
+    graph[bit].iter().any(|neighbor| !is_output[*neighbor])
+}
+
+fn is_output(num_bits: usize, outputs: &[usize]) -> Vec<bool> {
+    let mut is_output = vec![false; num_bits];
+    for bit in outputs {
+        is_output[*bit] = true;
+    }
+    is_output
+}
+
+/// Lower bound on the time cost, i.e., the number of steps: the longest chain in the
+/// `time_ordering` (taking into account the latencies and release times of the
+/// [Constraints]), and the number of measured nodes divided by the maximum step size.
+/// The `outputs` have to contain all nodes that are not measured.
+pub fn time(
+    spacial_graph: RefSpacialGraph,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+) -> usize {
+    let num_bits = spacial_graph.len();
+    let is_output = is_output(num_bits, outputs);
+    let num_measured = num_bits - outputs.len();
+    if num_measured == 0 {
+        return 0;
+    }
+
+    // the earliest step in which a node can be measured; the layers are topologically
+    // sorted, so the earliest step of the dependencies is final when it is propagated
+    let mut earliest = vec![0; num_bits];
+    for (bit, release_time) in
+        search::release_times(spacial_graph, &constraints.release_times)
+    {
+        earliest[bit] = release_time;
+    }
+    for (dep, bit, latency) in dependency_edges(&is_output, time_ordering, constraints) {
+        earliest[bit] = cmp::max(earliest[bit], earliest[dep] + 1 + latency);
+    }
+    let depth = (0..num_bits)
+        .filter(|bit| !is_output[*bit])
+        .map(|bit| earliest[bit] + 1)
+        .max()
+        .unwrap_or(0);
+
+    match constraints.max_step_size {
+        Some(max) => cmp::max(depth, num_measured.div_ceil(max)),
+        None => depth,
+    }
+}
+
+/// Lower bound on the space cost, i.e., the maximum of the (weighted) number of outputs
+/// and the [degeneracy] and [order] bounds. The `outputs` have to contain all nodes
+/// that are not measured.
+pub fn space(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+) -> usize {
+    let output_weight = outputs.iter().map(|bit| weight(weights, *bit)).sum();
+    let bound = cmp::max(output_weight, degeneracy(spacial_graph, weights, outputs));
+    match order(spacial_graph, weights, outputs) {
+        Some(order) => cmp::max(bound, order),
+        None => bound,
+    }
+}
+
+/// Degree-based lower bound on the space cost: when the first node of any subset of the
+/// measured nodes is measured, all its neighbors in the subset (and in the outputs) have
+/// to be in memory. The bound is the maximum over all subsets of the minimum (weighted)
+/// closed neighborhood in the subset, which can be calculated by greedily removing the
+/// nodes with the smallest neighborhood (for unit weights, it is the degeneracy of the
+/// graph plus one). The `outputs` have to contain all nodes that are not measured.
+pub fn degeneracy(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+) -> usize {
+    let num_bits = spacial_graph.len();
+    let is_output = is_output(num_bits, outputs);
+    let mut removed = is_output.clone();
+    let mut neighborhood = (0..num_bits)
+        .map(|bit| {
+            weight(weights, bit)
+                + spacial_graph[bit].iter().map(|n| weight(weights, *n)).sum::<usize>()
+        })
+        .collect::<Vec<_>>();
+
+    let mut bound = 0;
+    // PERF: use a priority queue
+    while let Some(bit) = (0..num_bits)
+        .filter(|bit| !removed[*bit])
+        .min_by_key(|bit| neighborhood[*bit])
+    {
+        bound = cmp::max(bound, neighborhood[bit]);
+        removed[bit] = true;
+        for neighbor in spacial_graph[bit].iter() {
+            neighborhood[*neighbor] -= weight(weights, bit);
+        }
+    }
+    bound
+}
+
+/// Lower bound on the space cost, which is the minimum over all orders in which the
+/// nodes can be measured one by one (ignoring the time ordering) of the maximum
+/// (weighted) number of nodes in memory. Since this scales exponentially, it is only
+/// calculated if there are at most [MAX_ORDER_NODES] measured nodes (and at most 64
+/// nodes in total), otherwise None is returned. The `outputs` have to contain all nodes
+/// that are not measured.
+pub fn order(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+) -> Option<usize> {
+    let num_bits = spacial_graph.len();
+    let is_output = is_output(num_bits, outputs);
+    let measured = (0..num_bits).filter(|bit| !is_output[*bit]).collect::<Vec<_>>();
+    if measured.len() > MAX_ORDER_NODES || num_bits > 64 {
+        return None;
+    }
+
+    // the closed neighborhoods as bit masks over all nodes
+    let neighborhoods = measured
+        .iter()
+        .map(|bit| {
+            spacial_graph[*bit]
+                .iter()
+                .fold(1u64 << bit, |mask, n| mask | (1 << n))
+        })
+        .collect::<Vec<_>>();
+    let mask_weight = |mut mask: u64| match weights {
+        Some(weights) => {
+            let mut weight = 0;
+            while mask != 0 {
+                weight += weights[mask.trailing_zeros() as usize];
+                mask &= mask - 1;
+            }
+            weight
+        },
+        None => mask.count_ones() as usize,
+    };
+
+    // indexed by the subsets of the measured nodes (as bit mask over `measured`): the
+    // initialized nodes (as bit mask over all nodes), and the optimal memory to measure
+    // the subset
+    let num_subsets = 1 << measured.len();
+    let mut initialized = vec![0u64; num_subsets];
+    let mut best = vec![usize::MAX; num_subsets];
+    best[0] = 0;
+    for subset in 1..num_subsets {
+        let lowest = subset.trailing_zeros() as usize;
+        initialized[subset] = initialized[subset & (subset - 1)] | neighborhoods[lowest];
+        let mut subset_bits = 0u64;
+        let mut rest = subset;
+        while rest != 0 {
+            subset_bits |= 1 << measured[rest.trailing_zeros() as usize];
+            rest &= rest - 1;
+        }
+        let mut rest = subset;
+        while rest != 0 {
+            let last = rest.trailing_zeros() as usize;
+            rest &= rest - 1;
+            let before = subset & !(1 << last);
+            let before_bits = subset_bits & !(1 << measured[last]);
+            let memory =
+                mask_weight((initialized[before] | neighborhoods[last]) & !before_bits);
+            best[subset] = cmp::min(best[subset], cmp::max(best[before], memory));
+        }
+    }
+    Some(best[num_subsets - 1])
+}
+
+/// Lower bound on the space-time volume: every initialized node is in memory for at
+/// least one step. The `outputs` have to contain all nodes that are not measured.
+pub fn volume(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+) -> usize {
+    let is_output = is_output(spacial_graph.len(), outputs);
+    (0..spacial_graph.len())
+        .filter(|bit| !is_output[*bit] || is_initialized(spacial_graph, &is_output, *bit))
+        .map(|bit| weight(weights, bit))
+        .sum()
+}
+
+/// Lower bound on the qubit idle time: a node which depends on one of its neighbors is
+/// initialized when the neighbor is measured at the latest, and it has to wait at least
+/// one step plus the latency, and the outputs stay in memory after their
+/// initialization. The `outputs` have to contain all nodes that are not measured.
+pub fn idle(
+    spacial_graph: RefSpacialGraph,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+) -> usize {
+    let num_bits = spacial_graph.len();
+    let is_output = is_output(num_bits, outputs);
+    let mut idle = (0..num_bits)
+        .map(|bit| {
+            (is_output[bit] && is_initialized(spacial_graph, &is_output, bit)) as usize
+        })
+        .collect::<Vec<_>>();
+    for (dep, bit, latency) in dependency_edges(&is_output, time_ordering, constraints) {
+        if spacial_graph[bit].contains(&dep) {
+            idle[bit] = cmp::max(idle[bit], 1 + latency);
+        }
+    }
+    idle.into_iter().sum()
+}
+
+fn weight(weights: Option<&[usize]>, bit: usize) -> usize {
+    match weights {
+        Some(weights) => weights[bit],
+        None => 1,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+
+    use super::*;
+    use crate::scheduler::space::SpacialGraph;
+
+    // 0 - 1 - 2 - 3 - 4, with a chain of dependencies 0 -> 2 -> 4
+    fn problem() -> (SpacialGraph, PartialOrderGraph) {
+        (
+            vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]],
+            vec![
+                vec![(0, vec![]), (1, vec![]), (3, vec![])],
+                vec![(2, vec![0])],
+                vec![(4, vec![2])],
+            ],
+        )
+    }
+
+    #[test]
+    fn time_bound() {
+        let (graph, ordering) = problem();
+        let bound = |constraints: &Constraints| time(&graph, &[], &ordering, constraints);
+        assert_eq!(bound(&Constraints::default()), 3);
+        assert_eq!(
+            bound(&Constraints {
+                latency: 1,
+                ..Default::default()
+            }),
+            5
+        );
+        assert_eq!(
+            bound(&Constraints {
+                edge_latencies: vec![(0, 2, 2)],
+                ..Default::default()
+            }),
+            5
+        );
+        assert_eq!(
+            bound(&Constraints {
+                max_step_size: Some(1),
+                ..Default::default()
+            }),
+            5
+        );
+        // 3 is a neighbor of 4, and 4 depends on 2 (which is also a neighbor of 3)
+        assert_eq!(
+            bound(&Constraints {
+                release_times: vec![(4, 3)],
+                ..Default::default()
+            }),
+            4
+        );
+    }
+
+    #[test]
+    fn space_bounds() {
+        let (graph, _) = problem();
+        // a path has degeneracy 1
+        assert_eq!(degeneracy(&graph, None, &[]), 2);
+        // measuring the path from one end only needs 2 nodes in memory
+        assert_eq!(order(&graph, None, &[]), Some(2));
+        assert_eq!(space(&graph, None, &[2, 3, 4]), 3);
+        assert_eq!(degeneracy(&graph, Some(&[1, 1, 5, 1, 1]), &[]), 6);
+
+        // a triangle with a pendant node
+        let graph = vec![vec![1, 2, 3], vec![0, 2], vec![0, 1], vec![0]];
+        assert_eq!(degeneracy(&graph, None, &[]), 3);
+        assert_eq!(order(&graph, None, &[]), Some(3));
+
+        // a 3x3 grid
+        let graph = vec![
+            vec![1, 3],
+            vec![0, 2, 4],
+            vec![1, 5],
+            vec![0, 4, 6],
+            vec![1, 3, 5, 7],
+            vec![2, 4, 8],
+            vec![3, 7],
+            vec![4, 6, 8],
+            vec![5, 7],
+        ];
+        assert_eq!(degeneracy(&graph, None, &[]), 3);
+        assert_eq!(order(&graph, None, &[]), Some(4));
+    }
+
+    #[test]
+    fn all_bounds() {
+        let (graph, ordering) = problem();
+        assert_eq!(
+            bounds(&graph, None, &[], &ordering, &Constraints::default()),
+            Bounds {
+                time: 3,
+                space: 2,
+                volume: 5,
+                idle: 0
+            }
+        );
+
+        // now the dependencies are between neighbors
+        let ordering = vec![
+            vec![(0, vec![]), (3, vec![])],
+            vec![(1, vec![0]), (4, vec![3])],
+            vec![(2, vec![1])],
+        ];
+        assert_eq!(idle(&graph, &[], &ordering, &Constraints::default()), 3);
+        let constraints = Constraints {
+            latency: 1,
+            ..Default::default()
+        };
+        assert_eq!(idle(&graph, &[], &ordering, &constraints), 6);
+        // the output 4 stays in memory
+        assert_eq!(idle(&graph, &[4], &ordering, &constraints), 5);
+
+        // the heavy output 2 is never initialized, so it does not count for the volume
+        let graph = vec![vec![1], vec![0], vec![]];
+        let ordering = vec![vec![(0, vec![]), (1, vec![])]];
+        let weights = [1, 1, 5];
+        let bounds = bounds(&graph, Some(&weights), &[2], &ordering, &Default::default());
+        assert_eq!((bounds.space, bounds.volume), (5, 2));
+        // the outputs 2 and 3 are only neighbors of each other
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        assert_eq!(idle(&graph, &[2, 3], &ordering, &Default::default()), 0);
+    }
+}
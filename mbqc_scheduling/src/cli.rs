@@ -1,4 +1,5 @@
-use clap::{value_parser, Arg, ArgAction, Command};
+use clap::{Arg, ArgAction, ArgMatches, Command, value_parser};
+use mbqc_scheduling::interface::{DataKind, Objective, Portfolio, labels::NodeLabel};
 
 const SPACIAL_GRAPH: &str = "spacial_graph";
 const SPACIAL_GRAPH_FORMAT: &str = "spacial_graph_format";
@@ -7,124 +8,1012 @@ const DEPENDENCY_GRAPH_FORMAT: &str = "dependency_graph_format";
 const PATHS: &str = "paths";
 const PATHS_FORMAT: &str = "paths_format";
 const SEARCH: &str = "search";
+const AUTO: &str = "auto";
+const PORTFOLIO: &str = "portfolio";
 const TIMEOUT: &str = "timeout";
+const STALL_TIMEOUT: &str = "stall_timeout";
+const FIRST_SOLUTION_TIMEOUT: &str = "first_solution_timeout";
+const NODE_BUDGET: &str = "node_budget";
+const CHECKPOINT: &str = "checkpoint";
 const NTHREADS: &str = "nthreads";
 const PROBABILISTIC: &str = "accept_func";
+const ACCEPT_CONFIG: &str = "accept_config";
+const CUTOFF: &str = "cutoff";
+const LIN_NUM_TOTAL_NODES_EXP: &str = "lin_num_total_nodes_exp";
+const EXP_NUM_TOTAL_NODES_EXP: &str = "exp_num_total_nodes_exp";
+const EXP_NUM_REMAINING_NODES_EXP: &str = "exp_num_remaining_nodes_exp";
+const EXP_DIFF_EXP: &str = "exp_diff_exp";
+const EXP_NUM_MEASURED_NODES_EXP: &str = "exp_num_measured_nodes_exp";
 const TASK_BOUND: &str = "task_bound";
+const MAX_STEP_SIZE: &str = "max_step_size";
+const LATENCY: &str = "latency";
+const RELEASE_TIMES: &str = "release_times";
+const DEADLINES: &str = "deadlines";
+const CONSTRAINTS: &str = "constraints";
+const OBJECTIVE: &str = "objective";
+const STATS: &str = "stats";
+const METADATA: &str = "metadata";
+const BOUNDS: &str = "bounds";
+const ESTIMATE: &str = "estimate";
+const ESTIMATE_FORMAT: &str = "estimate_format";
+const PROBES: &str = "probes";
+const SEED: &str = "seed";
+const OUTPUT: &str = "output";
+const EVALUATED: &str = "evaluated";
+const EVALUATED_FORMAT: &str = "evaluated_format";
+const INPUT: &str = "input";
+const INPUT_FORMAT: &str = "input_format";
+const OUTPUT_FORMAT: &str = "output_format";
+const KIND: &str = "kind";
+const REPEAT: &str = "repeat";
+const LABELS: &str = "labels";
+const FRAMES: &str = "frames";
+const EDGES: &str = "edges";
+const DECOMPOSE: &str = "decompose";
+const REDUCE: &str = "reduce";
+const WINDOW: &str = "window";
+const COMMIT: &str = "commit";
+
+// the subcommands
+const SCHEDULE: &str = "schedule";
+const VERIFY: &str = "verify";
+const EVALUATE: &str = "evaluate";
+const CONVERT: &str = "convert";
+const IMPORT: &str = "import";
+const INSTANCE_STATS: &str = "stats";
+const BENCH: &str = "bench";
 
 fn build() -> Command {
     Command::new(env!("CARGO_PKG_NAME"))
         .version(env!("CARGO_PKG_VERSION"))
         .author(env!("CARGO_PKG_AUTHORS"))
         .about(env!("CARGO_PKG_DESCRIPTION"))
-        .long_about("Compare the documentation of interface::run for more information.")
+        .long_about(
+            "Compare the documentation of interface::run for more information. Without \
+             a subcommand, the arguments are the same as for the schedule subcommand.\n\n\
+             The serialization formats are serde_json (or json), bincode, msgpack, cbor \
+             and yaml, optionally followed by +gzip or +zstd for compression, or auto to \
+             detect the format from the file extension (which is the default for \
+             optional formats); cf. the documentation of interface::format. The file - \
+             stands for stdin or stdout. Spacial graphs can also be given in the graph \
+             formats edge-list, dimacs, graph6, sparse6, graphml and adjacency-matrix, \
+             cf. the documentation of interface::import. If the nodes of the spacial \
+             graph have labels, i.e., it is a list of [label, neighbors] pairs, the \
+             other inputs and the paths use the same labels, cf. the documentation of \
+             interface::labels.",
+        )
         .arg_required_else_help(true)
-        .arg(
-            Arg::new(SPACIAL_GRAPH)
-                .value_name("SPACIAL_GRAPH")
-                .help("The spacial_graph's file name")
-                .required(true),
+        .args_conflicts_with_subcommands(true)
+        .subcommand_negates_reqs(true)
+        // backwards compatibility: no subcommand means schedule
+        .args(schedule_args())
+        .subcommand(
+            Command::new(SCHEDULE)
+                .about("Search for the best paths (the default without a subcommand)")
+                .long_about(
+                    "Compare the documentation of interface::run for more information.",
+                )
+                .args(schedule_args()),
         )
-        .arg(
-            Arg::new(SPACIAL_GRAPH_FORMAT)
-                .value_name("SPACIAL_GRAPH_FORMAT")
-                .help("The spacial_graph's serialization format")
-                .required(true),
+        .subcommand(
+            Command::new(ESTIMATE)
+                .about(
+                    "Estimate the size of the search tree and the runtime of the search",
+                )
+                .long_about(
+                    "Compare the documentation of estimate::estimate for more \
+                     information.",
+                )
+                .args(graph_args())
+                .arg(
+                    Arg::new(ESTIMATE)
+                        .value_name("ESTIMATE")
+                        .help("The estimate's file name")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new(ESTIMATE_FORMAT)
+                        .value_name("ESTIMATE_FORMAT")
+                        .help("The estimate's serialization format")
+                        .required(true),
+                )
+                .args(accept_func_args())
+                .args(constraint_args())
+                .arg(objective_arg())
+                .arg(
+                    Arg::new(PROBES)
+                        .value_name("PROBES")
+                        .long("probes")
+                        .help("The number of random probes")
+                        .default_value("1000")
+                        .value_parser(value_parser!(usize)),
+                )
+                .arg(seed_arg()),
         )
-        .arg(
-            Arg::new(DEPENDENCY_GRAPH)
-                .value_name("DEPENDENCY_GRAPH")
-                .help("The dependency_graph's file name")
-                .required(true),
+        .subcommand(
+            Command::new(VERIFY)
+                .about(
+                    "Check whether the paths are valid, printing their costs or the \
+                     violations; exits with an error if one is invalid",
+                )
+                .args(graph_args())
+                .args(paths_args())
+                .args(constraint_args()),
         )
-        .arg(
-            Arg::new(DEPENDENCY_GRAPH_FORMAT)
-                .value_name("DEPENDENCY_GRAPH_FORMAT")
-                .help("The dependency_graph's serialization format")
-                .required(true),
+        .subcommand(
+            Command::new(EVALUATE)
+                .about(
+                    "Recalculate the costs of the paths and compare them with the lower \
+                     bounds",
+                )
+                .args(graph_args())
+                .args(paths_args())
+                .arg(
+                    Arg::new(EVALUATED)
+                        .value_name("EVALUATED")
+                        .help("The evaluated paths' file name")
+                        .required(true),
+                )
+                .arg(
+                    Arg::new(EVALUATED_FORMAT)
+                        .value_name("EVALUATED_FORMAT")
+                        .help("The evaluated paths' serialization format")
+                        .required(true),
+                )
+                .args(constraint_args())
+                .arg(objective_arg()),
         )
-        .arg(
-            Arg::new(PATHS)
-                .value_name("PATHS")
-                .help("The paths' file name")
-                .required(true),
+        .subcommand(
+            Command::new(CONVERT)
+                .about("Convert a file into another serialization format")
+                .args(input_output_args())
+                .arg(
+                    Arg::new(KIND)
+                        .value_name("KIND")
+                        .short('k')
+                        .long("kind")
+                        .help("What kind of data the file contains")
+                        .required(true)
+                        .value_parser([
+                            "spacial-graph",
+                            "dependency-graph",
+                            "paths",
+                            "constraints",
+                        ]),
+                ),
         )
-        .arg(
-            Arg::new(PATHS_FORMAT)
-                .value_name("PATHS_FORMAT")
-                .help("The paths' serialization format")
-                .required(true),
+        .subcommand(
+            Command::new(IMPORT)
+                .about(
+                    "Import a spacial graph from another graph format, e.g., an edge \
+                     list or graph6",
+                )
+                .long_about(
+                    "Compare the documentation of interface::import for more \
+                     information.",
+                )
+                .args(input_output_args())
+                .arg(
+                    Arg::new(LABELS)
+                        .value_names(["LABELS", "LABELS_FORMAT"])
+                        .long("labels")
+                        .help(
+                            "A file, and optionally its serialization format, to write \
+                             the original labels of the nodes to",
+                        )
+                        .num_args(1..=2),
+                ),
         )
-        .arg(
-            Arg::new(SEARCH)
-                .short('s')
-                .long("search")
-                .help("Search for all best paths")
-                .action(ArgAction::SetTrue),
+        .subcommand(
+            Command::new(INSTANCE_STATS)
+                .about("Statistics about the instance, e.g., its size and lower bounds")
+                .long_about(
+                    "Compare the documentation of instance::InstanceStats for more \
+                     information.",
+                )
+                .args(graph_args())
+                .args(constraint_args())
+                .arg(output_arg()),
         )
-        .arg(
-            Arg::new(TIMEOUT)
-                .value_name("TIMEOUT")
-                .short('t')
-                .long("timeout")
-                .help("A timeout for the search")
-                .value_parser(value_parser!(u32)),
+        .subcommand(
+            Command::new(BENCH)
+                .about("Measure the runtime of the scheduling")
+                .args(graph_args())
+                .args(search_args())
+                .arg(
+                    Arg::new(REPEAT)
+                        .value_name("REPEAT")
+                        .short('r')
+                        .long("repeat")
+                        .help("How often to run the scheduling")
+                        .default_value("3")
+                        .value_parser(value_parser!(u32).range(1..)),
+                )
+                .arg(output_arg()),
         )
-        .arg(
-            Arg::new(NTHREADS)
-                .value_name("NTHREADS")
-                .short('n')
-                .long("nthreads")
-                .help("The number of threads to use for the search")
-                .default_value("1")
-                .value_parser(value_parser!(u16)),
+}
+
+// the arguments of the schedule subcommand, which are also the top level arguments
+fn schedule_args() -> Vec<Arg> {
+    let mut args = graph_args().to_vec();
+    args.extend([
+        Arg::new(PATHS)
+            .value_name("PATHS")
+            .help("The paths' file name")
+            .required(true),
+        Arg::new(PATHS_FORMAT)
+            .value_name("PATHS_FORMAT")
+            .help("The paths' serialization format")
+            .required(true),
+    ]);
+    args.extend(search_args());
+    args.extend([
+        Arg::new(CHECKPOINT)
+            .value_name("CHECKPOINT")
+            .long("checkpoint")
+            .help(
+                "The interval in seconds in which the paths found so far are written to \
+                 the paths' file during the search; 0 disables it",
+            )
+            .default_value("60")
+            .value_parser(value_parser!(u32)),
+        Arg::new(STATS)
+            .value_names(["STATS", "STATS_FORMAT"])
+            .long("stats")
+            .help(
+                "A file, and optionally its serialization format, to write statistics \
+                 about the search to",
+            )
+            .num_args(1..=2),
+        Arg::new(METADATA)
+            .value_names(["METADATA", "METADATA_FORMAT"])
+            .long("metadata")
+            .help(
+                "A file, and optionally its serialization format, to write metadata \
+                 about the run to, e.g., the chosen strategy",
+            )
+            .num_args(1..=2),
+        Arg::new(BOUNDS)
+            .value_names(["BOUNDS", "BOUNDS_FORMAT"])
+            .long("bounds")
+            .help(
+                "A file, and optionally its serialization format, to write lower bounds \
+                 on the costs to",
+            )
+            .num_args(1..=2),
+    ]);
+    args
+}
+
+// how to search, shared by the schedule and bench subcommands
+fn search_args() -> Vec<Arg> {
+    let mut args = vec![
+        Arg::new(SEARCH)
+            .short('s')
+            .long("search")
+            .help("Search for all best paths")
+            .action(ArgAction::SetTrue),
+        Arg::new(AUTO)
+            .short('a')
+            .long("auto")
+            .help(
+                "Choose automatically whether and how to search, using the timeout as \
+                 time budget and the number of threads as maximum",
+            )
+            .conflicts_with(SEARCH)
+            .action(ArgAction::SetTrue),
+        Arg::new(PORTFOLIO)
+            .value_name("STRATEGIES")
+            .long("portfolio")
+            .help(
+                "Run a portfolio of strategies in parallel, each in its own thread, \
+                 e.g., exact,probabilistic,greedy,greedy (the exact search is run at \
+                 most once); the probabilistic strategies use the accept function \
+                 below, each further one a more restrictive version of it, and the \
+                 number of threads is ignored",
+            )
+            .conflicts_with_all([SEARCH, AUTO])
+            .value_delimiter(',')
+            .value_parser(["exact", "probabilistic", "greedy"]),
+        Arg::new(DECOMPOSE)
+            .short('d')
+            .long("decompose")
+            .help(
+                "Schedule the independent components of the instance separately and \
+                 compose their paths; the composed paths are not necessarily optimal",
+            )
+            .action(ArgAction::SetTrue),
+        Arg::new(REDUCE)
+            .long("reduce")
+            .help(
+                "Shrink the instance with reduction rules before searching; this does \
+                 not affect the optimality",
+            )
+            .conflicts_with(DECOMPOSE)
+            .action(ArgAction::SetTrue),
+        Arg::new(WINDOW)
+            .value_name("LAYERS")
+            .long("window")
+            .help(
+                "Schedule the instance window by window, searching that many layers of \
+                 the dependency graph at a time; the path is not necessarily optimal, \
+                 and the mode is ignored",
+            )
+            .conflicts_with_all([DECOMPOSE, REDUCE])
+            .value_parser(value_parser!(u32).range(1..)),
+        Arg::new(COMMIT)
+            .value_name("STEPS")
+            .long("commit")
+            .help("How many steps of each window's path to commit before sliding forward")
+            .default_value("1")
+            .value_parser(value_parser!(u32).range(1..)),
+        Arg::new(TIMEOUT)
+            .value_name("TIMEOUT")
+            .short('t')
+            .long("timeout")
+            .help("A timeout for the search")
+            .value_parser(value_parser!(u32)),
+        Arg::new(STALL_TIMEOUT)
+            .value_name("STALL_TIMEOUT")
+            .long("stall-timeout")
+            .help("Stop the search if no better path has been found for that long")
+            .value_parser(value_parser!(u32)),
+        Arg::new(FIRST_SOLUTION_TIMEOUT)
+            .value_name("FIRST_SOLUTION_TIMEOUT")
+            .long("first-solution-timeout")
+            .help(
+                "Stop the search if no path has been found after that long, taking the \
+                 time-optimal path instead",
+            )
+            .value_parser(value_parser!(u32)),
+        Arg::new(NODE_BUDGET)
+            .value_name("NODE_BUDGET")
+            .long("node-budget")
+            .help("Stop the search after exploring that many nodes of the search tree")
+            .value_parser(value_parser!(u64)),
+        Arg::new(NTHREADS)
+            .value_name("NTHREADS")
+            .short('n')
+            .long("nthreads")
+            .help("The number of threads to use for the search")
+            .default_value("1")
+            .value_parser(value_parser!(u16)),
+        Arg::new(TASK_BOUND)
+            .value_name("TASK_BOUND")
+            .short('b')
+            .long("task-bound")
+            .help("A bound on the possible number of tasks")
+            .value_parser(value_parser!(u32)),
+    ];
+    args.extend(accept_func_args());
+    args.push(seed_arg());
+    args.extend(constraint_args());
+    args.push(objective_arg());
+    args
+}
+
+// the input graphs, shared by the subcommands
+fn graph_args() -> [Arg; 6] {
+    [
+        Arg::new(SPACIAL_GRAPH)
+            .value_name("SPACIAL_GRAPH")
+            .help("The spacial_graph's file name")
+            .required(true),
+        Arg::new(SPACIAL_GRAPH_FORMAT)
+            .value_name("SPACIAL_GRAPH_FORMAT")
+            .help("The spacial_graph's serialization format")
+            .required(true),
+        Arg::new(DEPENDENCY_GRAPH)
+            .value_name("DEPENDENCY_GRAPH")
+            .help("The dependency_graph's file name")
+            .required(true),
+        Arg::new(DEPENDENCY_GRAPH_FORMAT)
+            .value_name("DEPENDENCY_GRAPH_FORMAT")
+            .help("The dependency_graph's serialization format")
+            .required(true),
+        Arg::new(FRAMES)
+            .value_names(["MAP", "MAP_FORMAT"])
+            .long("frames")
+            .help(
+                "Read the dependency_graph's file as serialized Pauli tracker frames \
+                 and compute the dependency graph from them; MAP is a file, and \
+                 optionally its serialization format, with the list of the measured \
+                 qubits, one for each frame; cf. the documentation of interface::frames",
+            )
+            .num_args(1..=2),
+        Arg::new(EDGES)
+            .long("edges")
+            .help(
+                "Read the dependency_graph's file as a list of [before, after] \
+                 dependency edges and compute the layered dependency graph from them; \
+                 cf. the documentation of interface::edges",
+            )
+            .conflicts_with(FRAMES)
+            .action(ArgAction::SetTrue),
+    ]
+}
+
+// the input paths of the verify and evaluate subcommands
+fn paths_args() -> [Arg; 2] {
+    [
+        Arg::new(PATHS)
+            .value_name("PATHS")
+            .help("The paths' file name")
+            .required(true),
+        Arg::new(PATHS_FORMAT)
+            .value_name("PATHS_FORMAT")
+            .help("The paths' serialization format")
+            .required(true),
+    ]
+}
+
+// the input and output files of the convert and import subcommands
+fn input_output_args() -> [Arg; 4] {
+    [
+        Arg::new(INPUT)
+            .value_name("INPUT")
+            .help("The input file name")
+            .required(true),
+        Arg::new(INPUT_FORMAT)
+            .value_name("INPUT_FORMAT")
+            .help("The input's format")
+            .required(true),
+        Arg::new(OUTPUT)
+            .value_name("OUTPUT")
+            .help("The output file name")
+            .required(true),
+        Arg::new(OUTPUT_FORMAT)
+            .value_name("OUTPUT_FORMAT")
+            .help("The output's serialization format")
+            .required(true),
+    ]
+}
+
+// the accept function of the probabilistic search, cf. probabilistic::AcceptFunc
+fn accept_func_args() -> Vec<Arg> {
+    let parameter = |id: &'static str, value_name: &'static str, long: &'static str| {
+        Arg::new(id)
+            .value_name(value_name)
+            .long(long)
+            .help(format!(
+                "The {id} parameter of the parametrized accept function, cf. \
+                 probabilistic::HeavysideParameters; implies --probablistic, and the \
+                 parameters that are not set are taken from the accept config or are \
+                 the ones of the builtin accept function"
+            ))
+            .allow_negative_numbers(true)
+    };
+    vec![
+        Arg::new(PROBABILISTIC)
+            .short('p')
+            .long("probablistic")
+            .help("Whether to perform a probabilistically filtered serach")
+            .action(ArgAction::SetTrue),
+        Arg::new(ACCEPT_CONFIG)
+            .value_names(["ACCEPT_CONFIG", "ACCEPT_CONFIG_FORMAT"])
+            .long("accept-config")
+            .help(
+                "A file, and optionally its serialization format, with the accept \
+                 function; implies --probablistic, and the parameters are overwritten \
+                 by the according options below",
+            )
+            .num_args(1..=2),
+        parameter(CUTOFF, "CUTOFF", "cutoff").value_parser(value_parser!(f64)),
+        parameter(
+            LIN_NUM_TOTAL_NODES_EXP,
+            "LIN_NUM_TOTAL_NODES_EXP",
+            "lin-num-total-nodes-exp",
+        )
+        .value_parser(value_parser!(i32)),
+        parameter(
+            EXP_NUM_TOTAL_NODES_EXP,
+            "EXP_NUM_TOTAL_NODES_EXP",
+            "exp-num-total-nodes-exp",
         )
-        .arg(
-            Arg::new(PROBABILISTIC)
-                .short('p')
-                .long("probablistic")
-                .help("Whether to perform a probabilistically filtered serach")
-                .action(ArgAction::SetTrue),
+        .value_parser(value_parser!(i32)),
+        parameter(
+            EXP_NUM_REMAINING_NODES_EXP,
+            "EXP_NUM_REMAINING_NODES_EXP",
+            "exp-num-remaining-nodes-exp",
         )
-        .arg(
-            Arg::new(TASK_BOUND)
-                .value_name("TASK_BOUND")
-                .short('b')
-                .long("task-bound")
-                .help("A bound on the possible number of tasks")
-                .value_parser(value_parser!(u32)),
+        .value_parser(value_parser!(i32)),
+        parameter(EXP_DIFF_EXP, "EXP_DIFF_EXP", "exp-diff-exp")
+            .value_parser(value_parser!(i32)),
+        parameter(
+            EXP_NUM_MEASURED_NODES_EXP,
+            "EXP_NUM_MEASURED_NODES_EXP",
+            "exp-num-measured-nodes-exp",
         )
+        .value_parser(value_parser!(i32)),
+    ]
 }
 
-pub struct Args {
-    pub spacial_graph: String,
-    pub spacial_graph_format: String,
-    pub dependency_graph: String,
-    pub dependency_graph_format: String,
-    pub paths: String,
-    pub paths_format: String,
+fn seed_arg() -> Arg {
+    Arg::new(SEED)
+        .value_name("SEED")
+        .long("seed")
+        .help("A seed for the random number generator")
+        .value_parser(value_parser!(u64))
+}
+
+fn output_arg() -> Arg {
+    Arg::new(OUTPUT)
+        .value_names(["OUTPUT", "OUTPUT_FORMAT"])
+        .long("output")
+        .help(
+            "A file, and optionally its serialization format, to write the result to; \
+             if not given, it is printed as JSON",
+        )
+        .num_args(1..=2)
+}
+
+// the constraints, shared by the subcommands
+fn constraint_args() -> [Arg; 5] {
+    [
+        Arg::new(MAX_STEP_SIZE)
+            .value_name("MAX_STEP_SIZE")
+            .short('m')
+            .long("max-step-size")
+            .help("The maximum number of qubits that are measured in one step")
+            .value_parser(value_parser!(u32).range(1..)),
+        Arg::new(LATENCY)
+            .value_name("LATENCY")
+            .short('l')
+            .long("latency")
+            .help("The classical feed-forward latency between dependent measurements")
+            .value_parser(value_parser!(u32)),
+        Arg::new(RELEASE_TIMES)
+            .value_name("NODE=STEP")
+            .long("release-time")
+            .help(
+                "The node cannot be measured before the step (counted from 0); can be \
+                 repeated",
+            )
+            .action(ArgAction::Append)
+            .value_parser(node_step),
+        Arg::new(DEADLINES)
+            .value_name("NODE=STEP")
+            .long("deadline")
+            .help(
+                "The node has to be measured in the step (counted from 0) at the \
+                 latest; can be repeated",
+            )
+            .action(ArgAction::Append)
+            .value_parser(node_step),
+        Arg::new(CONSTRAINTS)
+            .value_names(["CONSTRAINTS", "CONSTRAINTS_FORMAT"])
+            .short('c')
+            .long("constraints")
+            .help(
+                "A file, and optionally its serialization format, with additional \
+                 constraints; they are overwritten by the according options above, \
+                 respectively the release times and deadlines above are added",
+            )
+            .num_args(1..=2),
+    ]
+}
+
+// a `NODE=STEP` pair of a release time or a deadline
+fn node_step(value: &str) -> Result<(NodeLabel, usize), String> {
+    let (node, step) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected NODE=STEP, got \"{value}\""))?;
+    let Ok(node) = node.parse();
+    let step = step
+        .parse()
+        .map_err(|err| format!("invalid step \"{step}\": {err}"))?;
+    Ok((node, step))
+}
+
+fn objective_arg() -> Arg {
+    Arg::new(OBJECTIVE)
+        .value_name("OBJECTIVE")
+        .short('o')
+        .long("objective")
+        .help("The cost that is optimized, besides the time, when searching")
+        .default_value("space")
+        .value_parser(["space", "volume", "idle"])
+}
+
+// each a file together with its serialization format
+#[derive(Debug, PartialEq)]
+pub struct GraphArgs {
+    pub spacial_graph: (String, String),
+    pub dependency_graph: (String, String),
+    pub frames_map: Option<(String, String)>,
+    pub edges: bool,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ConstraintArgs {
+    pub max_step_size: Option<u32>,
+    pub latency: Option<u32>,
+    pub release_times: Vec<(NodeLabel, usize)>,
+    pub deadlines: Vec<(NodeLabel, usize)>,
+    pub constraints: Option<(String, String)>,
+}
+
+// the parameters are the fields of probabilistic::HeavysideParameters
+#[derive(Debug, PartialEq)]
+pub struct AcceptFuncArgs {
+    pub probabilistic: bool,
+    pub config: Option<(String, String)>,
+    pub cutoff: Option<f64>,
+    pub lin_num_total_nodes_exp: Option<i32>,
+    pub exp_num_total_nodes_exp: Option<i32>,
+    pub exp_num_remaining_nodes_exp: Option<i32>,
+    pub exp_diff_exp: Option<i32>,
+    pub exp_num_measured_nodes_exp: Option<i32>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SearchArgs {
     pub search: bool,
+    pub auto: bool,
+    pub portfolio: Option<Portfolio>,
+    pub decompose: bool,
+    pub reduce: bool,
+    pub window: Option<u32>,
+    pub commit: u32,
     pub timeout: Option<u32>,
+    pub stall_timeout: Option<u32>,
+    pub first_solution_timeout: Option<u32>,
+    pub node_budget: Option<u64>,
     pub nthreads: u16,
-    pub probabilistic: bool,
     pub task_bound: Option<u32>,
+    pub accept_func: AcceptFuncArgs,
+    pub seed: Option<u64>,
+    pub constraints: ConstraintArgs,
+    pub objective: Objective,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Args {
+    pub graphs: GraphArgs,
+    pub paths: (String, String),
+    pub search: SearchArgs,
+    pub checkpoint: u32,
+    pub stats: Option<(String, String)>,
+    pub metadata: Option<(String, String)>,
+    pub bounds: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EstimateArgs {
+    pub graphs: GraphArgs,
+    pub estimate: (String, String),
+    pub accept_func: AcceptFuncArgs,
+    pub constraints: ConstraintArgs,
+    pub objective: Objective,
+    pub probes: usize,
+    pub seed: Option<u64>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct VerifyArgs {
+    pub graphs: GraphArgs,
+    pub paths: (String, String),
+    pub constraints: ConstraintArgs,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct EvaluateArgs {
+    pub graphs: GraphArgs,
+    pub paths: (String, String),
+    pub evaluated: (String, String),
+    pub constraints: ConstraintArgs,
+    pub objective: Objective,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct ConvertArgs {
+    pub input: (String, String),
+    pub output: (String, String),
+    pub kind: DataKind,
 }
 
-pub fn parse() -> Args {
-    let mut args = build().get_matches();
+#[derive(Debug, PartialEq)]
+pub struct ImportArgs {
+    pub input: (String, String),
+    pub output: (String, String),
+    pub labels: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct StatsArgs {
+    pub graphs: GraphArgs,
+    pub constraints: ConstraintArgs,
+    pub output: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct BenchArgs {
+    pub graphs: GraphArgs,
+    pub search: SearchArgs,
+    pub repeat: u32,
+    pub output: Option<(String, String)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum Subcommand {
+    Schedule(Args),
+    Estimate(EstimateArgs),
+    Verify(VerifyArgs),
+    Evaluate(EvaluateArgs),
+    Convert(ConvertArgs),
+    Import(ImportArgs),
+    Stats(StatsArgs),
+    Bench(BenchArgs),
+}
+
+pub fn parse() -> Subcommand {
+    from_matches(build().get_matches())
+}
+
+fn from_matches(mut args: ArgMatches) -> Subcommand {
+    let Some((subcommand, mut args)) = args.remove_subcommand() else {
+        return Subcommand::Schedule(remove_schedule(&mut args));
+    };
+    let args = &mut args;
+    match subcommand.as_str() {
+        SCHEDULE => Subcommand::Schedule(remove_schedule(args)),
+        ESTIMATE => Subcommand::Estimate(EstimateArgs {
+            graphs: remove_graphs(args),
+            estimate: remove_positional(args, ESTIMATE, ESTIMATE_FORMAT),
+            accept_func: remove_accept_func(args),
+            constraints: remove_constraints(args),
+            objective: remove_objective(args),
+            probes: args.remove_one(PROBES).expect("has default"),
+            seed: args.remove_one::<u64>(SEED),
+        }),
+        VERIFY => Subcommand::Verify(VerifyArgs {
+            graphs: remove_graphs(args),
+            paths: remove_positional(args, PATHS, PATHS_FORMAT),
+            constraints: remove_constraints(args),
+        }),
+        EVALUATE => Subcommand::Evaluate(EvaluateArgs {
+            graphs: remove_graphs(args),
+            paths: remove_positional(args, PATHS, PATHS_FORMAT),
+            evaluated: remove_positional(args, EVALUATED, EVALUATED_FORMAT),
+            constraints: remove_constraints(args),
+            objective: remove_objective(args),
+        }),
+        CONVERT => Subcommand::Convert(ConvertArgs {
+            input: remove_positional(args, INPUT, INPUT_FORMAT),
+            output: remove_positional(args, OUTPUT, OUTPUT_FORMAT),
+            kind: match args.remove_one::<String>(KIND).expect("is required").as_str() {
+                "spacial-graph" => DataKind::SpacialGraph,
+                "dependency-graph" => DataKind::DependencyGraph,
+                "paths" => DataKind::Paths,
+                "constraints" => DataKind::Constraints,
+                _ => unreachable!("restricted by the value parser"),
+            },
+        }),
+        IMPORT => Subcommand::Import(ImportArgs {
+            input: remove_positional(args, INPUT, INPUT_FORMAT),
+            output: remove_positional(args, OUTPUT, OUTPUT_FORMAT),
+            labels: remove_file(args, LABELS),
+        }),
+        INSTANCE_STATS => Subcommand::Stats(StatsArgs {
+            graphs: remove_graphs(args),
+            constraints: remove_constraints(args),
+            output: remove_file(args, OUTPUT),
+        }),
+        BENCH => Subcommand::Bench(BenchArgs {
+            graphs: remove_graphs(args),
+            search: remove_search(args),
+            repeat: args.remove_one(REPEAT).expect("has default"),
+            output: remove_file(args, OUTPUT),
+        }),
+        _ => unreachable!("unknown subcommand {subcommand}"),
+    }
+}
+
+fn remove_schedule(args: &mut ArgMatches) -> Args {
     Args {
-        spacial_graph: args.remove_one(SPACIAL_GRAPH).expect("is required"),
-        spacial_graph_format: args.remove_one(SPACIAL_GRAPH_FORMAT).expect("is required"),
-        dependency_graph: args.remove_one(DEPENDENCY_GRAPH).expect("is required"),
-        dependency_graph_format: args
-            .remove_one(DEPENDENCY_GRAPH_FORMAT)
-            .expect("is required"),
-        paths: args.remove_one(PATHS).expect("is required"),
-        paths_format: args.remove_one(PATHS_FORMAT).expect("is required"),
+        graphs: remove_graphs(args),
+        paths: remove_positional(args, PATHS, PATHS_FORMAT),
+        search: remove_search(args),
+        checkpoint: args.remove_one(CHECKPOINT).expect("has default"),
+        stats: remove_file(args, STATS),
+        metadata: remove_file(args, METADATA),
+        bounds: remove_file(args, BOUNDS),
+    }
+}
+
+fn remove_search(args: &mut ArgMatches) -> SearchArgs {
+    SearchArgs {
         search: args.remove_one(SEARCH).expect("has ArgAction"),
+        auto: args.remove_one(AUTO).expect("has ArgAction"),
+        portfolio: remove_portfolio(args),
+        decompose: args.remove_one(DECOMPOSE).expect("has ArgAction"),
+        reduce: args.remove_one(REDUCE).expect("has ArgAction"),
+        window: args.remove_one::<u32>(WINDOW),
+        commit: args.remove_one(COMMIT).expect("has default"),
         timeout: args.remove_one::<u32>(TIMEOUT),
+        stall_timeout: args.remove_one::<u32>(STALL_TIMEOUT),
+        first_solution_timeout: args.remove_one::<u32>(FIRST_SOLUTION_TIMEOUT),
+        node_budget: args.remove_one::<u64>(NODE_BUDGET),
         nthreads: args.remove_one(NTHREADS).expect("has default"),
-        probabilistic: args.remove_one(PROBABILISTIC).expect("has ArgAction"),
         task_bound: args.remove_one::<u32>(TASK_BOUND),
+        accept_func: remove_accept_func(args),
+        seed: args.remove_one::<u64>(SEED),
+        constraints: remove_constraints(args),
+        objective: remove_objective(args),
+    }
+}
+
+fn remove_portfolio(args: &mut ArgMatches) -> Option<Portfolio> {
+    args.remove_many::<String>(PORTFOLIO).map(|strategies| {
+        let mut portfolio = Portfolio {
+            exact: false,
+            probabilistic: 0,
+            greedy: 0,
+        };
+        for strategy in strategies {
+            match strategy.as_str() {
+                "exact" => portfolio.exact = true,
+                "probabilistic" => portfolio.probabilistic += 1,
+                "greedy" => portfolio.greedy += 1,
+                _ => unreachable!("restricted by the value parser"),
+            }
+        }
+        portfolio
+    })
+}
+
+fn remove_accept_func(args: &mut ArgMatches) -> AcceptFuncArgs {
+    AcceptFuncArgs {
+        probabilistic: args.remove_one(PROBABILISTIC).expect("has ArgAction"),
+        config: remove_file(args, ACCEPT_CONFIG),
+        cutoff: args.remove_one::<f64>(CUTOFF),
+        lin_num_total_nodes_exp: args.remove_one::<i32>(LIN_NUM_TOTAL_NODES_EXP),
+        exp_num_total_nodes_exp: args.remove_one::<i32>(EXP_NUM_TOTAL_NODES_EXP),
+        exp_num_remaining_nodes_exp: args.remove_one::<i32>(EXP_NUM_REMAINING_NODES_EXP),
+        exp_diff_exp: args.remove_one::<i32>(EXP_DIFF_EXP),
+        exp_num_measured_nodes_exp: args.remove_one::<i32>(EXP_NUM_MEASURED_NODES_EXP),
+    }
+}
+
+fn remove_graphs(args: &mut ArgMatches) -> GraphArgs {
+    GraphArgs {
+        spacial_graph: remove_positional(args, SPACIAL_GRAPH, SPACIAL_GRAPH_FORMAT),
+        dependency_graph: remove_positional(
+            args,
+            DEPENDENCY_GRAPH,
+            DEPENDENCY_GRAPH_FORMAT,
+        ),
+        frames_map: remove_file(args, FRAMES),
+        edges: args.remove_one(EDGES).expect("has ArgAction"),
+    }
+}
+
+fn remove_constraints(args: &mut ArgMatches) -> ConstraintArgs {
+    ConstraintArgs {
+        max_step_size: args.remove_one::<u32>(MAX_STEP_SIZE),
+        latency: args.remove_one::<u32>(LATENCY),
+        release_times: args
+            .remove_many(RELEASE_TIMES)
+            .map(Iterator::collect)
+            .unwrap_or_default(),
+        deadlines: args.remove_many(DEADLINES).map(Iterator::collect).unwrap_or_default(),
+        constraints: remove_file(args, CONSTRAINTS),
+    }
+}
+
+// a required positional file together with its (required positional) format
+fn remove_positional(
+    args: &mut ArgMatches,
+    file: &str,
+    format: &str,
+) -> (String, String) {
+    (
+        args.remove_one(file).expect("is required"),
+        args.remove_one(format).expect("is required"),
+    )
+}
+
+// a file together with its optional serialization format
+fn remove_file(args: &mut ArgMatches, id: &str) -> Option<(String, String)> {
+    args.remove_many::<String>(id).map(|mut values| {
+        let file = values.next().expect("has at least one value");
+        (file, values.next().unwrap_or_else(|| "auto".to_owned()))
+    })
+}
+
+fn remove_objective(args: &mut ArgMatches) -> Objective {
+    match args.remove_one::<String>(OBJECTIVE).expect("has default").as_str() {
+        "space" => Objective::Space,
+        "volume" => Objective::Volume,
+        "idle" => Objective::Idle,
+        _ => unreachable!("restricted by the value parser"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEDULE_ARGS: [&str; 6] =
+        ["spacial.json", "json", "dependency.json", "json", "paths.json", "json"];
+
+    fn try_parse_from(args: &[&str]) -> Result<Subcommand, clap::Error> {
+        let args = [env!("CARGO_PKG_NAME")].iter().chain(args);
+        Ok(from_matches(build().try_get_matches_from(args)?))
+    }
+
+    fn schedule(args: &[&str]) -> Args {
+        let mut all = vec![SCHEDULE];
+        all.extend(SCHEDULE_ARGS);
+        all.extend(args);
+        match try_parse_from(&all).unwrap() {
+            Subcommand::Schedule(args) => args,
+            subcommand => panic!("not the schedule subcommand: {subcommand:?}"),
+        }
+    }
+
+    #[test]
+    fn command() {
+        build().debug_assert();
+    }
+
+    #[test]
+    fn legacy_schedule() {
+        let options = [
+            "--search",
+            "--timeout",
+            "10",
+            "--seed",
+            "3",
+            "--max-step-size",
+            "2",
+            "--stats",
+            "stats.json",
+        ];
+        let args = schedule(&options);
+        assert_eq!(args.paths, ("paths.json".to_owned(), "json".to_owned()));
+        assert!(args.search.search);
+        assert_eq!(args.search.seed, Some(3));
+        assert_eq!(args.stats, Some(("stats.json".to_owned(), "auto".to_owned())));
+        // without the subcommand
+        let mut legacy = SCHEDULE_ARGS.to_vec();
+        legacy.extend(options);
+        assert_eq!(try_parse_from(&legacy).unwrap(), Subcommand::Schedule(args));
+    }
+
+    #[test]
+    fn portfolio() {
+        let args = schedule(&["--portfolio", "greedy,exact,greedy"]);
+        assert_eq!(
+            args.search.portfolio,
+            Some(Portfolio {
+                exact: true,
+                probabilistic: 0,
+                greedy: 2
+            })
+        );
+        let mut conflicting = SCHEDULE_ARGS.to_vec();
+        conflicting.extend(["--portfolio", "exact", "--search"]);
+        assert!(try_parse_from(&conflicting).is_err());
+    }
+
+    #[test]
+    fn release_times_and_deadlines() {
+        let args = schedule(&[
+            "--release-time",
+            "a=2",
+            "--deadline",
+            "3=1",
+            "--release-time",
+            "4=0",
+        ]);
+        assert_eq!(
+            args.search.constraints.release_times,
+            vec![(NodeLabel::from("a"), 2), (NodeLabel::from(4), 0)]
+        );
+        assert_eq!(args.search.constraints.deadlines, vec![(NodeLabel::from(3), 1)]);
+        let mut invalid = SCHEDULE_ARGS.to_vec();
+        invalid.extend(["--deadline", "3"]);
+        assert!(try_parse_from(&invalid).is_err());
     }
 }
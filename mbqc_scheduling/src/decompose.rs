@@ -0,0 +1,367 @@
+/*!
+Decomposition of a problem instance into independent components, cf.
+[interface::run_decomposed](crate::interface::run_decomposed).
+
+Two nodes are in the same component if they are connected via edges of the spacial
+graph or via dependencies. The components do not influence each other, so they can be
+scheduled separately, on the [restricted](Instance::restrict) instances, and the
+resulting Pareto fronts can then be [composed](compose): the paths of two components
+are measured in parallel, with the second one starting some steps after the first one
+(or the other way around), up to concatenating them. The costs of the composed paths are
+calculated exactly, however, since the composition does not try all possible
+interleavings, the composed paths are not necessarily optimal, even if the paths of the
+components are.
+*/
+
+use std::cmp;
+
+use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+
+use crate::{
+    interface::{Constraints, Path},
+    scheduler::{
+        space::{Objective, RefSpacialGraph, SpacialGraph},
+        time::RefPartialOrderGraph,
+    },
+    search::{self, Steps},
+    verify,
+};
+
+/// Get the independent components of the instance, each given by its sorted nodes, and
+/// sorted by their smallest node. Regarding the parameters, cf.
+/// [interface::run](crate::interface::run).
+///
+/// Components without any node that has to be measured, e.g., isolated outputs, are
+/// merged into the first component with such a node, so that only the components that
+/// actually have to be scheduled are returned (but at least one component, if the
+/// instance is not empty).
+///
+/// # Examples
+/// ```
+/// # use mbqc_scheduling::decompose::components;
+/// // 0 - 1   2 - 3   4, with 3 depending on 1, and the output 4
+/// let graph = vec![vec![1], vec![0], vec![3], vec![2], vec![]];
+/// let order = vec![vec![(0, vec![]), (1, vec![]), (2, vec![])], vec![(3, vec![1])]];
+/// assert_eq!(components(&graph, &[4], &order), vec![vec![0, 1, 2, 3, 4]]);
+/// let order = vec![vec![(0, vec![]), (1, vec![]), (2, vec![]), (3, vec![])]];
+/// assert_eq!(components(&graph, &[4], &order), vec![vec![0, 1, 4], vec![2, 3]]);
+/// ```
+pub fn components(
+    spacial_graph: RefSpacialGraph,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+) -> Vec<Vec<usize>> {
+    let num_bits = spacial_graph.len();
+    let mut parents: Vec<usize> = (0..num_bits).collect();
+    let edges = spacial_graph
+        .iter()
+        .enumerate()
+        .flat_map(|(bit, neighbors)| {
+            neighbors.iter().map(move |neighbor| (bit, *neighbor))
+        })
+        .chain(
+            time_ordering
+                .iter()
+                .flatten()
+                .flat_map(|(bit, deps)| deps.iter().map(move |dep| (*bit, *dep))),
+        );
+    for (left, right) in edges {
+        let (left, right) = (root(&mut parents, left), root(&mut parents, right));
+        parents[cmp::max(left, right)] = cmp::min(left, right);
+    }
+
+    let is_output = {
+        let mut is_output = vec![false; num_bits];
+        for output in search::all_outputs(num_bits, time_ordering, outputs) {
+            is_output[output] = true;
+        }
+        is_output
+    };
+    // the roots are the smallest nodes of the components, so iterating over the nodes
+    // gives the components sorted by their smallest node
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    let mut index = vec![usize::MAX; num_bits];
+    let mut measured = Vec::new();
+    for (bit, is_output) in is_output.into_iter().enumerate() {
+        let root = root(&mut parents, bit);
+        if index[root] == usize::MAX {
+            index[root] = components.len();
+            components.push(Vec::new());
+            measured.push(false);
+        }
+        components[index[root]].push(bit);
+        measured[index[root]] |= !is_output;
+    }
+
+    if !measured.iter().any(|measured| *measured) {
+        return if num_bits == 0 {
+            Vec::new()
+        } else {
+            vec![(0..num_bits).collect()]
+        };
+    }
+    let mut unmeasured = Vec::new();
+    let mut result = Vec::new();
+    for (component, measured) in components.into_iter().zip(measured) {
+        if measured {
+            result.push(component);
+        } else {
+            unmeasured.extend(component);
+        }
+    }
+    // this does not change the order of the components, since it can only decrease the
+    // smallest node of the first one
+    let first = &mut result[0];
+    first.extend(unmeasured);
+    first.sort_unstable();
+    result
+}
+
+fn root(parents: &mut [usize], mut bit: usize) -> usize {
+    while parents[bit] != bit {
+        parents[bit] = parents[parents[bit]];
+        bit = parents[bit];
+    }
+    bit
+}
+
+/// A problem instance restricted to some of the nodes, cf. [Instance::restrict]. The
+/// nodes are renumbered from 0, in the order of [Instance::nodes].
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Instance {
+    /// The nodes of the original instance, i.e., node `i` of the restricted instance is
+    /// the node `nodes[i]` of the original instance.
+    pub nodes: Vec<usize>,
+    /// The restricted spacial graph.
+    pub spacial_graph: SpacialGraph,
+    /// The restricted weights, if any.
+    pub weights: Option<Vec<usize>>,
+    /// The restricted (explicit) outputs.
+    pub outputs: Vec<usize>,
+    /// The restricted time ordering, without the layers that became empty.
+    pub time_ordering: PartialOrderGraph,
+    /// The restricted constraints.
+    pub constraints: Constraints,
+}
+
+impl Instance {
+    /// Restrict the instance to the sorted `nodes`, which should be a union of
+    /// [components] (otherwise edges and dependencies to the other nodes are dropped).
+    /// Regarding the other parameters, cf. [interface::run](crate::interface::run).
+    pub fn restrict(
+        spacial_graph: RefSpacialGraph,
+        weights: Option<&[usize]>,
+        outputs: &[usize],
+        time_ordering: RefPartialOrderGraph,
+        constraints: &Constraints,
+        nodes: &[usize],
+    ) -> Self {
+        let mut ids = vec![None; spacial_graph.len()];
+        for (id, node) in nodes.iter().enumerate() {
+            ids[*node] = Some(id);
+        }
+        let map = |nodes: &[usize]| -> Vec<usize> {
+            nodes.iter().filter_map(|node| ids[*node]).collect()
+        };
+        let time_ordering = time_ordering
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .filter_map(|(bit, deps)| ids[*bit].map(|id| (id, map(deps))))
+                    .collect::<Vec<_>>()
+            })
+            .filter(|layer| !layer.is_empty())
+            .collect();
+        let map_pairs = |pairs: &[(usize, usize)]| {
+            pairs
+                .iter()
+                .filter_map(|(node, step)| ids[*node].map(|id| (id, *step)))
+                .collect()
+        };
+        Self {
+            spacial_graph: nodes.iter().map(|node| map(&spacial_graph[*node])).collect(),
+            weights: weights
+                .map(|weights| nodes.iter().map(|node| weights[*node]).collect()),
+            outputs: map(outputs),
+            time_ordering,
+            constraints: Constraints {
+                max_step_size: constraints.max_step_size,
+                latency: constraints.latency,
+                edge_latencies: constraints
+                    .edge_latencies
+                    .iter()
+                    .filter_map(|(dependency, dependent, latency)| {
+                        Some((ids[*dependency]?, ids[*dependent]?, *latency))
+                    })
+                    .collect(),
+                release_times: map_pairs(&constraints.release_times),
+                deadlines: map_pairs(&constraints.deadlines),
+            },
+            nodes: nodes.to_vec(),
+        }
+    }
+
+    /// Map the `path` of the restricted instance to the original instance.
+    pub fn to_original(&self, mut path: Path) -> Path {
+        for node in path.steps.iter_mut().flatten() {
+            *node = self.nodes[*node];
+        }
+        path
+    }
+
+    // verify the steps, given in the original ids, and get the path with the costs (in
+    // the original ids)
+    pub(crate) fn verify(&self, steps: &[Vec<usize>]) -> Option<Path> {
+        let steps: Vec<Vec<usize>> = steps
+            .iter()
+            .map(|step| {
+                step.iter()
+                    .map(|node| self.nodes.binary_search(node).ok())
+                    .collect::<Option<_>>()
+            })
+            .collect::<Option<_>>()?;
+        verify::verify(
+            &self.spacial_graph,
+            self.weights.as_deref(),
+            &self.outputs,
+            &self.time_ordering,
+            &self.constraints,
+            &steps,
+        )
+        .ok()
+        .map(|path| self.to_original(path))
+    }
+}
+
+/// Compose the Pareto fronts `first` and `second` of two disjoint sets of components
+/// to a Pareto front of their union `instance`, cf. the [module](self) documentation.
+/// The paths are given in the ids of the original instance, that is, the one that has
+/// been restricted to get `instance`.
+///
+/// For each pair of paths, all the offsets of one path against the other are tried,
+/// from measuring them in parallel to concatenating them; the combinations that violate
+/// the constraints of the `instance`, e.g., the maximum step size, are skipped. The
+/// costs of the returned paths are calculated exactly, and the paths are sorted by their
+/// time, but their [Optimality](crate::interface::Optimality) is not set.
+pub fn compose(
+    instance: &Instance,
+    first: &[Path],
+    second: &[Path],
+    objective: Objective,
+) -> Vec<Path> {
+    let mut candidates = Vec::new();
+    for a in first {
+        for b in second {
+            for offset in 0..=a.steps.len() {
+                candidates.push(interleave(&a.steps, &b.steps, offset));
+            }
+            for offset in 1..=b.steps.len() {
+                candidates.push(interleave(&b.steps, &a.steps, offset));
+            }
+        }
+    }
+    let mut paths: Vec<Path> =
+        candidates.iter().filter_map(|steps| instance.verify(steps)).collect();
+    paths.sort_by_key(|path| (path.time, path.cost(objective)));
+    // the Pareto front: longer paths are only useful if they are cheaper
+    let mut best = usize::MAX;
+    paths.retain(|path| {
+        let cost = path.cost(objective);
+        let keep = cost < best;
+        best = cmp::min(best, cost);
+        keep
+    });
+    paths
+}
+
+// measure `second` in parallel to `first`, starting `offset` steps later
+fn interleave(first: &[Vec<usize>], second: &[Vec<usize>], offset: usize) -> Steps {
+    let len = cmp::max(first.len(), offset + second.len());
+    (0..len)
+        .map(|step| {
+            let mut nodes = first.get(step).cloned().unwrap_or_default();
+            if let Some(second) = step.checked_sub(offset).and_then(|s| second.get(s)) {
+                nodes.extend(second);
+            }
+            nodes
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compose_chains() {
+        // two chains 0 - 1 and 2 - 3, each with the dependency 1 -> 0 (respectively
+        // 3 -> 2)
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        let order =
+            vec![vec![(1, vec![]), (3, vec![])], vec![(0, vec![1]), (2, vec![3])]];
+        let constraints = Constraints::default();
+        let parts = components(&graph, &[], &order);
+        assert_eq!(parts, vec![vec![0, 1], vec![2, 3]]);
+
+        let restricted: Vec<Instance> = parts
+            .iter()
+            .map(|nodes| {
+                Instance::restrict(&graph, None, &[], &order, &constraints, nodes)
+            })
+            .collect();
+        assert_eq!(restricted[1].spacial_graph, vec![vec![1], vec![0]]);
+        assert_eq!(
+            restricted[1].time_ordering,
+            vec![vec![(1, vec![])], vec![(0, vec![1])]]
+        );
+        let fronts: Vec<Vec<Path>> = restricted
+            .iter()
+            .map(|instance| {
+                let path = verify::verify(
+                    &instance.spacial_graph,
+                    None,
+                    &[],
+                    &instance.time_ordering,
+                    &instance.constraints,
+                    &[vec![1], vec![0]],
+                )
+                .unwrap();
+                vec![instance.to_original(path)]
+            })
+            .collect();
+        assert_eq!(fronts[1][0].steps, vec![vec![3], vec![2]]);
+
+        let whole =
+            Instance::restrict(&graph, None, &[], &order, &constraints, &[0, 1, 2, 3]);
+        let composed = compose(&whole, &fronts[0], &fronts[1], Objective::Space);
+        // in parallel, we need all four qubits at once, but concatenated only two
+        assert_eq!(
+            composed
+                .iter()
+                .map(|path| (path.time, path.space, path.steps.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                (2, 4, vec![vec![1, 3], vec![0, 2]]),
+                (3, 3, vec![vec![1], vec![0, 3], vec![2]]),
+                (4, 2, vec![vec![1], vec![0], vec![3], vec![2]]),
+            ]
+        );
+
+        // with a maximum step size of 1, only the concatenations are possible
+        let whole = Instance::restrict(
+            &graph,
+            None,
+            &[],
+            &order,
+            &Constraints {
+                max_step_size: Some(1),
+                ..Default::default()
+            },
+            &[0, 1, 2, 3],
+        );
+        let composed = compose(&whole, &fronts[0], &fronts[1], Objective::Space);
+        assert_eq!(composed.len(), 1);
+        assert_eq!(composed[0].time, 4);
+    }
+}
@@ -0,0 +1,403 @@
+/*!
+Estimate the size of the search tree, and thereby the runtime of the
+[search](crate::search), before actually searching.
+
+The estimate is done with random probes down the tree, as proposed by Knuth
+([Estimating the efficiency of backtrack programs](https://doi.org/10.2307/2005454)):
+Each probe follows one random path from the root to a leaf, choosing the child in each
+step uniformly at random. The product of the numbers of children along the path is an
+unbiased estimate of the number of nodes in the respective depth, so summing them up
+gives an unbiased estimate of the tree size. Averaging over many probes reduces the
+variance.
+
+The search tree is pruned, depending on the best paths found so far and the lower
+[bounds](crate::bounds) on the cost. Since we cannot know the best paths without
+searching, we start with the time-optimal path and improve it with the paths found by a
+first round of warm-up probes; the actual estimate is then done with a second round of
+probes. The search usually finds a path as good as the time-optimal one early on, since
+it tries the larger steps first, but not necessarily as its first path (the steps are
+not ordered by the resulting costs, and the best paths of the other threads might prune
+the time-optimal one), so it starts with weaker bounds, while the warm-up might not find
+the best paths. Therefore, the estimate is only a rough one (usually within an order of
+magnitude). Note also that the estimator has, as always with Knuth's estimator, a large
+variance if the tree is very unbalanced, so consider the `relative_error` of the
+[Estimate].
+*/
+
+use std::{cmp, time::Instant};
+
+use rand::{Rng, SeedableRng, seq::index};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds,
+    interface::Constraints,
+    probabilistic::Accept,
+    scheduler::{
+        Scheduler,
+        space::{Graph, Objective, RefSpacialGraph},
+        time::{MeasurableSet, Partitioner, PathGenerator, RefPartialOrderGraph},
+        tree::{Focus, FocusIterator},
+    },
+    search,
+};
+
+/// Up to that many children, the children of a node are generated explicitly when
+/// probing; otherwise their number is calculated combinatorially and only the chosen
+/// child is generated (ignoring that some children might be skipped because they miss
+/// a deadline).
+pub const MAX_ENUMERATED_CHILDREN: usize = 64;
+
+/// The result of [estimate].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Estimate {
+    /// The number of probes that have been done.
+    pub probes: usize,
+    /// The estimated number of nodes that the deterministic search explores, cf.
+    /// [SearchStats::explored](crate::search::SearchStats::explored).
+    pub nodes: f64,
+    /// The relative standard error of `nodes`.
+    pub relative_error: f64,
+    /// The number of nodes that are explored per second, measured while probing.
+    pub nodes_per_second: f64,
+    /// The estimated runtime of the single-threaded deterministic search in seconds.
+    pub seconds: f64,
+    /// The estimated number of nodes that the probabilistic search explores, if an
+    /// accept function is given.
+    pub probabilistic_nodes: Option<f64>,
+    /// The estimated runtime of the single-threaded probabilistic search in seconds, if
+    /// an accept function is given.
+    pub probabilistic_seconds: Option<f64>,
+}
+
+/// Estimate the size of the search tree and the runtime of the search, with `probes`
+/// random probes (plus as many warm-up probes). Regarding the other parameters, cf.
+/// [interface::run](crate::interface::run); if `accept_func` is given, the
+/// probabilistic search is estimated, too, and `seed` is an optional seed for the
+/// random number generator.
+#[allow(clippy::too_many_arguments)]
+pub fn estimate(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    objective: Objective,
+    accept_func: Option<&Accept>,
+    probes: usize,
+    seed: Option<u64>,
+) -> Estimate {
+    let all_outputs = search::all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let mut dependency_buffer =
+        search::new_dependency_buffer(spacial_graph, &all_outputs, constraints);
+    let root = Scheduler::<Partitioner>::new(
+        PathGenerator::from_dependency_graph(time_ordering, &mut dependency_buffer, None)
+            .with_max_step_size(constraints.max_step_size),
+        Graph::new(spacial_graph)
+            .with_weights(weights)
+            .with_outputs(&all_outputs),
+    )
+    .with_objective(objective);
+
+    // the search finds a path at least as good as the time-optimal one early on, so we
+    // take it as the initial bound, cf. the module documentation
+    let mut best_memory = vec![usize::MAX; root.time().max_path_length() + 1];
+    if let Some(path) = search::get_time_optimal(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        constraints,
+    )
+    .first()
+    {
+        for m in best_memory[path.time..].iter_mut() {
+            *m = path.cost(objective);
+        }
+    }
+    let cost_bound = cmp::max(
+        bounds::bounds(spacial_graph, weights, outputs, time_ordering, constraints)
+            .cost(objective),
+        root.cost_bound(),
+    );
+
+    let mut rng = if let Some(seed) = seed {
+        Pcg64::seed_from_u64(seed)
+    } else {
+        Pcg64::from_entropy()
+    };
+
+    let mut nodes = Vec::with_capacity(probes);
+    let mut probabilistic_nodes = 0.;
+    let mut generated = 0;
+    let start = Instant::now();
+    // the search quickly finds better paths than the time-optimal one, so we first
+    // improve the bounds with the paths that random probes find; otherwise we would
+    // overestimate the tree size by orders of magnitude
+    for _ in 0..probes {
+        probe(
+            &root,
+            &mut best_memory,
+            cost_bound,
+            accept_func,
+            true,
+            &mut rng,
+            &mut generated,
+        );
+    }
+    for _ in 0..probes {
+        let (exact, probabilistic) = probe(
+            &root,
+            &mut best_memory,
+            cost_bound,
+            accept_func,
+            false,
+            &mut rng,
+            &mut generated,
+        );
+        nodes.push(exact);
+        probabilistic_nodes += probabilistic;
+    }
+    let elapsed = start.elapsed().as_secs_f64();
+
+    let (mean, relative_error) = mean_and_relative_error(&nodes);
+    let nodes_per_second = if elapsed > 0. {
+        generated as f64 / elapsed
+    } else {
+        f64::INFINITY
+    };
+    let probabilistic_nodes =
+        accept_func.map(|_| probabilistic_nodes / cmp::max(probes, 1) as f64);
+    Estimate {
+        probes,
+        nodes: mean,
+        relative_error,
+        nodes_per_second,
+        seconds: mean / nodes_per_second,
+        probabilistic_nodes,
+        probabilistic_seconds: probabilistic_nodes.map(|nodes| nodes / nodes_per_second),
+    }
+}
+
+// one random path down the tree; returns the estimates for the number of explored nodes
+// of the deterministic and the probabilistic search; if `improve`, the `best_memory` is
+// updated if the probe reaches a leaf; `generated` counts the nodes that have been
+// generated, to measure the throughput
+#[allow(clippy::too_many_arguments)]
+fn probe(
+    root: &Scheduler<Partitioner>,
+    best_memory: &mut [usize],
+    cost_bound: usize,
+    accept_func: Option<&Accept>,
+    improve: bool,
+    rng: &mut impl Rng,
+    generated: &mut u64,
+) -> (f64, f64) {
+    let mut current = root.clone();
+    let mut depth = 0;
+    // the products of the numbers of children along the path, i.e., the estimated
+    // number of nodes in the current depth; in the probabilistic case weighted with the
+    // accept probabilities
+    let mut weight = 1.;
+    let mut probabilistic_weight = 1.;
+    let mut nodes = 0.;
+    let mut probabilistic_nodes = 0.;
+
+    while let Some((num_children, child)) = random_child(&mut current, rng, generated) {
+        // all children are explored, but only the chosen one is followed
+        nodes += weight * num_children;
+        // (the weight might be infinite, but if it is zero, it stays zero)
+        if probabilistic_weight > 0. {
+            probabilistic_nodes += probabilistic_weight * num_children;
+        }
+        if child.time().missed_deadline().is_some() {
+            break;
+        }
+        let bound_best_mem = best_memory[depth + 1 + child.time().min_remaining_steps()];
+        if cmp::max(child.cost_bound(), cost_bound) >= bound_best_mem {
+            break;
+        }
+        if let Some(accept_func) = accept_func {
+            let accept = accept_func(
+                bound_best_mem as f64,
+                *best_memory.last().unwrap() as f64,
+                current.cost_bound() as f64,
+                search::current_cost(&current) as f64,
+                search::current_cost(&child) as f64,
+                child.time().num_remaining_nodes() as f64,
+                child.space().nodes().len() as f64,
+            );
+            // like in the search, NaN is a rejection
+            probabilistic_weight *= if accept >= 1. {
+                1.
+            } else if accept > 0. {
+                accept
+            } else {
+                0.
+            };
+        }
+        weight *= num_children;
+        if probabilistic_weight > 0. {
+            probabilistic_weight *= num_children;
+        }
+        current = child;
+        depth += 1;
+    }
+
+    if improve && current.time().is_finished() {
+        let cost = current.cost();
+        for m in best_memory[depth..].iter_mut() {
+            *m = cmp::min(*m, cost);
+        }
+    }
+
+    (nodes, probabilistic_nodes)
+}
+
+// choose a child of `current` uniformly at random and return it together with the
+// number of children; None if `current` is a leaf
+fn random_child<'l>(
+    current: &mut Scheduler<'l, Partitioner>,
+    rng: &mut impl Rng,
+    generated: &mut u64,
+) -> Option<(f64, Scheduler<'l, Partitioner>)> {
+    let measurable = current.time().measurable().set();
+    let num_measurable = measurable.len();
+    let max_size = current
+        .time()
+        .max_step_size()
+        .map_or(num_measurable, |max| cmp::min(max, num_measurable));
+    // the number of children is sum_{i=1}^{max_size} binomial(num_measurable, i); we
+    // calculate it logarithmically, because it might be huge
+    let log_binomials = log_binomials(num_measurable, max_size);
+    let log_max = log_binomials.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let log_num_children =
+        log_max + log_binomials.iter().map(|b| (b - log_max).exp()).sum::<f64>().ln();
+
+    // idle steps and small numbers of children are handled by the scheduler itself
+    if num_measurable == 0 || log_num_children <= (MAX_ENUMERATED_CHILDREN as f64).ln() {
+        // reservoir sampling
+        let mut iter = current.clone();
+        let mut num_children = 0;
+        let mut chosen = None;
+        while let Some((child, _)) = iter.next_and_focus() {
+            *generated += 1;
+            num_children += 1;
+            if rng.gen_range(0..num_children) == 0 {
+                chosen = Some(child);
+            }
+        }
+        return chosen.map(|child| (num_children as f64, child));
+    }
+
+    // choose the size of the measured set, weighted by the number of sets with that size
+    let mut threshold =
+        rng.gen::<f64>() * log_binomials.iter().map(|b| (b - log_max).exp()).sum::<f64>();
+    let mut size = max_size;
+    for (i, b) in log_binomials.iter().enumerate() {
+        threshold -= (b - log_max).exp();
+        if threshold < 0. {
+            size = i + 1;
+            break;
+        }
+    }
+    let mut measuring = index::sample(rng, num_measurable, size)
+        .into_iter()
+        .map(|i| measurable[i])
+        .collect::<Vec<_>>();
+    measuring.sort();
+    *generated += 1;
+    let child = current
+        .focus(&measuring)
+        .expect("the measured set is a subset of the measurable set");
+    Some((log_num_children.exp(), child))
+}
+
+// ln(binomial(n, i)) for i in 1..=max
+fn log_binomials(n: usize, max: usize) -> Vec<f64> {
+    let mut res = Vec::with_capacity(max);
+    let mut log_binomial = 0.;
+    for i in 1..=max {
+        log_binomial += ((n - i + 1) as f64).ln() - (i as f64).ln();
+        res.push(log_binomial);
+    }
+    res
+}
+
+fn mean_and_relative_error(samples: &[f64]) -> (f64, f64) {
+    if samples.is_empty() {
+        return (0., 0.);
+    }
+    let n = samples.len() as f64;
+    let mean = samples.iter().sum::<f64>() / n;
+    if mean == 0. || samples.len() < 2 {
+        return (mean, 0.);
+    }
+    let variance = samples.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / (n - 1.);
+    (mean, (variance / n).sqrt() / mean)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{interface::Limits, search::SearchStats};
+
+    #[test]
+    fn small_trees() {
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        // without dependencies, all non-empty subsets can be measured in the first step
+        let ordering = vec![(0..5).map(|bit| (bit, vec![])).collect::<Vec<_>>()];
+        let result = estimate(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Constraints::default(),
+            Objective::Space,
+            None,
+            10,
+            Some(0),
+        );
+        assert_eq!(result.probes, 10);
+        assert!(result.nodes >= 31.);
+        assert!(result.probabilistic_nodes.is_none());
+
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        let (_, SearchStats { explored, .. }) = search::search(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Limits::default(),
+            1,
+            None,
+            100,
+            &Constraints::default(),
+            Objective::Space,
+            None,
+        );
+        let result = estimate(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Constraints::default(),
+            Objective::Space,
+            Some(&|_, _, _, _, _, _, _| 0.5),
+            1000,
+            Some(0),
+        );
+        // the search prunes with the paths it finds, while the probes prune with the
+        // paths found in the warm-up, so the estimate is only rough
+        let explored = explored as f64;
+        assert!(result.nodes > explored / 4. && result.nodes < explored * 4.);
+        assert!(result.relative_error < 0.5);
+        assert!(result.probabilistic_nodes.unwrap() <= result.nodes);
+        assert!(result.seconds.is_finite());
+    }
+}
@@ -0,0 +1,157 @@
+/*!
+Statistics about a problem instance, e.g., to judge how hard it is before searching.
+*/
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds::{self, Bounds},
+    interface::Constraints,
+    scheduler::{
+        space::RefSpacialGraph,
+        time::{self, RefPartialOrderGraph},
+    },
+    search, strategy,
+};
+
+/// Statistics about a problem instance, cf. [instance_stats].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct InstanceStats {
+    /// The number of nodes in the spacial graph.
+    pub nodes: usize,
+    /// The number of edges in the spacial graph.
+    pub edges: usize,
+    /// The maximum degree of a node in the spacial graph.
+    pub max_degree: usize,
+    /// The (weighted) number of output nodes, including the nodes that are not in the
+    /// time ordering. Like for the space cost, this includes the outputs that are never
+    /// initialized by a measurement, e.g., isolated ones, cf.
+    /// [Graph::with_outputs](crate::scheduler::space::Graph::with_outputs).
+    pub outputs: usize,
+    /// The number of nodes that have to be measured.
+    pub measured: usize,
+    /// The sum of the memory weights of all nodes.
+    pub total_weight: usize,
+    /// The number of layers in the time ordering.
+    pub layers: usize,
+    /// The number of nodes in the widest layer of the time ordering.
+    pub max_layer_width: usize,
+    /// The number of (direct) dependencies in the time ordering.
+    pub dependencies: usize,
+    /// The number of dependencies that are implied by other dependencies, which are
+    /// dropped before searching, cf. [time::reduce_dependencies].
+    #[serde(default)]
+    pub redundant_dependencies: usize,
+    /// The logarithm (base 2) of the number of possible measurement steps in the widest
+    /// layer, cf. [strategy::MAX_LOG2_BRANCHING].
+    pub log2_max_branching: f64,
+    /// The lower bounds on the costs.
+    pub bounds: Bounds,
+}
+
+/// Calculate the [InstanceStats]. Regarding the parameters, cf.
+/// [interface::run](crate::interface::run).
+pub fn instance_stats(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+) -> InstanceStats {
+    let all_outputs = search::all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let weight = |node: usize| weights.map_or(1, |weights| weights[node]);
+    let max_layer_width =
+        time_ordering.iter().map(|layer| layer.len()).max().unwrap_or(0);
+    InstanceStats {
+        nodes: spacial_graph.len(),
+        edges: spacial_graph.iter().map(|neighbors| neighbors.len()).sum::<usize>() / 2,
+        max_degree: spacial_graph
+            .iter()
+            .map(|neighbors| neighbors.len())
+            .max()
+            .unwrap_or(0),
+        outputs: all_outputs.iter().map(|node| weight(*node)).sum(),
+        measured: spacial_graph.len() - all_outputs.len(),
+        total_weight: (0..spacial_graph.len()).map(weight).sum(),
+        layers: time_ordering.len(),
+        max_layer_width,
+        dependencies: time_ordering
+            .iter()
+            .flatten()
+            .map(|(_, dependencies)| dependencies.len())
+            .sum(),
+        redundant_dependencies: time::reduce_dependencies(&mut time_ordering.to_vec()),
+        log2_max_branching: strategy::log2_num_steps(
+            max_layer_width,
+            constraints.max_step_size,
+        ),
+        bounds: bounds::bounds(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            constraints,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn path_graph() {
+        // 0 - 1 - 2 - 3 - 4, with a chain of dependencies 0 -> 2 -> 4 and the implied
+        // 0 -> 4
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![0, 2])],
+        ];
+        let stats = instance_stats(
+            &graph,
+            Some(&[1, 1, 1, 1, 2]),
+            &[4],
+            &ordering,
+            &Constraints::default(),
+        );
+        assert_eq!(stats.nodes, 5);
+        assert_eq!(stats.edges, 4);
+        assert_eq!(stats.max_degree, 2);
+        assert_eq!(stats.outputs, 2);
+        assert_eq!(stats.measured, 4);
+        assert_eq!(stats.total_weight, 6);
+        assert_eq!(stats.layers, 3);
+        assert_eq!(stats.max_layer_width, 3);
+        assert_eq!(stats.dependencies, 3);
+        assert_eq!(stats.redundant_dependencies, 1);
+        assert_eq!(stats.log2_max_branching, 3.);
+    }
+
+    #[test]
+    fn isolated_outputs() {
+        // 0 - 1, and the isolated outputs 2, 3 and 4
+        let graph = vec![vec![1], vec![0], vec![], vec![], vec![]];
+        let outputs = [2, 3, 4];
+        let ordering = vec![vec![(0, vec![])], vec![(1, vec![0])]];
+        let constraints = Constraints::default();
+        let stats = instance_stats(&graph, None, &outputs, &ordering, &constraints);
+        assert_eq!(stats.outputs, 3);
+        assert_eq!(stats.measured, 2);
+        let path = |graph: &[Vec<usize>], outputs: &[usize]| {
+            search::get_time_optimal(graph, None, outputs, &ordering, &constraints)
+                .pop()
+                .unwrap()
+        };
+        let with_outputs = path(&graph, &outputs);
+        let without_outputs = path(&graph[..2], &[]);
+        // the isolated outputs are prepared after the last step, so they count for the
+        // space cost, but not for the costs of the steps
+        assert_eq!(without_outputs.space, 2);
+        assert_eq!(with_outputs.space, stats.outputs);
+        assert_eq!(with_outputs.volume, without_outputs.volume);
+        assert_eq!(with_outputs.idle, without_outputs.idle);
+        assert_eq!(stats.bounds.space, stats.outputs);
+    }
+}
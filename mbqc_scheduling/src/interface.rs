@@ -2,33 +2,463 @@
 Main interface to run the search algorithms.
 */
 
-use std::{error, fmt, fs, fs::File, io, path, time::Duration};
+use std::{
+    error,
+    ffi::OsString,
+    fmt, fs,
+    hash::Hash,
+    path::{self, PathBuf},
+    sync::Arc,
+    time::{Duration, Instant},
+};
 
-use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+use hashbrown::{HashMap, HashSet};
+use pauli_tracker::{
+    boolean_vector::BooleanVector,
+    pauli::PauliStack,
+    tracker::frames::{Frames, induced_order::PartialOrderGraph},
+};
+use rand::Rng;
 use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use thiserror::Error;
 
-use crate::{probabilistic::AcceptFunc, search};
+use self::{
+    edges::{DependencyEdges, EdgesError},
+    format::{FileFormat, Format, STDIO},
+    frames::{FramesError, FramesStorage},
+    import::{GraphFormat, ImportedGraph},
+    labels::{
+        LabelError, LabeledPartialOrderGraph, LabeledSpacialGraph,
+        LabeledSpacialGraphInput, Labeling, NodeLabel,
+    },
+};
 pub use crate::{
+    bounds::{Bounds, bounds},
+    estimate::{Estimate, estimate},
+    instance::{InstanceStats, instance_stats},
     scheduler::{
-        space::{RefSpacialGraph, SpacialGraph},
+        space::{Objective, RefSpacialGraph, SpacialGraph},
         time::RefPartialOrderGraph,
     },
-    search::Steps,
+    search::{Checkpoint, Portfolio, SearchStats, Steps},
+    strategy::Strategy,
+    timer::{CancelFlag, StopReason},
+    verify::VerificationError,
+    window::Window,
+};
+use crate::{
+    decompose::{self, Instance},
+    probabilistic::{Accept, AcceptFunc, AcceptFuncConfig},
+    reduce, search, strategy,
+    timer::Timer,
+    verify, window,
 };
 
+pub mod edges;
+pub mod format;
+pub mod frames;
+pub mod import;
+pub mod labels;
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 /// Description of a measurement pattern/path/schedule
 // Pattern would be a better name, however, we also use Path in the python wrapper, which
 // is published, so we wait until we have to do a breaking change for other reasons there.
-pub struct Path {
+pub struct Path<L = usize> {
     /// The time cost, i.e., the number of parallel measurement `steps` (it's just
     /// `steps.len()`).
     pub time: usize,
     /// The space cost, i.e., the maximum number of qubits that have been in memory at a
-    /// certain point in time.
+    /// certain point in time. This includes all output qubits, also the ones that are
+    /// not initialized by a measurement, e.g., isolated ones, since they are in memory
+    /// at the end, cf. [Graph::with_outputs].
+    ///
+    /// [Graph::with_outputs]: crate::scheduler::space::Graph::with_outputs
     pub space: usize,
+    /// The space-time volume, i.e., the sum of the number of qubits in memory over all
+    /// `steps` (without the outputs that are not initialized yet).
+    #[serde(default)]
+    pub volume: usize,
+    /// The qubit idle time, i.e., the sum over all qubits of the number of steps in
+    /// which they have been in memory without being measured.
+    #[serde(default)]
+    pub idle: usize,
+    /// Whether the path is proven to be optimal.
+    #[serde(default)]
+    pub optimality: Optimality,
     /// The measurement pattern, consisting of a list of parallel measurement steps.
-    pub steps: Steps,
+    pub steps: Vec<Vec<L>>,
+}
+
+impl<L> Path<L> {
+    /// Get the cost of the path according to the `objective`.
+    pub fn cost(&self, objective: Objective) -> usize {
+        match objective {
+            Objective::Space => self.space,
+            Objective::Volume => self.volume,
+            Objective::Idle => self.idle,
+        }
+    }
+
+    /// Map the nodes in the `steps`, e.g., to other labels, cf. [labels].
+    pub fn map_nodes<M>(self, mut f: impl FnMut(L) -> M) -> Path<M> {
+        Path {
+            time: self.time,
+            space: self.space,
+            volume: self.volume,
+            idle: self.idle,
+            optimality: self.optimality,
+            steps: self
+                .steps
+                .into_iter()
+                .map(|step| step.into_iter().map(&mut f).collect())
+                .collect(),
+        }
+    }
+}
+
+/// The optimality status of a [Path], with respect to the [Objective] of the search: a
+/// path is optimal if there is no path with the same or a smaller time cost that has a
+/// smaller cost.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Optimality {
+    /// The path is proven to be optimal, either because the search finished
+    /// deterministically, or because its cost reaches a lower bound.
+    Optimal,
+    /// The path is the best one that has been found, but it is not known whether it is
+    /// optimal.
+    #[default]
+    BestFound,
+    /// The path is the best one that has been found, and the optimal cost is at least
+    /// `lower_bound`, i.e., the difference to the path's cost is the remaining gap.
+    Gap { lower_bound: usize },
+}
+
+/// A [SpacialGraph] together with optional memory weights for its nodes and the output
+/// nodes, cf. the `weights` and `outputs` arguments of [run]. [run_serialized] accepts
+/// this as spacial graph input, as well as a plain [SpacialGraph].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SpacialGraphInput {
+    pub graph: SpacialGraph,
+    #[serde(default)]
+    pub weights: Option<Vec<usize>>,
+    #[serde(default)]
+    pub outputs: Vec<usize>,
+}
+
+/// Additional constraints for the scheduling, e.g., coming from the hardware. The default
+/// does not constrain anything. The nodes are given by their ids, or by their labels for
+/// [run_labeled].
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Constraints<L = usize> {
+    /// The maximum number of qubits that can be measured in parallel in one step. If
+    /// set, it has to be at least 1.
+    pub max_step_size: Option<usize>,
+    /// The classical feed-forward latency, i.e., the number of additional steps that have
+    /// to pass after measuring a qubit before its dependents can be measured (0 means
+    /// that they can be measured in the next step). Qubits that are waiting for the
+    /// latency stay in memory as usual.
+    pub latency: usize,
+    /// Latencies for specific dependency edges, given as `(dependency, dependent,
+    /// latency)`, overwriting `latency` for these edges. The dependency may also be only
+    /// implied by other dependencies; then the latency is enforced additionally to the
+    /// dependencies that imply it.
+    pub edge_latencies: Vec<(L, L, usize)>,
+    /// Release times, given as `(node, step)`, i.e., `node` can neither be initialized
+    /// nor measured before `step` (the steps are counted from 0). Since initializing a
+    /// node happens when measuring one of its neighbors, the neighbors cannot be
+    /// measured before `step` either.
+    pub release_times: Vec<(L, usize)>,
+    /// Deadlines, given as `(node, step)`, i.e., `node` has to be measured in `step` at
+    /// the latest.
+    pub deadlines: Vec<(L, usize)>,
+}
+
+impl<L> Default for Constraints<L> {
+    fn default() -> Self {
+        Self {
+            max_step_size: None,
+            latency: 0,
+            edge_latencies: Vec::new(),
+            release_times: Vec::new(),
+            deadlines: Vec::new(),
+        }
+    }
+}
+
+impl<L> Constraints<L> {
+    /// Map the nodes, e.g., to other labels, cf. [labels]; fails if `f` fails for one of
+    /// the nodes.
+    pub fn map_nodes<M, E>(
+        &self,
+        mut f: impl FnMut(&L) -> std::result::Result<M, E>,
+    ) -> std::result::Result<Constraints<M>, E> {
+        Ok(Constraints {
+            max_step_size: self.max_step_size,
+            latency: self.latency,
+            edge_latencies: self
+                .edge_latencies
+                .iter()
+                .map(|(dependency, dependent, latency)| {
+                    Ok((f(dependency)?, f(dependent)?, *latency))
+                })
+                .collect::<std::result::Result<_, _>>()?,
+            release_times: self
+                .release_times
+                .iter()
+                .map(|(node, step)| Ok((f(node)?, *step)))
+                .collect::<std::result::Result<_, _>>()?,
+            deadlines: self
+                .deadlines
+                .iter()
+                .map(|(node, step)| Ok((f(node)?, *step)))
+                .collect::<std::result::Result<_, _>>()?,
+        })
+    }
+}
+
+impl Constraints {
+    /// Check that the constraints are valid for a graph with `num_nodes` nodes and the
+    /// `time_ordering`, i.e., that the `max_step_size` is not 0 (then nothing could ever
+    /// be measured), that all nodes are in the graph, and that the `edge_latencies` are
+    /// on (direct or implied) dependencies. The run functions, e.g., [run], check this
+    /// themselves and fail with the error.
+    pub fn validate(
+        &self,
+        num_nodes: usize,
+        time_ordering: RefPartialOrderGraph,
+    ) -> std::result::Result<(), ConstraintsError> {
+        if self.max_step_size == Some(0) {
+            return Err(ConstraintsError::ZeroStepSize);
+        }
+        let mut nodes = self
+            .edge_latencies
+            .iter()
+            .flat_map(|(dependency, dependent, _)| [dependency, dependent])
+            .chain(self.release_times.iter().map(|(node, _)| node))
+            .chain(self.deadlines.iter().map(|(node, _)| node));
+        if let Some(&node) = nodes.find(|&&node| node >= num_nodes) {
+            return Err(ConstraintsError::UnknownNode { node, num_nodes });
+        }
+        if self.edge_latencies.is_empty() {
+            return Ok(());
+        }
+        let dependencies: HashMap<usize, &[usize]> = time_ordering
+            .iter()
+            .flatten()
+            .map(|(bit, deps)| (*bit, deps.as_slice()))
+            .collect();
+        for (dependency, dependent, _) in self.edge_latencies.iter() {
+            if !depends_on(&dependencies, *dependent, *dependency) {
+                return Err(ConstraintsError::NotADependency {
+                    dependency: *dependency,
+                    dependent: *dependent,
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Errors when the [Constraints] are invalid, cf. [Constraints::validate].
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum ConstraintsError {
+    #[error("the max_step_size has to be at least 1")]
+    ZeroStepSize,
+    #[error(
+        "the constraints contain the node {node}, but the graph has only {num_nodes} \
+         nodes"
+    )]
+    UnknownNode { node: usize, num_nodes: usize },
+    #[error(
+        "there is an edge latency from {dependency} to {dependent}, but {dependent} \
+         does not depend on {dependency}"
+    )]
+    NotADependency { dependency: usize, dependent: usize },
+}
+
+// whether `dependent` depends on `dependency`, directly or indirectly
+fn depends_on(
+    dependencies: &HashMap<usize, &[usize]>,
+    dependent: usize,
+    dependency: usize,
+) -> bool {
+    let mut stack = vec![dependent];
+    let mut visited = HashSet::new();
+    while let Some(bit) = stack.pop() {
+        for dep in dependencies.get(&bit).copied().unwrap_or_default() {
+            if *dep == dependency {
+                return true;
+            }
+            if visited.insert(*dep) {
+                stack.push(*dep);
+            }
+        }
+    }
+    false
+}
+
+/// Criteria to stop the search early, cf. the `limits` argument of [run]. The default
+/// does not limit anything. An `Option<Duration>` (or a [Duration]) is converted to
+/// limits with only the `timeout`.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Limits {
+    /// Stop the search after this time.
+    pub timeout: Option<Duration>,
+    /// Stop the search if no better path has been found for this time (counted from the
+    /// start if no path has been found yet).
+    pub stall_timeout: Option<Duration>,
+    /// Stop the search if no path has been found after this time; then the time-optimal
+    /// path is returned instead.
+    pub first_solution_timeout: Option<Duration>,
+    /// Stop the search after exploring that many nodes in the search tree, cf.
+    /// [SearchStats::explored]. In contrast to the timeouts, this does not depend on the
+    /// speed of the machine, so it gives deterministic cutoffs (when not multi-threaded;
+    /// with multiple threads, the nodes are counted in batches, and which nodes are
+    /// explored depends on the thread scheduling).
+    pub node_budget: Option<u64>,
+    /// Stop the search when this flag is set, e.g., from a signal handler; then the
+    /// [SearchStats::stop_reason] is [StopReason::Interrupted]. The flag only applies to
+    /// this run (and the runs with clones of it).
+    #[serde(skip)]
+    pub cancel: Option<CancelFlag>,
+}
+
+impl Limits {
+    // a timer that finishes when one of the limits is reached
+    pub(crate) fn start_timer(&self) -> Timer {
+        let mut timer = Timer::new()
+            .with_node_budget(self.node_budget)
+            .with_cancel(self.cancel.clone());
+        timer.start_with(self.timeout, self.stall_timeout, self.first_solution_timeout);
+        timer
+    }
+
+    // the limits that are left after `elapsed` time and `explored` nodes, when the
+    // `timeout` and the `node_budget` are shared by several steps one after another
+    pub(crate) fn remaining(&self, elapsed: Duration, explored: u64) -> Self {
+        Self {
+            timeout: self.timeout.map(|timeout| timeout.saturating_sub(elapsed)),
+            node_budget: self.node_budget.map(|budget| budget.saturating_sub(explored)),
+            ..self.clone()
+        }
+    }
+}
+
+impl From<Option<Duration>> for Limits {
+    fn from(timeout: Option<Duration>) -> Self {
+        Self { timeout, ..Default::default() }
+    }
+}
+
+impl From<Duration> for Limits {
+    fn from(timeout: Duration) -> Self {
+        Some(timeout).into()
+    }
+}
+
+/// How [run] gets the [Path]s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Mode {
+    /// Just take the time-optimal path.
+    #[default]
+    TimeOptimal,
+    /// Search for all best paths, deterministically or probabilistically, depending on
+    /// the `probabilistic` argument of [run].
+    Search,
+    /// Choose the [Strategy] automatically, based on the instance and the `timeout` of
+    /// the [Limits], cf. [strategy::choose]. The time spent on choosing is deducted from
+    /// the `timeout`.
+    Auto,
+    /// Run the strategies of the [Portfolio] in parallel, each in its own thread, cf.
+    /// [search::portfolio]; `nthreads`, the `task_bound` and the checkpoint of
+    /// [run_with_checkpoint] are ignored. The probabilistic strategies use the accept
+    /// function of the `probabilistic` argument of [run], or the builtin one, with
+    /// different parameters, and the strategies are seeded with consecutive seeds,
+    /// starting at the given or a drawn one, cf. [Portfolio::strategies].
+    Portfolio(Portfolio),
+}
+
+impl From<bool> for Mode {
+    /// `true` is [Mode::Search] and `false` is [Mode::TimeOptimal].
+    fn from(do_search: bool) -> Self {
+        if do_search {
+            Mode::Search
+        } else {
+            Mode::TimeOptimal
+        }
+    }
+}
+
+/// How the instance is preprocessed before searching, cf. [run_serialized].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Preprocessing {
+    /// Search the whole instance, cf. [run_with_checkpoint].
+    #[default]
+    None,
+    /// Schedule the independent components separately, cf. [run_decomposed].
+    Decompose,
+    /// Shrink the instance with the reduction rules, cf. [run_reduced].
+    Reduce,
+    /// Schedule the instance window by window, cf. [run_windowed]; the `mode` is
+    /// ignored.
+    Window(Window),
+}
+
+/// The source of the dependency graph for the file based functions, e.g.,
+/// [run_serialized]. A `(file, format)` tuple is converted into
+/// [DependencyGraphSource::Graph].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DependencyGraphSource {
+    /// A serialized dependency graph, given as `(file, format)`.
+    Graph(PathBuf, String),
+    /// Serialized Pauli tracker frames, or only their storage, and the serialized map of
+    /// the frames to the measured qubits, both given as `(file, format)`, cf. [frames].
+    Frames {
+        frames: (PathBuf, String),
+        map: (PathBuf, String),
+    },
+    /// A serialized list of `(before, after)` dependency edges, given as `(file,
+    /// format)`, cf. [edges].
+    Edges(PathBuf, String),
+}
+
+impl<P: AsRef<path::Path>> From<(P, &str)> for DependencyGraphSource {
+    fn from((file, format): (P, &str)) -> Self {
+        Self::Graph(file.as_ref().to_owned(), format.to_owned())
+    }
+}
+
+/// Metadata about how the [Path]s have been obtained, cf. [run_with_metadata].
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// The strategy that has been used.
+    pub strategy: Strategy,
+    /// The number of threads that have been used.
+    pub nthreads: u16,
+    /// The estimate on which the automatic choice of the `strategy` was based, if any.
+    pub estimate: Option<Estimate>,
+    /// The statistics of the search, if there was one.
+    pub stats: Option<SearchStats>,
+    /// The seed of the random number generator, if the search was probabilistic. If no
+    /// seed was given, this is the randomly drawn one, so that the run can be replayed
+    /// (in the single-threaded case, cf. [run]).
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// The accept function of the probabilistic search, unless there was none or it is a
+    /// custom one; with [Mode::Auto], this is the chosen one, and with [Mode::Portfolio],
+    /// the one of the first probabilistic strategy.
+    #[serde(default)]
+    pub accept_func: Option<AcceptFuncConfig>,
+    /// The metadata of the single components, if the instance has been decomposed, cf.
+    /// [run_decomposed]; otherwise empty.
+    #[serde(default)]
+    pub components: Vec<Metadata>,
 }
 
 /// Searching for optimal initialization-measurement [Path]s.
@@ -37,17 +467,31 @@ pub struct Path {
 ///
 /// * `spacial_graph` - A list of neighbors for each node, describing the graph
 /// obtained from running the stabilizer simulator (and transforming it into a graph).
+/// * `weights` - Optional memory weights for the nodes in the `spacial_graph`, i.e.,
+/// how many memory slots a node occupies (if None, every node occupies one slot). The
+/// space cost of the [Path]s is then the weighted peak memory. Must have the same
+/// length as the `spacial_graph`.
+/// * `outputs` - The output nodes, which are never measured, but which are initialized
+/// as usual and then stay in memory until the end (so the space cost is at least the
+/// (weighted) number of outputs). Nodes that are not in the `time_ordering` are never
+/// measured either, so they are treated as outputs, too.
 /// * `dependency_graph` - The output obtained from the pauli tracker, describing the
 /// partial ordering of the measurements in time.
-/// * `do_search` - A flag that determines whether to search for all best paths or just
-/// take the first one, which is the time optimal path. Searching for all best paths may
-/// take some time ...
-/// * `timeout` - A timeout for the search. You'll probably want to set this, because if
-/// the run is cancelled by some other reason, the results are generally lost, but when
-/// the run cancelled because of a timeout, the function returns as normally with the
-/// results obtained so far. However, note that is timeout is too short, i.e., shorter
-/// than how long it would take to get the first path (which depends potentially
-/// `probabilistic`), then the function will return an empty list.
+/// * `mode` - Determines whether to search for all best paths or just take the first
+/// one, which is the time optimal path, cf. [Mode]; `true` and `false` are converted
+/// to [Mode::Search] and [Mode::TimeOptimal], respectively. Searching for all best
+/// paths may take some time ... With [Mode::Auto], the strategy is chosen
+/// automatically, using the timeout of the `limits` as time budget, `nthreads` as the
+/// maximum number of threads and the accept function of `probabilistic`, if given,
+/// for the probabilistic search; use [run_with_metadata] to get the chosen strategy.
+/// With [Mode::Portfolio], several strategies run in parallel.
+/// * `limits` - The [Limits] for the search, e.g., a timeout; `None` or
+/// `Some(timeout)` can be passed directly. You'll probably want to set a timeout,
+/// because if the run is cancelled by some other reason, the results are generally
+/// lost, but when the run is stopped because of the limits, the function returns as
+/// normally with the results obtained so far. If the search is stopped before it has
+/// found any path, the time-optimal path is returned instead, cf.
+/// [SearchStats::fallback].
 /// * `nthreads` - the number of threads to use for the search. If `nthreads` is below
 /// 2, it will not multithread. Otherwise it will start a threadpool (where one thread
 /// is used to manage shared data). The tasks for the threadpool are all the possible
@@ -64,111 +508,1993 @@ pub struct Path {
 /// it probabilistically, with a relatively low accept rate, because otherwise it takes
 /// forever (scaling is in the worst case something between factorial and double
 /// exponential). The second tuple element is an optional seed for the random number
-/// generator. However, note that if multithreaded, i.e., `nthreads > 1`, fixing the seed
-/// does not ensure reproducibibility (the threads communicate the results with each
-/// other, and depending on that they adjust the search; this communication is not
-/// deterministic (on this level here) since it depends on how the threads are scheduled).
+/// generator; if None, a random seed is drawn, which is recorded in the [SearchStats]
+/// and the [Metadata]. However, note that if multithreaded, i.e., `nthreads > 1`,
+/// fixing the seed does not ensure reproducibility (the threads communicate the
+/// results with each other, and depending on that they adjust the search; this
+/// communication is not deterministic (on this level here) since it depends on how the
+/// threads are scheduled).
+/// * `constraints` - Additional [Constraints] that the paths have to fulfill.
+/// * `objective` - The [Objective] that is optimized (together with the time) when
+/// searching; the [Path]s contain all the costs, independent of the `objective`.
 ///
 /// Note that the algorithm always first tries the more time optimal patterns, however,
 /// whether they are accepted can be controlled with the `probabilistic` accept function.
+///
+/// Fails if the `constraints` are invalid, cf. [Constraints::validate]; the same holds
+/// for all the other run functions.
+#[allow(clippy::too_many_arguments, clippy::doc_lazy_continuation)]
 pub fn run(
     spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
     time_ordering: RefPartialOrderGraph,
-    do_search: bool,
-    timeout: Option<Duration>,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
     nthreads: u16,
     task_bound: Option<u32>,
     probabilistic: Option<(AcceptFunc, Option<u64>)>,
-) -> Vec<Path> {
-    if !do_search {
-        search::get_time_optimal(spacial_graph, time_ordering)
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<Vec<Path>, ConstraintsError> {
+    Ok(run_with_stats(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        constraints,
+        objective,
+    )?
+    .0)
+}
+
+/// Same as [run], but additionally returns the [SearchStats] if there was a search.
+#[allow(clippy::too_many_arguments)]
+pub fn run_with_stats(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path>, Option<SearchStats>), ConstraintsError> {
+    let (paths, metadata) = run_with_metadata(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        constraints,
+        objective,
+    )?;
+    Ok((paths, metadata.stats))
+}
+
+/// Same as [run], but additionally returns [Metadata] about how the paths have been
+/// obtained.
+#[allow(clippy::too_many_arguments)]
+pub fn run_with_metadata(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path>, Metadata), ConstraintsError> {
+    run_with_checkpoint(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        constraints,
+        objective,
+        None,
+    )
+}
+
+/// Same as [run_with_metadata], but if there is a search, the paths found so far are
+/// periodically passed to the `checkpoint`, cf. [Checkpoint].
+#[allow(clippy::too_many_arguments)]
+pub fn run_with_checkpoint(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+    checkpoint: Option<Checkpoint>,
+) -> std::result::Result<(Vec<Path>, Metadata), ConstraintsError> {
+    constraints.validate(spacial_graph.len(), time_ordering)?;
+    let (mode, mut limits) = (mode.into(), limits.into());
+    let (strategy, nthreads, probabilistic, estimate) = match mode {
+        Mode::TimeOptimal => (Strategy::TimeOptimal, 1, None, None),
+        Mode::Search if probabilistic.is_some() => {
+            (Strategy::Probabilistic, nthreads, probabilistic, None)
+        },
+        Mode::Search => (Strategy::Exact, nthreads, None, None),
+        Mode::Auto => {
+            let (accept_func, seed) = match probabilistic {
+                Some((func, seed)) => (Some(func), seed),
+                None => (None, None),
+            };
+            let start = Instant::now();
+            let choice = strategy::choose(
+                spacial_graph,
+                weights,
+                outputs,
+                time_ordering,
+                constraints,
+                objective,
+                limits.timeout,
+                nthreads,
+                accept_func,
+            );
+            tracing::info!("automatically chose the {:?} strategy", choice.strategy);
+            // the time for the estimates is part of the time budget
+            limits = limits.remaining(start.elapsed(), 0);
+            (
+                choice.strategy,
+                choice.nthreads,
+                choice.accept_func.map(|func| (func, seed)),
+                choice.estimate,
+            )
+        },
+        Mode::Portfolio(portfolio) => (
+            Strategy::Portfolio,
+            portfolio.len(),
+            Some(probabilistic.unwrap_or_default()),
+            None,
+        ),
+    };
+
+    let accept_func = probabilistic.as_ref().and_then(|(func, _)| func.config());
+    // draw the seed here, so that it can be recorded to replay the run
+    let probabilistic = probabilistic
+        .map(|(func, seed)| (func, seed.unwrap_or_else(|| rand::thread_rng().gen())));
+    let seed = probabilistic.as_ref().map(|(_, seed)| *seed);
+    if let Some(seed) = seed {
+        tracing::info!("the seed of the probabilistic search is {seed}");
+    }
+
+    let (paths, stats) = if strategy == Strategy::TimeOptimal {
+        (
+            search::get_time_optimal(
+                spacial_graph,
+                weights,
+                outputs,
+                time_ordering,
+                constraints,
+            ),
+            None,
+        )
+    } else if let Mode::Portfolio(portfolio) = mode {
+        let (func, seed) = probabilistic.expect("is set for the portfolio");
+        let func = SharedAcceptFunc::from(func);
+        let (paths, stats) = search::portfolio(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            &limits,
+            portfolio.strategies(|| func.get(), seed),
+            constraints,
+            objective,
+        );
+        (paths, Some(SearchStats { seed: Some(seed), ..stats }))
     } else {
-        search::search(
+        let (paths, stats) = search::search(
             spacial_graph,
+            weights,
+            outputs,
             time_ordering,
-            timeout,
+            &limits,
             nthreads,
-            probabilistic.map(|(func, seed)| (func.get_accept_func(), seed)),
+            probabilistic.map(|(func, seed)| (func.get_accept_func(), Some(seed))),
             task_bound.map(|b| b.into()).unwrap_or(100000),
+            constraints,
+            objective,
+            checkpoint,
+        );
+        (paths, Some(SearchStats { seed, ..stats }))
+    };
+    Ok((
+        paths,
+        Metadata {
+            strategy,
+            nthreads,
+            estimate,
+            stats,
+            seed,
+            accept_func,
+            components: Vec::new(),
+        },
+    ))
+}
+
+/// Same as [run_with_metadata], but the instance is first split into its independent
+/// components, cf. [decompose]: each component is scheduled separately, with the same
+/// arguments, and then the Pareto fronts of the components are composed. This can be
+/// exponentially faster if the instance is modular, however, the composed paths are not
+/// necessarily optimal (their [Optimality] is determined by the lower [bounds] of the
+/// whole instance).
+///
+/// The `timeout` and the `node_budget` of the `limits` are shared by the components,
+/// i.e., each component gets an equal share of what is left, and if `probabilistic` has
+/// no seed, one seed is drawn for all components. The [Metadata] of the components are
+/// collected in [Metadata::components], and the [SearchStats] are summed up. If the
+/// instance has only one component, or if no composition of the components fulfills
+/// the `constraints` (e.g., because the steps become too large), this falls back to
+/// [run_with_metadata] on the whole instance.
+#[allow(clippy::too_many_arguments)]
+pub fn run_decomposed(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path>, Metadata), ConstraintsError> {
+    constraints.validate(spacial_graph.len(), time_ordering)?;
+    let (mode, limits) = (mode.into(), limits.into());
+    let parts = decompose::components(spacial_graph, outputs, time_ordering);
+    if parts.len() < 2 {
+        return run_with_metadata(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            mode,
+            limits,
+            nthreads,
+            task_bound,
+            probabilistic,
+            constraints,
+            objective,
+        );
+    }
+    tracing::info!("scheduling {} independent components", parts.len());
+
+    let seed = probabilistic
+        .as_ref()
+        .map(|(_, seed)| seed.unwrap_or_else(|| rand::thread_rng().gen()));
+    let config = probabilistic.as_ref().and_then(|(func, _)| func.config());
+    let accept_func = probabilistic.map(|(func, _)| SharedAcceptFunc::from(func));
+    let restrict = |nodes: &[usize]| {
+        Instance::restrict(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            constraints,
+            nodes,
         )
+    };
+
+    let start = Instant::now();
+    let mut front = Vec::new();
+    let mut nodes = Vec::new();
+    let mut components = Vec::new();
+    for (i, part) in parts.iter().enumerate() {
+        let remaining = (parts.len() - i) as u32;
+        let component_limits = Limits {
+            timeout: limits
+                .timeout
+                .map(|timeout| timeout.saturating_sub(start.elapsed()) / remaining),
+            node_budget: limits.node_budget.map(|budget| budget / parts.len() as u64),
+            ..limits.clone()
+        };
+        let instance = restrict(part);
+        let (paths, metadata) = run_with_metadata(
+            &instance.spacial_graph,
+            instance.weights.as_deref(),
+            &instance.outputs,
+            &instance.time_ordering,
+            mode,
+            component_limits,
+            nthreads,
+            task_bound,
+            accept_func.as_ref().map(|func| (func.get(), seed)),
+            &instance.constraints,
+            objective,
+        )?;
+        components.push(metadata);
+        let paths: Vec<Path> =
+            paths.into_iter().map(|path| instance.to_original(path)).collect();
+        nodes.extend(part);
+        nodes.sort_unstable();
+        front = if i == 0 {
+            paths
+        } else if paths.is_empty() {
+            Vec::new()
+        } else {
+            let composed =
+                decompose::compose(&restrict(&nodes), &front, &paths, objective);
+            if composed.is_empty() {
+                tracing::info!(
+                    "no composition of the components fulfills the constraints; \
+                     scheduling the whole instance"
+                );
+                return run_with_metadata(
+                    spacial_graph,
+                    weights,
+                    outputs,
+                    time_ordering,
+                    mode,
+                    limits,
+                    nthreads,
+                    task_bound,
+                    accept_func.map(|func| (func.get(), seed)),
+                    constraints,
+                    objective,
+                );
+            }
+            composed
+        };
+        // some component cannot be scheduled, so the whole instance cannot either
+        if front.is_empty() {
+            break;
+        }
+    }
+
+    let lower_bound = bounds(spacial_graph, weights, outputs, time_ordering, constraints)
+        .cost(objective);
+    for path in front.iter_mut() {
+        path.optimality = search::optimality(path.cost(objective), lower_bound, false);
+    }
+    let mut stats: Option<SearchStats> = None;
+    for component_stats in
+        components.iter().filter_map(|metadata| metadata.stats.as_ref())
+    {
+        stats.get_or_insert_with(Default::default).merge(component_stats);
+    }
+    let metadata = Metadata {
+        strategy: components
+            .iter()
+            .map(|metadata| metadata.strategy)
+            .find(|strategy| *strategy != Strategy::TimeOptimal)
+            .unwrap_or_default(),
+        nthreads: components.iter().map(|metadata| metadata.nthreads).max().unwrap_or(1),
+        estimate: None,
+        stats,
+        seed,
+        accept_func: config,
+        components,
+    };
+    Ok((front, metadata))
+}
+
+/// Same as [run_with_metadata], but the instance is first shrunk with the reduction rules
+/// of the [reduce] module: the search runs on the kernel and the paths are lifted back to
+/// the full instance. The rules are exact, so the lifted paths keep their [Optimality].
+/// Note that the rules depend on the `objective`; for example, for [Objective::Space],
+/// only nodes with weight 0 are removed.
+#[allow(clippy::too_many_arguments)]
+pub fn run_reduced(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path>, Metadata), ConstraintsError> {
+    constraints.validate(spacial_graph.len(), time_ordering)?;
+    let reduction = reduce::reduce(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        constraints,
+        objective,
+    );
+    if reduction.removed.is_empty() {
+        return run_with_metadata(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            mode,
+            limits,
+            nthreads,
+            task_bound,
+            probabilistic,
+            constraints,
+            objective,
+        );
     }
+    tracing::info!(
+        "the reduction rules removed {} of {} nodes",
+        reduction.removed.len(),
+        spacial_graph.len()
+    );
+    let kernel = &reduction.kernel;
+    let (paths, metadata) = run_with_metadata(
+        &kernel.spacial_graph,
+        kernel.weights.as_deref(),
+        &kernel.outputs,
+        &kernel.time_ordering,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        &kernel.constraints,
+        objective,
+    )?;
+    Ok((reduction.lift_all(paths), metadata))
+}
+
+/// Same as [run_with_metadata], but the instance is scheduled window by window, cf.
+/// [window]: each window of [Window::layers] layers is searched exactly, or
+/// probabilistically if `probabilistic` is given, and the first [Window::commit] steps
+/// of its best path are committed. This scales to large instances, however, the path is
+/// not necessarily optimal (its [Optimality] is always
+/// [BestFound](Optimality::BestFound)).
+///
+/// The `timeout` and the `node_budget` of the `limits` bound all windows together (when
+/// they are exhausted, the remaining windows fall back to their time-optimal paths, cf.
+/// [SearchStats::fallback]), while the other limits apply to each window separately. If
+/// `probabilistic` has no seed, one seed is drawn for all windows. The [SearchStats] of
+/// the windows are summed up. At most one path is returned; none if a window cannot be
+/// scheduled, e.g., because a deadline
+/// is missed, which may happen even if the whole instance can be scheduled.
+#[allow(clippy::too_many_arguments)]
+pub fn run_windowed(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    window: Window,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path>, Metadata), ConstraintsError> {
+    constraints.validate(spacial_graph.len(), time_ordering)?;
+    let limits = limits.into();
+    let seed = probabilistic
+        .as_ref()
+        .map(|(_, seed)| seed.unwrap_or_else(|| rand::thread_rng().gen()));
+    let config = probabilistic.as_ref().and_then(|(func, _)| func.config());
+    let accept_func = probabilistic.map(|(func, _)| SharedAcceptFunc::from(func));
+    let task_bound = task_bound.map(|b| b.into()).unwrap_or(100000);
+
+    let start = Instant::now();
+    let mut stats = SearchStats::default();
+    let paths = window::schedule(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        constraints,
+        objective,
+        window,
+        |instance, initialized| {
+            let (paths, window_stats) = search::search_from(
+                &instance.spacial_graph,
+                instance.weights.as_deref(),
+                &instance.outputs,
+                &instance.time_ordering,
+                &limits.remaining(start.elapsed(), stats.explored),
+                nthreads,
+                accept_func.as_ref().map(|func| (func.get().get_accept_func(), seed)),
+                task_bound,
+                &instance.constraints,
+                objective,
+                initialized,
+                None,
+            );
+            stats.merge(&window_stats);
+            paths
+        },
+    )
+    .into_iter()
+    .collect();
+    stats.seed = seed;
+    let metadata = Metadata {
+        strategy: Strategy::Windowed,
+        nthreads,
+        estimate: None,
+        stats: Some(stats),
+        seed,
+        accept_func: config,
+        components: Vec::new(),
+    };
+    Ok((paths, metadata))
+}
+
+// an accept function that can be used for multiple searches, cf. run_decomposed
+enum SharedAcceptFunc {
+    Config(AcceptFuncConfig),
+    Custom(Arc<Accept>),
+}
+
+impl SharedAcceptFunc {
+    fn get(&self) -> AcceptFunc {
+        match self {
+            SharedAcceptFunc::Config(config) => (*config).into(),
+            SharedAcceptFunc::Custom(func) => {
+                let func = func.clone();
+                AcceptFunc::Custom(Box::new(move |a, b, c, d, e, f, g| {
+                    func(a, b, c, d, e, f, g)
+                }))
+            },
+        }
+    }
+}
+
+impl From<AcceptFunc> for SharedAcceptFunc {
+    fn from(func: AcceptFunc) -> Self {
+        match func {
+            AcceptFunc::BuiltinHeavyside => {
+                SharedAcceptFunc::Config(AcceptFuncConfig::BuiltinHeavyside)
+            },
+            AcceptFunc::ParametrizedHeavyside { param } => {
+                SharedAcceptFunc::Config(AcceptFuncConfig::ParametrizedHeavyside(param))
+            },
+            AcceptFunc::Custom(func) => SharedAcceptFunc::Custom(func.into()),
+        }
+    }
+}
+
+/// Same as [run_with_metadata], but the nodes can have arbitrary labels, e.g., sparse
+/// numbers or names, instead of being numbered from 0, cf. [labels]. The
+/// `spacial_graph` lists the nodes together with their neighbors, and the `weights` are
+/// given in the same order; all the other arguments refer to the nodes by their labels,
+/// and the steps of the returned [Path]s consist of the labels, too. Fails if a label
+/// appears more than once in the `spacial_graph`, or if a label is not a node of the
+/// `spacial_graph`.
+#[allow(clippy::too_many_arguments)]
+pub fn run_labeled<L: Clone + Eq + Hash + fmt::Display>(
+    spacial_graph: &[(L, Vec<L>)],
+    weights: Option<&[usize]>,
+    outputs: &[L],
+    time_ordering: &[Vec<(L, Vec<L>)>],
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints<L>,
+    objective: Objective,
+) -> std::result::Result<(Vec<Path<L>>, Metadata), LabelError> {
+    let (labeling, spacial_graph) = Labeling::from_spacial_graph(spacial_graph)?;
+    let (paths, metadata) = run_with_metadata(
+        &spacial_graph,
+        weights,
+        &labeling.ids(outputs)?,
+        &labeling.dependency_graph(time_ordering)?,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        &labeling.constraints(constraints)?,
+        objective,
+    )?;
+    Ok((
+        paths.into_iter().map(|path| labeling.label_path(path)).collect(),
+        metadata,
+    ))
+}
+
+/// Same as [run], but the dependency graph is computed from the frames of a Pauli
+/// tracker, cf. [frames]: `frames` are the qubits together with their Pauli stacks, e.g.,
+/// the pairs of a [FramesStorage], and `map` maps the frames to the measured qubits.
+/// Fails if the frames are not valid, cf. [frames::get_order].
+#[allow(clippy::too_many_arguments)]
+pub fn run_from_frames<'l, B: BooleanVector + 'l>(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    frames: impl IntoIterator<Item = (usize, &'l PauliStack<B>)>,
+    map: &[usize],
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<Vec<Path>, FramesError> {
+    Ok(run(
+        spacial_graph,
+        weights,
+        outputs,
+        &frames::get_order(frames, map)?,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        constraints,
+        objective,
+    )?)
+}
+
+/// Same as [run], but the dependency graph is computed from the `(before, after)`
+/// dependency `edges`, cf. [edges]. Fails if the edges refer to nodes that are not in the
+/// `spacial_graph` or if they are cyclic, cf. [edges::get_order].
+#[allow(clippy::too_many_arguments)]
+pub fn run_from_edges(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    edges: &[(usize, usize)],
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
+    nthreads: u16,
+    task_bound: Option<u32>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> std::result::Result<Vec<Path>, EdgesError> {
+    Ok(run(
+        spacial_graph,
+        weights,
+        outputs,
+        &edges::get_order(spacial_graph.len(), edges)?,
+        mode,
+        limits,
+        nthreads,
+        task_bound,
+        probabilistic,
+        constraints,
+        objective,
+    )?)
 }
 
 type Result<T> = std::result::Result<T, Box<dyn error::Error>>;
 
-/// Same as [run], but with file paths to the input and output data.
+/// Same as [run], but with file paths to the input and output data. If `stats` is
+/// given, the [SearchStats] are written to it (`null`/`None` if there was no search),
+/// if `metadata` is given, the [Metadata] are written to it, and if `bounds` is given,
+/// the lower [Bounds] are written to it. If `checkpoint_interval` is given, the paths
+/// found so far are periodically written to the `paths` file during the search, cf.
+/// [run_with_checkpoint], so that they are not lost when the process is killed (the file
+/// is replaced atomically, so it always contains complete paths); this is disabled if the
+/// `paths` are written to stdout or if the instance is preprocessed, cf. [Preprocessing].
+/// The serialization formats are described in [mod@format].
+///
+/// If the spacial graph is labeled, cf. [labels], the dependency graph and the
+/// `constraints` refer to the same labels, and the paths are written with them;
+/// otherwise the numbers in the `constraints` are the node ids. Instead of the layered
+/// dependency graph, the `dependency_graph` can also be given as Pauli tracker frames or
+/// as a list of dependency edges, cf. [DependencyGraphSource].
 #[allow(clippy::too_many_arguments)]
 pub fn run_serialized(
     spacial_graph: (impl AsRef<path::Path>, &str),
-    dependency_graph: (impl AsRef<path::Path>, &str),
-    do_search: bool,
-    timeout: Option<Duration>,
+    dependency_graph: impl Into<DependencyGraphSource>,
+    mode: impl Into<Mode>,
+    limits: impl Into<Limits>,
     nthreads: u16,
     task_bound: Option<u32>,
-    probablistic: Option<AcceptFunc>,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    constraints: &Constraints<NodeLabel>,
+    objective: Objective,
+    preprocessing: Preprocessing,
     paths: (impl AsRef<path::Path>, &str),
+    checkpoint_interval: Option<Duration>,
+    stats: Option<(&path::Path, &str)>,
+    metadata: Option<(&path::Path, &str)>,
+    bounds: Option<(&path::Path, &str)>,
 ) -> Result<()> {
-    let spacial_graph: SpacialGraph =
-        deserialize_from_file(spacial_graph.0, spacial_graph.1)?;
-    let dependency_graph: PartialOrderGraph =
-        deserialize_from_file(dependency_graph.0, dependency_graph.1)?;
-    serialize_to_file(
-        paths.0,
-        &run(
+    let (
+        SpacialGraphInput {
+            graph: spacial_graph,
+            weights,
+            outputs,
+        },
+        dependency_graph,
+        labeling,
+    ) = read_graphs(spacial_graph, dependency_graph)?;
+    let constraints = &constraints.resolve(labeling.as_ref())?;
+    let (paths_file, paths_format) = (paths.0.as_ref(), paths.1);
+    let write_checkpoint = |checkpoint: &[Path]| {
+        if let Err(e) =
+            write_paths(paths_file, checkpoint, paths_format, labeling.as_ref())
+        {
+            tracing::warn!("failed to write checkpoint: {e}");
+        }
+    };
+    let (result, run_metadata) = match preprocessing {
+        Preprocessing::Decompose => run_decomposed(
+            &spacial_graph,
+            weights.as_deref(),
+            &outputs,
+            &dependency_graph,
+            mode,
+            limits,
+            nthreads,
+            task_bound,
+            probabilistic,
+            constraints,
+            objective,
+        ),
+        Preprocessing::Reduce => run_reduced(
+            &spacial_graph,
+            weights.as_deref(),
+            &outputs,
+            &dependency_graph,
+            mode,
+            limits,
+            nthreads,
+            task_bound,
+            probabilistic,
+            constraints,
+            objective,
+        ),
+        Preprocessing::Window(window) => run_windowed(
             &spacial_graph,
+            weights.as_deref(),
+            &outputs,
             &dependency_graph,
-            do_search,
-            timeout,
+            window,
+            limits,
             nthreads,
             task_bound,
-            probablistic.map(|func| (func, None)),
+            probabilistic,
+            constraints,
+            objective,
         ),
-        paths.1,
+        Preprocessing::None => run_with_checkpoint(
+            &spacial_graph,
+            weights.as_deref(),
+            &outputs,
+            &dependency_graph,
+            mode,
+            limits,
+            nthreads,
+            task_bound,
+            probabilistic,
+            constraints,
+            objective,
+            checkpoint_interval.filter(|_| paths_file.as_os_str() != STDIO).map(
+                |interval| Checkpoint {
+                    interval,
+                    callback: &write_checkpoint,
+                },
+            ),
+        ),
+    }?;
+    write_paths(paths_file, &result, paths_format, labeling.as_ref())?;
+    if let Some((file, format)) = stats {
+        serialize_to_file(file, &run_metadata.stats, format)?;
+    }
+    if let Some((file, format)) = metadata {
+        serialize_to_file(file, &run_metadata, format)?;
+    }
+    if let Some((file, format)) = bounds {
+        serialize_to_file(
+            file,
+            &self::bounds(
+                &spacial_graph,
+                weights.as_deref(),
+                &outputs,
+                &dependency_graph,
+                constraints,
+            ),
+            format,
+        )?;
+    }
+    Ok(())
+}
+
+/// Same as [estimate], but with file paths to the input and output data, cf.
+/// [run_serialized]. If `probabilistic` is given, the probabilistic search is estimated,
+/// too.
+#[allow(clippy::too_many_arguments)]
+pub fn estimate_serialized(
+    spacial_graph: (impl AsRef<path::Path>, &str),
+    dependency_graph: impl Into<DependencyGraphSource>,
+    probabilistic: Option<AcceptFunc>,
+    constraints: &Constraints<NodeLabel>,
+    objective: Objective,
+    probes: usize,
+    seed: Option<u64>,
+    estimate: (impl AsRef<path::Path>, &str),
+) -> Result<()> {
+    let (
+        SpacialGraphInput {
+            graph: spacial_graph,
+            weights,
+            outputs,
+        },
+        dependency_graph,
+        labeling,
+    ) = read_graphs(spacial_graph, dependency_graph)?;
+    let constraints = &constraints.resolve(labeling.as_ref())?;
+    constraints.validate(spacial_graph.len(), &dependency_graph)?;
+    let result = self::estimate(
+        &spacial_graph,
+        weights.as_deref(),
+        &outputs,
+        &dependency_graph,
+        constraints,
+        objective,
+        probabilistic.map(|func| func.get_accept_func()).as_deref(),
+        probes,
+        seed,
+    );
+    serialize_to_file(estimate.0, &result, estimate.1)
+}
+
+/// Verify the [Path]s in the `paths` file, cf. [verify](crate::verify::verify), with
+/// file paths to the input data as in [run_serialized]. Returns for each path either the
+/// path with the recalculated costs (with the node ids in the steps, also if the graph
+/// is labeled) or the violation.
+pub fn verify_serialized(
+    spacial_graph: (impl AsRef<path::Path>, &str),
+    dependency_graph: impl Into<DependencyGraphSource>,
+    constraints: &Constraints<NodeLabel>,
+    paths: (impl AsRef<path::Path>, &str),
+) -> Result<Vec<std::result::Result<Path, VerificationError>>> {
+    let (
+        SpacialGraphInput {
+            graph: spacial_graph,
+            weights,
+            outputs,
+        },
+        dependency_graph,
+        labeling,
+    ) = read_graphs(spacial_graph, dependency_graph)?;
+    let constraints = &constraints.resolve(labeling.as_ref())?;
+    constraints.validate(spacial_graph.len(), &dependency_graph)?;
+    Ok(read_labeled_paths(paths.0, paths.1, labeling.as_ref())?
+        .into_iter()
+        .map(|path| {
+            verify::verify(
+                &spacial_graph,
+                weights.as_deref(),
+                &outputs,
+                &dependency_graph,
+                constraints,
+                &path.steps,
+            )
+        })
+        .collect())
+}
+
+/// Recalculate the costs of the [Path]s in the `paths` file and write them to the
+/// `evaluated` file; their [Optimality] is determined by the lower [bounds] for the
+/// `objective`. Fails if one of the paths is not valid, cf. [verify_serialized].
+pub fn evaluate_serialized(
+    spacial_graph: (impl AsRef<path::Path>, &str),
+    dependency_graph: impl Into<DependencyGraphSource>,
+    constraints: &Constraints<NodeLabel>,
+    objective: Objective,
+    paths: (impl AsRef<path::Path>, &str),
+    evaluated: (impl AsRef<path::Path>, &str),
+) -> Result<()> {
+    let (
+        SpacialGraphInput {
+            graph: spacial_graph,
+            weights,
+            outputs,
+        },
+        dependency_graph,
+        labeling,
+    ) = read_graphs(spacial_graph, dependency_graph)?;
+    let constraints = &constraints.resolve(labeling.as_ref())?;
+    constraints.validate(spacial_graph.len(), &dependency_graph)?;
+    let lower_bound = self::bounds(
+        &spacial_graph,
+        weights.as_deref(),
+        &outputs,
+        &dependency_graph,
+        constraints,
     )
+    .cost(objective);
+    let mut result = Vec::new();
+    let paths = read_labeled_paths(paths.0, paths.1, labeling.as_ref())?;
+    for (index, path) in paths.into_iter().enumerate() {
+        let mut path = verify::verify(
+            &spacial_graph,
+            weights.as_deref(),
+            &outputs,
+            &dependency_graph,
+            constraints,
+            &path.steps,
+        )
+        .map_err(|error| InvalidPath(index, error))?;
+        path.optimality = search::optimality(path.cost(objective), lower_bound, false);
+        result.push(path);
+    }
+    write_paths(evaluated.0.as_ref(), &result, evaluated.1, labeling.as_ref())
+}
+
+/// The kind of data in a file, cf. [convert_serialized].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DataKind {
+    /// A [SpacialGraph], or a [SpacialGraphInput].
+    SpacialGraph,
+    /// A dependency graph, cf. [run].
+    DependencyGraph,
+    /// A list of [Path]s.
+    Paths,
+    /// The [Constraints].
+    Constraints,
+}
+
+/// Convert the data of the given `kind` in the `input` file into another serialization
+/// format, writing it to the `output` file (the formats are the same as in
+/// [run_serialized]). A spacial graph without weights and outputs is written as plain
+/// [SpacialGraph], otherwise as [SpacialGraphInput] (or the according labeled types, cf.
+/// [labels]); the node labels are kept.
+pub fn convert_serialized(
+    kind: DataKind,
+    input: (impl AsRef<path::Path>, &str),
+    output: (impl AsRef<path::Path>, &str),
+) -> Result<()> {
+    fn convert<T: Serialize + DeserializeOwned>(
+        input: (impl AsRef<path::Path>, &str),
+        output: (impl AsRef<path::Path>, &str),
+    ) -> Result<()> {
+        let value: T = deserialize_from_file(input.0, input.1)?;
+        serialize_to_file(output.0, &value, output.1)
+    }
+    // bincode is not self-describing, so it cannot contain labels (the labels of the
+    // spacial graph are detected when deserializing it)
+    let labeled = kind != DataKind::SpacialGraph
+        && FileFormat::new(input.1, input.0.as_ref())?.format != Format::Bincode;
+    match kind {
+        DataKind::SpacialGraph => {
+            let (graph, labeling) = deserialize_spacial_graph(input.0, input.1)?;
+            let plain = graph.weights.is_none() && graph.outputs.is_empty();
+            match (labeling, plain) {
+                (Some(labeling), true) => {
+                    let graph = labeling.label_spacial_graph(graph).graph;
+                    serialize_to_file(output.0, &graph, output.1)
+                },
+                (Some(labeling), false) => {
+                    let graph = labeling.label_spacial_graph(graph);
+                    serialize_to_file(output.0, &graph, output.1)
+                },
+                (None, true) => serialize_to_file(output.0, &graph.graph, output.1),
+                (None, false) => serialize_to_file(output.0, &graph, output.1),
+            }
+        },
+        DataKind::DependencyGraph if labeled => {
+            convert::<LabeledPartialOrderGraph<NodeLabel>>(input, output)
+        },
+        DataKind::DependencyGraph => convert::<PartialOrderGraph>(input, output),
+        DataKind::Paths if labeled => convert::<Vec<Path<NodeLabel>>>(input, output),
+        DataKind::Paths => convert::<Vec<Path>>(input, output),
+        DataKind::Constraints if labeled => {
+            convert::<Constraints<NodeLabel>>(input, output)
+        },
+        DataKind::Constraints => convert::<Constraints>(input, output),
+    }
+}
+
+/// Import a spacial graph from the `input` file in one of the [import::GraphFormat]s
+/// and write it as plain [SpacialGraph] to the `output` file. If `labels` is given, the
+/// original labels of the nodes are written to it, cf. [import::ImportedGraph].
+pub fn import_serialized(
+    input: (impl AsRef<path::Path>, &str),
+    output: (impl AsRef<path::Path>, &str),
+    labels: Option<(&path::Path, &str)>,
+) -> Result<()> {
+    let path = input.0.as_ref();
+    let format = GraphFormat::new(input.1, path)
+        .ok_or_else(|| import::ImportError::UnknownFormat(input.1.to_owned()))?;
+    let ImportedGraph { graph, labels: node_labels } = import::import_file(path, format)?;
+    serialize_to_file(output.0, &graph, output.1)?;
+    if let Some((file, format)) = labels {
+        serialize_to_file(file, &node_labels, format)?;
+    }
+    Ok(())
+}
+
+/// Read and check the input graphs, cf. [run_serialized]; the spacial graph is read as
+/// [SpacialGraphInput]. If the spacial graph is labeled, cf. [labels], the graphs are
+/// translated into the node ids, and the [Labeling] is returned, too.
+pub fn read_graphs(
+    spacial_graph: (impl AsRef<path::Path>, &str),
+    dependency_graph: impl Into<DependencyGraphSource>,
+) -> Result<(SpacialGraphInput, PartialOrderGraph, Option<Labeling<NodeLabel>>)> {
+    let (spacial_graph, labeling) =
+        deserialize_spacial_graph(spacial_graph.0, spacial_graph.1)?;
+    let num_bits = spacial_graph.graph.len();
+    if let Some(ref weights) = spacial_graph.weights {
+        if weights.len() != num_bits {
+            return Err(WrongNumberOfWeights(weights.len(), num_bits).into());
+        }
+    }
+    if let Some(output) = spacial_graph.outputs.iter().find(|output| **output >= num_bits)
+    {
+        return Err(UnknownOutput(*output).into());
+    }
+    let dependency_graph = match (dependency_graph.into(), labeling.as_ref()) {
+        (DependencyGraphSource::Graph(file, format), Some(labeling)) => {
+            let graph: LabeledPartialOrderGraph<NodeLabel> =
+                deserialize_from_file(file, &format)?;
+            labeling.dependency_graph(&graph)?
+        },
+        (DependencyGraphSource::Graph(file, format), None) => {
+            deserialize_from_file(file, &format)?
+        },
+        (DependencyGraphSource::Frames { frames, map }, labeling) => {
+            let storage = read_frames(frames.0, &frames.1)?;
+            let map: Vec<usize> = deserialize_from_file(map.0, &map.1)?;
+            let graph = frames::get_order(
+                storage.iter().map(|(qubit, stack)| (*qubit, stack)),
+                &map,
+            )?;
+            match labeling {
+                Some(labeling) => labeling.numbered_dependency_graph(&graph)?,
+                None => graph,
+            }
+        },
+        (DependencyGraphSource::Edges(file, format), Some(labeling)) => {
+            let edges: DependencyEdges<NodeLabel> = deserialize_from_file(file, &format)?;
+            let edges = edges
+                .iter()
+                .map(|(before, after)| Ok((labeling.id(before)?, labeling.id(after)?)))
+                .collect::<std::result::Result<DependencyEdges, LabelError>>()?;
+            edges::get_order(num_bits, &edges)?
+        },
+        (DependencyGraphSource::Edges(file, format), None) => {
+            let edges: DependencyEdges = deserialize_from_file(file, &format)?;
+            edges::get_order(num_bits, &edges)?
+        },
+    };
+    Ok((spacial_graph, dependency_graph, labeling))
+}
+
+/// Read [Constraints] from a file; `format` is one of the serialization formats that
+/// are also used by [run_serialized]. The nodes are read as [NodeLabel]s, cf.
+/// [Constraints::resolve], except for bincode, which is not self-describing.
+pub fn read_constraints(
+    path: impl AsRef<path::Path>,
+    format: &str,
+) -> Result<Constraints<NodeLabel>> {
+    let path = path.as_ref();
+    let format = FileFormat::new(format, path)?;
+    if format.format == Format::Bincode {
+        Ok(format.read::<Constraints>(path)?.into())
+    } else {
+        format.read(path)
+    }
+}
+
+/// Read [Path]s from a file, cf. [read_constraints].
+pub fn read_paths(path: impl AsRef<path::Path>, format: &str) -> Result<Vec<Path>> {
+    deserialize_from_file(path, format)
+}
+
+/// Read the storage of Pauli tracker frames from a file, which contains either the frames
+/// or only their storage, cf. [read_constraints] and [frames].
+pub fn read_frames(path: impl AsRef<path::Path>, format: &str) -> Result<FramesStorage> {
+    let path = path.as_ref();
+    let format = FileFormat::new(format, path)?;
+    // we cannot use an untagged enum, since it does not support the integer keys of the
+    // storage in, e.g., JSON, so we just try both (also for bincode, which is not
+    // self-describing, this works, cf. deserialize_spacial_graph)
+    let bytes = format.read_bytes(path)?;
+    match format.format.deserialize::<Frames<FramesStorage>>(&bytes) {
+        Ok(frames) => Ok(frames.into_storage()),
+        Err(_) => format.format.deserialize(&bytes),
+    }
+}
+
+/// Read an [AcceptFuncConfig] from a file, cf. [read_constraints].
+pub fn read_accept_func(
+    path: impl AsRef<path::Path>,
+    format: &str,
+) -> Result<AcceptFuncConfig> {
+    deserialize_from_file(path, format)
+}
+
+/// Write a `value` to a file; `format` is one of the serialization formats that are also
+/// used by [run_serialized].
+pub fn write_serialized(
+    path: impl AsRef<path::Path>,
+    format: &str,
+    value: &impl Serialize,
+) -> Result<()> {
+    serialize_to_file(path, value, format)
 }
 
-fn open(path: impl AsRef<path::Path>) -> io::Result<File> {
-    File::open(path)
+#[derive(Debug)]
+struct WrongNumberOfWeights(usize, usize);
+
+impl fmt::Display for WrongNumberOfWeights {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "got {} weights for {} nodes", self.0, self.1)
+    }
 }
 
-fn create(path: impl AsRef<path::Path>) -> io::Result<File> {
-    if let Some(parent) = path.as_ref().parent() {
-        fs::create_dir_all(parent)?;
+impl error::Error for WrongNumberOfWeights {}
+
+#[derive(Debug)]
+struct UnknownOutput(usize);
+
+impl fmt::Display for UnknownOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the output {} is not a node of the graph", self.0)
     }
-    File::create(path)
 }
 
+impl error::Error for UnknownOutput {}
+
 #[derive(Debug)]
-struct UnknownFormat(String);
+struct InvalidPath(usize, VerificationError);
 
-impl fmt::Display for UnknownFormat {
+impl fmt::Display for InvalidPath {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "unknown format: {}", self.0)
+        write!(f, "path {} is invalid: {}", self.0, self.1)
     }
 }
 
-impl error::Error for UnknownFormat {}
+impl error::Error for InvalidPath {}
 
 fn serialize_to_file<T: Serialize, P: AsRef<path::Path>>(
     path: P,
     value: &T,
     format: &str,
 ) -> Result<()> {
-    match format {
-        "serde_json" => serde_json::to_writer(create(path)?, value)?,
-        "bincode" => bincode::serialize_into(create(path)?, value)?,
-        _ => return Err(UnknownFormat(format.to_owned()).into()),
-    };
+    let path = path.as_ref();
+    FileFormat::new(format, path)?.write(path, value)
+}
+
+// write to a temporary file first and then rename it, so that `path` is never left with
+// a partially written value
+fn replace_file<T: Serialize>(path: &path::Path, value: &T, format: &str) -> Result<()> {
+    // resolve the format before, since the temporary file has a different extension
+    let format = FileFormat::new(format, path)?;
+    if path.as_os_str() == STDIO {
+        return format.write(path, value);
+    }
+    let mut tmp = OsString::from(path.as_os_str());
+    tmp.push(".tmp");
+    format.write(path::Path::new(&tmp), value)?;
+    fs::rename(tmp, path)?;
     Ok(())
 }
 
+// write the paths with `replace_file`, translated into the labels if there are any
+fn write_paths(
+    path: &path::Path,
+    paths: &[Path],
+    format: &str,
+    labeling: Option<&Labeling<NodeLabel>>,
+) -> Result<()> {
+    match labeling {
+        Some(labeling) => {
+            let paths: Vec<_> =
+                paths.iter().map(|path| labeling.label_path(path.clone())).collect();
+            replace_file(path, &paths, format)
+        },
+        None => replace_file(path, &paths, format),
+    }
+}
+
+// read the paths, translated from the labels if there are any
+fn read_labeled_paths(
+    path: impl AsRef<path::Path>,
+    format: &str,
+    labeling: Option<&Labeling<NodeLabel>>,
+) -> Result<Vec<Path>> {
+    match labeling {
+        Some(labeling) => {
+            let paths: Vec<Path<NodeLabel>> = deserialize_from_file(path, format)?;
+            Ok(paths
+                .into_iter()
+                .map(|path| labeling.id_path(path))
+                .collect::<std::result::Result<_, _>>()?)
+        },
+        None => read_paths(path, format),
+    }
+}
+
 fn deserialize_from_file<T: DeserializeOwned, P: AsRef<path::Path>>(
     path: P,
     format: &str,
 ) -> Result<T> {
-    Ok(match format {
-        "serde_json" => serde_json::from_reader(open(path)?)?,
-        "bincode" => bincode::deserialize_from(open(path)?)?,
-        _ => return Err(UnknownFormat(format.to_owned()).into()),
+    let path = path.as_ref();
+    FileFormat::new(format, path)?.read(path)
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum SpacialGraphFormat {
+    Plain(SpacialGraph),
+    Full(SpacialGraphInput),
+    Labeled(LabeledSpacialGraph<NodeLabel>),
+    LabeledFull(LabeledSpacialGraphInput<NodeLabel>),
+}
+
+fn deserialize_spacial_graph<P: AsRef<path::Path>>(
+    path: P,
+    format: &str,
+) -> Result<(SpacialGraphInput, Option<Labeling<NodeLabel>>)> {
+    let path = path.as_ref();
+    if let Some(format) = GraphFormat::new(format, path) {
+        let ImportedGraph { graph, labels } = import::import_file(path, format)?;
+        return Ok((
+            SpacialGraphInput { graph, ..Default::default() },
+            Labeling::from_imported(labels),
+        ));
+    }
+    let format = FileFormat::new(format, path)?;
+    Ok(match format.format {
+        // bincode is not self-describing, so we cannot use the untagged enum; instead we
+        // just try the variants (a shorter variant cannot be deserialized as a longer
+        // one, because the data ends too early)
+        Format::Bincode => {
+            let bytes = format.read_bytes(path)?;
+            let graph = if let Ok(graph) = bincode::deserialize(&bytes) {
+                graph
+            } else if let Ok((graph, weights)) = bincode::deserialize(&bytes) {
+                SpacialGraphInput {
+                    graph,
+                    weights,
+                    outputs: Vec::new(),
+                }
+            } else {
+                SpacialGraphInput {
+                    graph: bincode::deserialize(&bytes)?,
+                    ..Default::default()
+                }
+            };
+            (graph, None)
+        },
+        _ => match format.read(path)? {
+            SpacialGraphFormat::Plain(graph) => {
+                (SpacialGraphInput { graph, ..Default::default() }, None)
+            },
+            SpacialGraphFormat::Full(graph) => (graph, None),
+            SpacialGraphFormat::Labeled(graph) => {
+                let (labeling, graph) = Labeling::from_spacial_graph(&graph)?;
+                (SpacialGraphInput { graph, ..Default::default() }, Some(labeling))
+            },
+            SpacialGraphFormat::LabeledFull(LabeledSpacialGraphInput {
+                graph,
+                weights,
+                outputs,
+            }) => {
+                let (labeling, graph) = Labeling::from_spacial_graph(&graph)?;
+                let outputs = labeling.ids(&outputs)?;
+                (SpacialGraphInput { graph, weights, outputs }, Some(labeling))
+            },
+        },
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+    use crate::probabilistic::HeavysideParameters;
+
+    #[test]
+    fn spacial_graph_input() {
+        let graph = vec![vec![1], vec![0, 2], vec![1]];
+        let full = SpacialGraphInput {
+            graph: graph.clone(),
+            weights: Some(vec![1, 2, 3]),
+            outputs: vec![2],
+        };
+        let weighted = (graph.clone(), Some(vec![1, 2, 3]));
+        let plain = SpacialGraphInput {
+            graph: graph.clone(),
+            ..Default::default()
+        };
+        let dir = env::temp_dir().join("mbqc_scheduling_spacial_graph_input");
+        for format in ["serde_json", "bincode"] {
+            let file = dir.join(format!("plain_{format}"));
+            serialize_to_file(&file, &graph, format).unwrap();
+            assert_eq!(deserialize_spacial_graph(&file, format).unwrap().0, plain);
+            let file = dir.join(format!("full_{format}"));
+            serialize_to_file(&file, &full, format).unwrap();
+            assert_eq!(deserialize_spacial_graph(&file, format).unwrap().0, full);
+        }
+        // the format before the outputs were added
+        let file = dir.join("weighted_bincode");
+        serialize_to_file(&file, &weighted, "bincode").unwrap();
+        assert_eq!(
+            deserialize_spacial_graph(&file, "bincode").unwrap().0,
+            SpacialGraphInput {
+                graph,
+                weights: weighted.1,
+                outputs: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn convert_spacial_graph() {
+        let dir = env::temp_dir().join("mbqc_scheduling_convert_spacial_graph");
+        let full = SpacialGraphInput {
+            graph: vec![vec![1], vec![0]],
+            weights: None,
+            outputs: vec![1],
+        };
+        serialize_to_file(dir.join("json"), &full, "serde_json").unwrap();
+        convert_serialized(
+            DataKind::SpacialGraph,
+            (dir.join("json"), "serde_json"),
+            (dir.join("bincode"), "bincode"),
+        )
+        .unwrap();
+        assert_eq!(
+            deserialize_spacial_graph(dir.join("bincode"), "bincode").unwrap().0,
+            full
+        );
+        // without weights and outputs, the plain graph is written
+        serialize_to_file(dir.join("json"), &full.graph, "serde_json").unwrap();
+        convert_serialized(
+            DataKind::SpacialGraph,
+            (dir.join("json"), "serde_json"),
+            (dir.join("bincode"), "bincode"),
+        )
+        .unwrap();
+        let graph: SpacialGraph =
+            deserialize_from_file(dir.join("bincode"), "bincode").unwrap();
+        assert_eq!(graph, full.graph);
+    }
+
+    #[test]
+    fn labeled() {
+        // "a" - 10 - "b", with the output "b" and 10 depending on "a"
+        let graph: LabeledSpacialGraph<NodeLabel> = vec![
+            (10.into(), vec!["a".into(), "b".into()]),
+            ("a".into(), vec![10.into()]),
+            ("b".into(), vec![10.into()]),
+        ];
+        let order = vec![vec![("a".into(), vec![])], vec![(10.into(), vec!["a".into()])]];
+        let constraints = Constraints {
+            latency: 1,
+            ..Default::default()
+        };
+        let (paths, _) = run_labeled(
+            &graph,
+            None,
+            &["b".into()],
+            &order,
+            true,
+            None,
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+        )
+        .unwrap();
+        // with the latency, there is an empty step
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].steps, vec![vec!["a".into()], vec![], vec![10.into()]]);
+
+        let dir = env::temp_dir().join("mbqc_scheduling_labeled");
+        let input = LabeledSpacialGraphInput {
+            graph,
+            weights: None,
+            outputs: vec!["b".into()],
+        };
+        serialize_to_file(dir.join("spacial.json"), &input, "auto").unwrap();
+        serialize_to_file(dir.join("dependency.json"), &order, "auto").unwrap();
+        run_serialized(
+            (dir.join("spacial.json"), "auto"),
+            (dir.join("dependency.json"), "auto"),
+            true,
+            None,
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+            Preprocessing::None,
+            (dir.join("paths.json"), "auto"),
+            None,
+            None,
+            None,
+            None,
+        )
+        .unwrap();
+        let written: Vec<Path<NodeLabel>> =
+            deserialize_from_file(dir.join("paths.json"), "auto").unwrap();
+        assert_eq!(written[0].steps, paths[0].steps);
+        let verified = verify_serialized(
+            (dir.join("spacial.json"), "auto"),
+            (dir.join("dependency.json"), "auto"),
+            &constraints,
+            (dir.join("paths.json"), "auto"),
+        )
+        .unwrap();
+        assert!(verified.iter().all(|result| result.is_ok()));
+    }
+
+    #[test]
+    fn frames() {
+        // cf. the test in the frames module
+        let stack = |z, x| frames::FramesStack::try_from_str(z, x).unwrap();
+        let storage: FramesStorage = [
+            (0, stack("", "")),
+            (1, stack("10", "00")),
+            (2, stack("01", "10")),
+            (3, stack("1", "0")),
+        ]
+        .into_iter()
+        .collect();
+        let order =
+            vec![vec![(0, vec![])], vec![(1, vec![0]), (3, vec![0])], vec![(2, vec![3])]];
+
+        let dir = env::temp_dir().join("mbqc_scheduling_frames");
+        let spacial = dir.join("spacial.json");
+        serialize_to_file(
+            &spacial,
+            &vec![vec![1], vec![0, 2], vec![1, 3], vec![2]],
+            "auto",
+        )
+        .unwrap();
+        serialize_to_file(dir.join("map.json"), &[0, 3], "auto").unwrap();
+        let wrapped = Frames::new_unchecked(storage.clone(), 2);
+        for format in ["serde_json", "bincode"] {
+            // the frames themselves and only their storage
+            let wrapped_file = dir.join(format!("frames_{format}"));
+            serialize_to_file(&wrapped_file, &wrapped, format).unwrap();
+            let bare_file = dir.join(format!("storage_{format}"));
+            serialize_to_file(&bare_file, &storage, format).unwrap();
+            for file in [wrapped_file, bare_file] {
+                let source = DependencyGraphSource::Frames {
+                    frames: (file, format.into()),
+                    map: (dir.join("map.json"), "auto".into()),
+                };
+                let (_, graph, _) = read_graphs((&spacial, "auto"), source).unwrap();
+                assert_eq!(graph, order);
+            }
+        }
+
+        serialize_to_file(dir.join("map.json"), &[0, 4], "auto").unwrap();
+        let source = DependencyGraphSource::Frames {
+            frames: (dir.join("storage_serde_json"), "serde_json".into()),
+            map: (dir.join("map.json"), "auto".into()),
+        };
+        assert!(read_graphs((&spacial, "auto"), source).is_err());
+    }
+
+    #[test]
+    fn edges() {
+        let dir = env::temp_dir().join("mbqc_scheduling_edges");
+        let spacial = dir.join("spacial.json");
+        let edges = dir.join("edges.json");
+        // "a" - "b" - 2, with "a" before "b" before 2 and the implied "a" before 2
+        let graph: LabeledSpacialGraph<NodeLabel> = vec![
+            ("a".into(), vec!["b".into()]),
+            ("b".into(), vec!["a".into(), 2.into()]),
+            (2.into(), vec!["b".into()]),
+        ];
+        serialize_to_file(&spacial, &graph, "auto").unwrap();
+        let labeled: DependencyEdges<NodeLabel> = vec![
+            ("a".into(), "b".into()),
+            ("b".into(), 2.into()),
+            ("a".into(), 2.into()),
+        ];
+        serialize_to_file(&edges, &labeled, "auto").unwrap();
+        let source = DependencyGraphSource::Edges(edges.clone(), "auto".into());
+        let (_, order, labeling) = read_graphs((&spacial, "auto"), source).unwrap();
+        let labeling = labeling.unwrap();
+        let id = |label: NodeLabel| labeling.id(&label).unwrap();
+        let (a, b, c) = (id("a".into()), id("b".into()), id(2.into()));
+        // the implied dependency is kept, since it might have a specific latency
+        let mut c_deps = vec![a, b];
+        c_deps.sort_unstable();
+        assert_eq!(order, vec![vec![(a, vec![])], vec![(b, vec![a])], vec![(c, c_deps)]]);
+
+        serialize_to_file(&edges, &vec![("a", "c")], "auto").unwrap();
+        let source = DependencyGraphSource::Edges(edges, "auto".into());
+        assert!(read_graphs((&spacial, "auto"), source).is_err());
+    }
+
+    #[test]
+    fn checkpoint() {
+        let dir = env::temp_dir().join("mbqc_scheduling_checkpoint");
+        let spacial = dir.join("spacial.json");
+        let edges = dir.join("edges.json");
+        let checkpoint = dir.join("checkpoint.json");
+        // "a" - "b" - 2, with "a" before "b" and 2
+        let graph: LabeledSpacialGraph<NodeLabel> = vec![
+            ("a".into(), vec!["b".into()]),
+            ("b".into(), vec!["a".into(), 2.into()]),
+            (2.into(), vec!["b".into()]),
+        ];
+        serialize_to_file(&spacial, &graph, "auto").unwrap();
+        let labeled: DependencyEdges<NodeLabel> =
+            vec![("a".into(), "b".into()), ("a".into(), 2.into())];
+        serialize_to_file(&edges, &labeled, "auto").unwrap();
+        let source = || DependencyGraphSource::Edges(edges.clone(), "auto".into());
+        let (input, order, labeling) = read_graphs((&spacial, "auto"), source()).unwrap();
+        let paths = run(
+            &input.graph,
+            None,
+            &input.outputs,
+            &order,
+            Mode::Search,
+            None,
+            1,
+            None,
+            None,
+            &Constraints::default(),
+            Objective::Space,
+        )
+        .unwrap();
+        let costs = |paths: &[Path]| {
+            paths
+                .iter()
+                .map(|path| {
+                    (path.time, path.space, path.volume, path.idle, path.optimality)
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // what the checkpoint callback of run_serialized does
+        write_paths(&checkpoint, &paths, "auto", labeling.as_ref()).unwrap();
+        let written: Vec<Path<NodeLabel>> =
+            deserialize_from_file(&checkpoint, "auto").unwrap();
+        assert_eq!(written[0].steps[0], vec![NodeLabel::from("a")]);
+
+        // resuming from the checkpoint gives the same paths
+        let resumed = read_labeled_paths(&checkpoint, "auto", labeling.as_ref()).unwrap();
+        assert_eq!(costs(&resumed), costs(&paths));
+        for (resumed, path) in resumed.iter().zip(paths.iter()) {
+            assert_eq!(resumed.steps, path.steps);
+        }
+        let verified = verify_serialized(
+            (&spacial, "auto"),
+            source(),
+            &Constraints::default(),
+            (&checkpoint, "auto"),
+        )
+        .unwrap()
+        .into_iter()
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .unwrap();
+        assert_eq!(
+            verified
+                .iter()
+                .map(|path| (path.time, path.space))
+                .collect::<Vec<_>>(),
+            paths.iter().map(|path| (path.time, path.space)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn replace_file_atomically() {
+        let dir = env::temp_dir().join("mbqc_scheduling_replace_file");
+        let file = dir.join("paths.json");
+        let tmp = dir.join("paths.json.tmp");
+        let _ = fs::remove_dir_all(&dir);
+        let read = || deserialize_from_file::<Vec<u8>, _>(&file, "auto").unwrap();
+        serialize_to_file(&file, &vec![1], "auto").unwrap();
+        // if the new content cannot be written, the old file is left untouched
+        fs::create_dir_all(&tmp).unwrap();
+        assert!(replace_file(&file, &vec![2], "auto").is_err());
+        assert_eq!(read(), vec![1]);
+        fs::remove_dir(&tmp).unwrap();
+        // otherwise it is replaced, without leaving the temporary file behind
+        replace_file(&file, &vec![2], "auto").unwrap();
+        assert_eq!(read(), vec![2]);
+        assert!(!tmp.exists());
+    }
+
+    #[test]
+    fn remaining_limits() {
+        let limits = Limits {
+            timeout: Some(Duration::from_secs(10)),
+            stall_timeout: Some(Duration::from_secs(3)),
+            node_budget: Some(100),
+            ..Default::default()
+        };
+        let remaining = limits.remaining(Duration::from_secs(4), 30);
+        assert_eq!(remaining.timeout, Some(Duration::from_secs(6)));
+        assert_eq!(remaining.stall_timeout, Some(Duration::from_secs(3)));
+        assert_eq!(remaining.node_budget, Some(70));
+        let exhausted = limits.remaining(Duration::from_secs(20), 200);
+        assert_eq!(exhausted.timeout, Some(Duration::ZERO));
+        assert_eq!(exhausted.node_budget, Some(0));
+        assert_eq!(
+            Limits::default().remaining(Duration::from_secs(1), 1),
+            Limits::default()
+        );
+    }
+
+    #[test]
+    fn invalid_constraints() {
+        let graph = vec![vec![1], vec![0]];
+        let order = vec![vec![(0, vec![])], vec![(1, vec![0])]];
+        let cases = [
+            (
+                Constraints {
+                    max_step_size: Some(0),
+                    ..Default::default()
+                },
+                ConstraintsError::ZeroStepSize,
+            ),
+            (
+                Constraints {
+                    deadlines: vec![(2, 1)],
+                    ..Default::default()
+                },
+                ConstraintsError::UnknownNode { node: 2, num_nodes: 2 },
+            ),
+            (
+                Constraints {
+                    edge_latencies: vec![(0, 5, 1)],
+                    ..Default::default()
+                },
+                ConstraintsError::UnknownNode { node: 5, num_nodes: 2 },
+            ),
+            (
+                Constraints {
+                    edge_latencies: vec![(1, 0, 1)],
+                    ..Default::default()
+                },
+                ConstraintsError::NotADependency { dependency: 1, dependent: 0 },
+            ),
+        ];
+        for (constraints, expected) in cases {
+            for mode in [Mode::TimeOptimal, Mode::Search, Mode::Auto] {
+                let error = run(
+                    &graph,
+                    None,
+                    &[],
+                    &order,
+                    mode,
+                    None,
+                    1,
+                    None,
+                    None,
+                    &constraints,
+                    Objective::Space,
+                )
+                .unwrap_err();
+                assert_eq!(error, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn implied_latency() {
+        // 0 - 1 - 2, where 2 depends on 0 only via 1, but with a large latency on 0 -> 2
+        let graph = vec![vec![1], vec![0, 2], vec![1]];
+        let order = vec![vec![(0, vec![])], vec![(1, vec![0])], vec![(2, vec![1])]];
+        let constraints = Constraints {
+            edge_latencies: vec![(0, 2, 3)],
+            ..Default::default()
+        };
+        let expected = vec![vec![0], vec![1], vec![], vec![], vec![2]];
+        assert_eq!(bounds(&graph, None, &[], &order, &constraints).time, 5);
+        for mode in
+            [Mode::TimeOptimal, Mode::Search, Mode::Portfolio(Portfolio::default())]
+        {
+            let paths = run(
+                &graph,
+                None,
+                &[],
+                &order,
+                mode,
+                None,
+                1,
+                None,
+                None,
+                &constraints,
+                Objective::Space,
+            )
+            .unwrap();
+            assert!(paths.iter().all(|path| path.steps == expected));
+        }
+        let (paths, _) = run_windowed(
+            &graph,
+            None,
+            &[],
+            &order,
+            Window { layers: 1, commit: 1 },
+            None,
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+        )
+        .unwrap();
+        assert_eq!(paths[0].steps, expected);
+        // the implied edge is kept when the order is computed from the edges
+        let paths = run_from_edges(
+            &graph,
+            None,
+            &[],
+            &[(0, 1), (1, 2), (0, 2)],
+            Mode::TimeOptimal,
+            None,
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+        )
+        .unwrap();
+        assert_eq!(paths[0].steps, expected);
+        assert!(
+            verify::verify(&graph, None, &[], &order, &constraints, &expected).is_ok()
+        );
+        let too_early = vec![vec![0], vec![1], vec![2]];
+        assert!(
+            verify::verify(&graph, None, &[], &order, &constraints, &too_early).is_err()
+        );
+    }
+
+    #[test]
+    fn threaded_deadlines() {
+        // the task roots that measure 0 first are dead ends, because 1 and 2 cannot both
+        // be measured at step 1
+        let graph = vec![vec![1], vec![0, 2], vec![1]];
+        let order = vec![vec![(0, vec![]), (1, vec![]), (2, vec![])]];
+        let constraints = Constraints {
+            max_step_size: Some(1),
+            deadlines: vec![(1, 1), (2, 1)],
+            ..Default::default()
+        };
+        let run = |nthreads, probabilistic| {
+            run(
+                &graph,
+                None,
+                &[],
+                &order,
+                Mode::Search,
+                None,
+                nthreads,
+                None,
+                probabilistic,
+                &constraints,
+                Objective::Space,
+            )
+            .unwrap()
+            .into_iter()
+            .map(|path| (path.steps, path.optimality))
+            .collect::<Vec<_>>()
+        };
+        let expected = run(1, None);
+        assert_eq!(
+            expected,
+            vec![(vec![vec![2], vec![1], vec![0]], Optimality::Optimal)]
+        );
+        for nthreads in [2, 3] {
+            assert_eq!(run(nthreads, None), expected);
+            let accept =
+                Some((AcceptFunc::Custom(Box::new(|_, _, _, _, _, _, _| 1.)), Some(0)));
+            assert_eq!(run(nthreads, accept), expected);
+        }
+    }
+
+    #[test]
+    fn accept_func_metadata() {
+        let graph = vec![vec![1], vec![0, 2], vec![1]];
+        let order = vec![vec![(0, vec![])], vec![(1, vec![0])], vec![(2, vec![1])]];
+        let run = |accept_func| {
+            run_with_metadata(
+                &graph,
+                None,
+                &[],
+                &order,
+                Mode::Search,
+                None,
+                1,
+                None,
+                Some((accept_func, Some(0))),
+                &Constraints::default(),
+                Objective::Space,
+            )
+            .unwrap()
+            .1
+            .accept_func
+        };
+        let param = HeavysideParameters {
+            cutoff: 1.,
+            ..Default::default()
+        };
+        assert_eq!(
+            run(AcceptFunc::ParametrizedHeavyside { param }),
+            Some(AcceptFuncConfig::ParametrizedHeavyside(param))
+        );
+        assert_eq!(run(AcceptFunc::Custom(Box::new(|_, _, _, _, _, _, _| 1.))), None);
+    }
+
+    #[test]
+    fn decomposed() {
+        // two chains 0 - 1 and 2 - 3, each with the dependency 1 -> 0 (respectively
+        // 3 -> 2)
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        let order =
+            vec![vec![(1, vec![]), (3, vec![])], vec![(0, vec![1]), (2, vec![3])]];
+        let constraints = Constraints::default();
+        let (paths, metadata) = run_decomposed(
+            &graph,
+            None,
+            &[],
+            &order,
+            true,
+            None,
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+        )
+        .unwrap();
+        assert_eq!(metadata.components.len(), 2);
+        for path in paths.iter() {
+            let verified =
+                verify::verify(&graph, None, &[], &order, &constraints, &path.steps)
+                    .unwrap();
+            assert_eq!((verified.time, verified.space), (path.time, path.space));
+        }
+        let costs: Vec<_> = paths.iter().map(|path| (path.time, path.space)).collect();
+        assert!(costs.contains(&(2, 4)));
+        assert!(costs.contains(&(4, 2)));
+    }
+
+    #[test]
+    fn portfolio() {
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let order = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        let constraints = Constraints::default();
+        let run = |mode| {
+            run_with_metadata(
+                &graph,
+                None,
+                &[],
+                &order,
+                mode,
+                None,
+                1,
+                None,
+                None,
+                &constraints,
+                Objective::Space,
+            )
+            .unwrap()
+        };
+        let costs = |paths: &[Path]| {
+            paths.iter().map(|path| (path.time, path.space)).collect::<Vec<_>>()
+        };
+        let (exact, _) = run(Mode::Search);
+        let (paths, metadata) = run(Mode::Portfolio(Portfolio {
+            exact: true,
+            probabilistic: 1,
+            greedy: 2,
+        }));
+        assert_eq!(costs(&paths), costs(&exact));
+        assert!(paths.iter().all(|path| path.optimality == Optimality::Optimal));
+        assert_eq!(metadata.strategy, Strategy::Portfolio);
+        assert_eq!(metadata.nthreads, 4);
+        let stats = metadata.stats.unwrap();
+        assert!(metadata.seed.is_some());
+        assert_eq!(stats.seed, metadata.seed);
+        assert_eq!(stats.threads.len(), 4);
+        // the exact search stops the others, which is not a timeout
+        assert!(!stats.timed_out);
+        assert_eq!(stats.stop_reason, None);
+        assert!(stats.threads.iter().all(|thread| !thread.timed_out));
+    }
+
+    #[test]
+    fn windowed() {
+        // a ladder with 10 rungs, where each rung depends on the previous one
+        let graph: SpacialGraph = (0..20)
+            .map(|bit: usize| {
+                let mut neighbors = vec![bit ^ 1];
+                neighbors.extend(bit.checked_sub(2));
+                neighbors.extend(Some(bit + 2).filter(|neighbor| *neighbor < 20));
+                neighbors
+            })
+            .collect();
+        let order: PartialOrderGraph = (0..10)
+            .map(|rung: usize| {
+                let deps: Vec<usize> = rung
+                    .checked_sub(1)
+                    .map(|previous| vec![2 * previous, 2 * previous + 1])
+                    .unwrap_or_default();
+                vec![(2 * rung, deps.clone()), (2 * rung + 1, deps)]
+            })
+            .collect();
+        let constraints = Constraints::default();
+        for probabilistic in [None, Some((AcceptFunc::BuiltinHeavyside, Some(42)))] {
+            let is_probabilistic = probabilistic.is_some();
+            let (paths, metadata) = run_windowed(
+                &graph,
+                None,
+                &[],
+                &order,
+                Window { layers: 3, commit: 1 },
+                None,
+                1,
+                None,
+                probabilistic,
+                &constraints,
+                Objective::Space,
+            )
+            .unwrap();
+            assert_eq!(paths.len(), 1);
+            let verified =
+                verify::verify(&graph, None, &[], &order, &constraints, &paths[0].steps)
+                    .unwrap();
+            assert_eq!((verified.time, verified.space), (paths[0].time, paths[0].space));
+            assert_eq!(paths[0].optimality, Optimality::BestFound);
+            assert_eq!(metadata.strategy, Strategy::Windowed);
+            assert_eq!(metadata.seed.is_some(), is_probabilistic);
+            assert_eq!(metadata.stats.unwrap().seed, metadata.seed);
+        }
+
+        // the node budget is shared by all windows; the windows after it is exhausted
+        // fall back to their time-optimal paths
+        let (paths, metadata) = run_windowed(
+            &graph,
+            None,
+            &[],
+            &order,
+            Window { layers: 3, commit: 1 },
+            Limits {
+                node_budget: Some(10),
+                ..Default::default()
+            },
+            1,
+            None,
+            None,
+            &constraints,
+            Objective::Space,
+        )
+        .unwrap();
+        assert_eq!(paths.len(), 1);
+        let stats = metadata.stats.unwrap();
+        // each window explores at least one node
+        assert!(stats.explored < 10 + paths[0].time as u64);
+        assert!(stats.fallback);
+        assert_eq!(stats.stop_reason, Some(StopReason::NodeBudget));
+    }
+}
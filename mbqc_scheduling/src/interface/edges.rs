@@ -0,0 +1,156 @@
+/*!
+Computing the dependency graph from a plain list of dependency edges, cf.
+[run_from_edges](super::run_from_edges).
+
+Instead of the layered [PartialOrderGraph], the dependencies can be given as a flat
+list of `(before, after)` edges, i.e., the node `after` can only be measured after the
+node `before` has been measured. [get_order] computes the layering (every node is in
+the layer after its latest dependency), and it reports cycles as errors. Dependencies
+that are implied by other dependencies are kept, since they might have a specific
+latency, cf. [Constraints::edge_latencies](super::Constraints::edge_latencies); the
+scheduler drops them itself if the latencies allow it, cf.
+[reduce_dependencies](crate::scheduler::time::reduce_dependencies).
+*/
+
+use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+use thiserror::Error;
+
+use super::ConstraintsError;
+
+/// A list of `(before, after)` dependency edges, cf. the [module](self) documentation.
+pub type DependencyEdges<L = usize> = Vec<(L, L)>;
+
+/// Errors when computing the dependency graph from the edges.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum EdgesError {
+    #[error("the edge ({0}, {1}) refers to a node that is not in the graph ({2} nodes)")]
+    UnknownNode(usize, usize, usize),
+    #[error("the dependencies are cyclic: node {0} depends (indirectly) on itself")]
+    Cycle(usize),
+    #[error(transparent)]
+    Constraints(#[from] ConstraintsError),
+}
+
+/// Compute the dependency graph of the nodes `0..num_nodes` from the dependency `edges`,
+/// cf. the [module](self) documentation. Every node is part of the returned graph, and
+/// the layers and the dependencies are sorted. Duplicate edges are ignored.
+///
+/// # Examples
+/// ```
+/// # use mbqc_scheduling::interface::edges::get_order;
+/// // 0 -> 1 -> 2 and the implied 0 -> 2; 3 is independent
+/// let order = get_order(4, &[(0, 1), (1, 2), (0, 2)]).unwrap();
+/// assert_eq!(
+///     order,
+///     vec![
+///         vec![(0, vec![]), (3, vec![])],
+///         vec![(1, vec![0])],
+///         vec![(2, vec![0, 1])]
+///     ]
+/// );
+/// ```
+pub fn get_order(
+    num_nodes: usize,
+    edges: &[(usize, usize)],
+) -> Result<PartialOrderGraph, EdgesError> {
+    let mut dependencies = vec![Vec::new(); num_nodes];
+    for &(before, after) in edges {
+        if before >= num_nodes || after >= num_nodes {
+            return Err(EdgesError::UnknownNode(before, after, num_nodes));
+        }
+        if before == after {
+            return Err(EdgesError::Cycle(before));
+        }
+        dependencies[after].push(before);
+    }
+    for deps in dependencies.iter_mut() {
+        deps.sort_unstable();
+        deps.dedup();
+    }
+
+    let order = topological_order(&dependencies)?;
+
+    // the layer of a node is one after the latest layer of its dependencies
+    let mut layers = vec![0; num_nodes];
+    for &node in order.iter() {
+        for &dep in dependencies[node].iter() {
+            layers[node] = layers[node].max(layers[dep] + 1);
+        }
+    }
+
+    let num_layers = layers.iter().max().map_or(0, |max| max + 1);
+    let mut graph = vec![Vec::new(); num_layers];
+    for (node, deps) in dependencies.into_iter().enumerate() {
+        graph[layers[node]].push((node, deps));
+    }
+    Ok(graph)
+}
+
+// Kahn's algorithm; if it gets stuck, we follow the remaining dependencies until we hit a
+// node twice, which is then on a cycle (cf. the frames module)
+fn topological_order(dependencies: &[Vec<usize>]) -> Result<Vec<usize>, EdgesError> {
+    let mut open: Vec<usize> = dependencies.iter().map(Vec::len).collect();
+    let mut dependents = vec![Vec::new(); dependencies.len()];
+    for (node, deps) in dependencies.iter().enumerate() {
+        for &dep in deps {
+            dependents[dep].push(node);
+        }
+    }
+    let mut order: Vec<usize> =
+        (0..dependencies.len()).filter(|n| open[*n] == 0).collect();
+    let mut next = 0;
+    while let Some(&node) = order.get(next) {
+        next += 1;
+        for &dependent in dependents[node].iter() {
+            open[dependent] -= 1;
+            if open[dependent] == 0 {
+                order.push(dependent);
+            }
+        }
+    }
+    let Some(mut node) = open.iter().position(|num| *num > 0) else {
+        return Ok(order);
+    };
+    let mut visited = vec![false; dependencies.len()];
+    while !visited[node] {
+        visited[node] = true;
+        node = *dependencies[node]
+            .iter()
+            .find(|dep| open[**dep] > 0)
+            .expect("an unresolved node has an unresolved dependency");
+    }
+    Err(EdgesError::Cycle(node))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn order() {
+        //   0 -> 1 -> 3 -> 4
+        //    \-> 2 ------/
+        // with the implied 0 -> 3 and 0 -> 4; 5 is independent
+        let edges = [(0, 1), (1, 3), (3, 4), (0, 2), (2, 4), (0, 3), (0, 4), (1, 3)];
+        assert_eq!(
+            get_order(6, &edges).unwrap(),
+            vec![
+                vec![(0, vec![]), (5, vec![])],
+                vec![(1, vec![0]), (2, vec![0])],
+                vec![(3, vec![0, 1])],
+                vec![(4, vec![0, 2, 3])],
+            ]
+        );
+        assert_eq!(get_order(0, &[]).unwrap(), PartialOrderGraph::new());
+        assert_eq!(
+            get_order(4, &[(0, 4)]).unwrap_err(),
+            EdgesError::UnknownNode(0, 4, 4)
+        );
+        assert_eq!(get_order(2, &[(1, 1)]).unwrap_err(), EdgesError::Cycle(1));
+        // 0 -> 1 -> 2 -> 3 -> 1
+        assert_eq!(
+            get_order(4, &[(0, 1), (1, 2), (2, 3), (3, 1)]).unwrap_err(),
+            EdgesError::Cycle(1)
+        );
+    }
+}
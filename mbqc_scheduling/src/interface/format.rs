@@ -0,0 +1,427 @@
+/*!
+The serialization formats of the file based functions in [interface](super), e.g.,
+[run_serialized](super::run_serialized).
+
+The format arguments there are strings, which are parsed into a [FileFormat]: the name
+of a [Format], optionally followed by `+gzip` or `+zstd` to compress the data, e.g.,
+`"serde_json+gzip"`, or `"auto"` to detect the format from the file extension, cf.
+[FileFormat::detect]. If no compression is specified, it is detected from the file
+extension, too. The file name `-` stands for stdin or stdout, respectively; when reading
+from stdin, compressed data is detected from its magic bytes.
+
+MessagePack, CBOR, YAML, gzip and zstd require the according cargo features `msgpack`,
+`cbor`, `yaml`, `gzip` and `zstd`.
+*/
+
+use std::{
+    fmt, fs,
+    io::{self, Read, Write},
+    path::Path,
+    str::FromStr,
+};
+
+use serde::{Serialize, de::DeserializeOwned};
+use thiserror::Error;
+
+use super::Result;
+
+/// The file name that stands for stdin or stdout.
+pub const STDIO: &str = "-";
+
+/// A serialization format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// JSON, with the names `serde_json` and `json`, and the extension `.json`.
+    Json,
+    /// [bincode](https://docs.rs/bincode/1), with the name `bincode`, and the
+    /// extensions `.bincode` and `.bin`.
+    Bincode,
+    /// MessagePack, with the names `msgpack` and `messagepack`, and the extensions
+    /// `.msgpack` and `.mpk`.
+    MessagePack,
+    /// CBOR, with the name `cbor`, and the extension `.cbor`.
+    Cbor,
+    /// YAML, with the names `yaml` and `yml`, and the extensions `.yaml` and `.yml`.
+    Yaml,
+}
+
+/// A compression of the serialized data.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Compression {
+    /// No compression.
+    #[default]
+    None,
+    /// gzip, with the name `gzip`, and the extension `.gz`.
+    Gzip,
+    /// zstd, with the name `zstd`, and the extensions `.zst` and `.zstd`.
+    Zstd,
+}
+
+/// A [Format] together with a [Compression].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FileFormat {
+    pub format: Format,
+    pub compression: Compression,
+}
+
+/// Errors when parsing or detecting a [FileFormat], or when using a format that has not
+/// been compiled in.
+#[derive(Debug, Error)]
+pub enum FormatError {
+    #[error("unknown format: {0}")]
+    Unknown(String),
+    #[error("cannot detect the format of {0} from its extension")]
+    Undetectable(String),
+    #[error("the {0} format requires the {1} feature")]
+    MissingFeature(String, &'static str),
+}
+
+impl Format {
+    /// The [Format] with the given extension, if any.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "json" => Some(Format::Json),
+            "bincode" | "bin" => Some(Format::Bincode),
+            "msgpack" | "mpk" => Some(Format::MessagePack),
+            "cbor" => Some(Format::Cbor),
+            "yaml" | "yml" => Some(Format::Yaml),
+            _ => None,
+        }
+    }
+
+    /// Serialize the `value` into bytes.
+    pub fn serialize(self, value: &impl Serialize) -> Result<Vec<u8>> {
+        Ok(match self {
+            Format::Json => serde_json::to_vec(value)?,
+            Format::Bincode => bincode::serialize(value)?,
+            Format::MessagePack => {
+                #[cfg(feature = "msgpack")]
+                {
+                    rmp_serde::to_vec_named(value)?
+                }
+                #[cfg(not(feature = "msgpack"))]
+                return Err(self.missing_feature("msgpack"));
+            },
+            Format::Cbor => {
+                #[cfg(feature = "cbor")]
+                {
+                    let mut bytes = Vec::new();
+                    ciborium::into_writer(value, &mut bytes)?;
+                    bytes
+                }
+                #[cfg(not(feature = "cbor"))]
+                return Err(self.missing_feature("cbor"));
+            },
+            Format::Yaml => {
+                #[cfg(feature = "yaml")]
+                {
+                    serde_yaml::to_string(value)?.into_bytes()
+                }
+                #[cfg(not(feature = "yaml"))]
+                return Err(self.missing_feature("yaml"));
+            },
+        })
+    }
+
+    /// Deserialize a value from the `bytes`.
+    pub fn deserialize<T: DeserializeOwned>(self, bytes: &[u8]) -> Result<T> {
+        Ok(match self {
+            Format::Json => serde_json::from_slice(bytes)?,
+            Format::Bincode => bincode::deserialize(bytes)?,
+            Format::MessagePack => {
+                #[cfg(feature = "msgpack")]
+                {
+                    rmp_serde::from_slice(bytes)?
+                }
+                #[cfg(not(feature = "msgpack"))]
+                return Err(self.missing_feature("msgpack"));
+            },
+            Format::Cbor => {
+                #[cfg(feature = "cbor")]
+                {
+                    ciborium::from_reader(bytes)?
+                }
+                #[cfg(not(feature = "cbor"))]
+                return Err(self.missing_feature("cbor"));
+            },
+            Format::Yaml => {
+                #[cfg(feature = "yaml")]
+                {
+                    serde_yaml::from_slice(bytes)?
+                }
+                #[cfg(not(feature = "yaml"))]
+                return Err(self.missing_feature("yaml"));
+            },
+        })
+    }
+
+    #[allow(dead_code)] // only used without some features
+    fn missing_feature(self, feature: &'static str) -> Box<dyn std::error::Error> {
+        FormatError::MissingFeature(self.to_string(), feature).into()
+    }
+}
+
+impl FromStr for Format {
+    type Err = FormatError;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "serde_json" | "json" => Ok(Format::Json),
+            "bincode" => Ok(Format::Bincode),
+            "msgpack" | "messagepack" => Ok(Format::MessagePack),
+            "cbor" => Ok(Format::Cbor),
+            "yaml" | "yml" => Ok(Format::Yaml),
+            _ => Err(FormatError::Unknown(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Format::Json => "serde_json",
+            Format::Bincode => "bincode",
+            Format::MessagePack => "msgpack",
+            Format::Cbor => "cbor",
+            Format::Yaml => "yaml",
+        })
+    }
+}
+
+impl Compression {
+    /// The [Compression] with the given extension, if any.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "gz" => Some(Compression::Gzip),
+            "zst" | "zstd" => Some(Compression::Zstd),
+            _ => None,
+        }
+    }
+
+    /// Detect the [Compression] from the magic bytes at the start of the `bytes`.
+    pub fn from_magic(bytes: &[u8]) -> Self {
+        if bytes.starts_with(&[0x1f, 0x8b]) {
+            Compression::Gzip
+        } else if bytes.starts_with(&[0x28, 0xb5, 0x2f, 0xfd]) {
+            Compression::Zstd
+        } else {
+            Compression::None
+        }
+    }
+
+    /// Compress the `bytes`.
+    pub fn compress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => bytes,
+            Compression::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let mut encoder = flate2::write::GzEncoder::new(
+                        Vec::new(),
+                        flate2::Compression::default(),
+                    );
+                    encoder.write_all(&bytes)?;
+                    encoder.finish()?
+                }
+                #[cfg(not(feature = "gzip"))]
+                return Err(FormatError::MissingFeature("gzip".into(), "gzip").into());
+            },
+            Compression::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    zstd::encode_all(bytes.as_slice(), 0)?
+                }
+                #[cfg(not(feature = "zstd"))]
+                return Err(FormatError::MissingFeature("zstd".into(), "zstd").into());
+            },
+        })
+    }
+
+    /// Decompress the `bytes`.
+    pub fn decompress(self, bytes: Vec<u8>) -> Result<Vec<u8>> {
+        Ok(match self {
+            Compression::None => bytes,
+            Compression::Gzip => {
+                #[cfg(feature = "gzip")]
+                {
+                    let mut decompressed = Vec::new();
+                    flate2::read::GzDecoder::new(bytes.as_slice())
+                        .read_to_end(&mut decompressed)?;
+                    decompressed
+                }
+                #[cfg(not(feature = "gzip"))]
+                return Err(FormatError::MissingFeature("gzip".into(), "gzip").into());
+            },
+            Compression::Zstd => {
+                #[cfg(feature = "zstd")]
+                {
+                    zstd::decode_all(bytes.as_slice())?
+                }
+                #[cfg(not(feature = "zstd"))]
+                return Err(FormatError::MissingFeature("zstd".into(), "zstd").into());
+            },
+        })
+    }
+}
+
+impl FileFormat {
+    /// Parse the `format` string (cf. the [module](self) documentation) for the file at
+    /// `path`.
+    pub fn new(format: &str, path: &Path) -> std::result::Result<Self, FormatError> {
+        if format == "auto" {
+            return Self::detect(path);
+        }
+        let (format, compression) = match format.split_once('+') {
+            Some((format, "gzip")) => (format, Some(Compression::Gzip)),
+            Some((format, "zstd")) => (format, Some(Compression::Zstd)),
+            Some(_) => return Err(FormatError::Unknown(format.to_owned())),
+            None => (format, None),
+        };
+        Ok(Self {
+            format: format.parse()?,
+            compression: compression.unwrap_or_else(|| {
+                path.extension()
+                    .and_then(|extension| extension.to_str())
+                    .and_then(Compression::from_extension)
+                    .unwrap_or_default()
+            }),
+        })
+    }
+
+    /// Detect the format from the file extension(s) of `path`, e.g., `paths.json` or
+    /// `paths.bincode.gz`. For [STDIO], it is uncompressed JSON.
+    pub fn detect(path: &Path) -> std::result::Result<Self, FormatError> {
+        if path.as_os_str() == STDIO {
+            return Ok(Self {
+                format: Format::Json,
+                compression: Compression::None,
+            });
+        }
+        let undetectable = || FormatError::Undetectable(path.display().to_string());
+        let get_extension = |path: &Path| {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .map(str::to_owned)
+        };
+        let mut extension = get_extension(path).ok_or_else(undetectable)?;
+        let compression = Compression::from_extension(&extension);
+        if compression.is_some() {
+            extension =
+                get_extension(&path.with_extension("")).ok_or_else(undetectable)?;
+        }
+        Ok(Self {
+            format: Format::from_extension(&extension).ok_or_else(undetectable)?,
+            compression: compression.unwrap_or_default(),
+        })
+    }
+
+    /// Read the (decompressed) bytes from the file at `path`.
+    pub fn read_bytes(self, path: &Path) -> Result<Vec<u8>> {
+        if path.as_os_str() == STDIO {
+            let mut bytes = Vec::new();
+            io::stdin().lock().read_to_end(&mut bytes)?;
+            let compression = Compression::from_magic(&bytes);
+            compression.decompress(bytes)
+        } else {
+            self.compression.decompress(fs::read(path)?)
+        }
+    }
+
+    /// Read a value from the file at `path`.
+    pub fn read<T: DeserializeOwned>(self, path: &Path) -> Result<T> {
+        self.format.deserialize(&self.read_bytes(path)?)
+    }
+
+    /// Write the `value` to the file at `path`, creating the parent directories if
+    /// necessary.
+    pub fn write(self, path: &Path, value: &impl Serialize) -> Result<()> {
+        let bytes = self.compression.compress(self.format.serialize(value)?)?;
+        if path.as_os_str() == STDIO {
+            let mut stdout = io::stdout().lock();
+            stdout.write_all(&bytes)?;
+            stdout.flush()?;
+        } else {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(path, bytes)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_and_detect() {
+        let new = |format, path| FileFormat::new(format, Path::new(path)).unwrap();
+        let file_format = |format, compression| FileFormat { format, compression };
+        assert_eq!(
+            new("serde_json", "paths"),
+            file_format(Format::Json, Compression::None)
+        );
+        assert_eq!(
+            new("bincode", "paths.gz"),
+            file_format(Format::Bincode, Compression::Gzip)
+        );
+        assert_eq!(
+            new("yaml+zstd", "paths"),
+            file_format(Format::Yaml, Compression::Zstd)
+        );
+        assert_eq!(
+            new("auto", "dir.d/paths.msgpack"),
+            file_format(Format::MessagePack, Compression::None)
+        );
+        assert_eq!(
+            new("auto", "paths.cbor.zst"),
+            file_format(Format::Cbor, Compression::Zstd)
+        );
+        assert_eq!(new("auto", STDIO), file_format(Format::Json, Compression::None));
+        assert!(matches!(
+            FileFormat::new("auto", Path::new("paths.gz")),
+            Err(FormatError::Undetectable(_))
+        ));
+        assert!(matches!(
+            FileFormat::new("json+lzma", Path::new("paths")),
+            Err(FormatError::Unknown(_))
+        ));
+    }
+
+    #[test]
+    fn roundtrip() {
+        let value = (vec![vec![1usize], vec![0]], Some(vec![3usize, 4]));
+        let dir = std::env::temp_dir().join("mbqc_scheduling_format_roundtrip");
+        let mut formats = vec!["serde_json", "bincode"];
+        if cfg!(feature = "msgpack") {
+            formats.push("msgpack");
+        }
+        if cfg!(feature = "cbor") {
+            formats.push("cbor");
+        }
+        if cfg!(feature = "yaml") {
+            formats.push("yaml");
+        }
+        let mut compressions = vec![""];
+        if cfg!(feature = "gzip") {
+            compressions.push("+gzip");
+        }
+        if cfg!(feature = "zstd") {
+            compressions.push("+zstd");
+        }
+        for format in formats {
+            for compression in compressions.iter() {
+                let format = format!("{format}{compression}");
+                let path = dir.join(&format);
+                let file_format = FileFormat::new(&format, &path).unwrap();
+                file_format.write(&path, &value).unwrap();
+                assert_eq!(
+                    file_format
+                        .read::<(Vec<Vec<usize>>, Option<Vec<usize>>)>(&path)
+                        .unwrap(),
+                    value
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,183 @@
+/*!
+Computing the dependency graph directly from the frames of a Pauli tracker, cf.
+[run_from_frames](super::run_from_frames).
+
+The frames are the [Frames](pauli_tracker::tracker::frames::Frames) of the
+[pauli_tracker] crate with a [Map] storage of [PauliStack]s on [BitVec]s, as they are,
+for example, serialized by the `pauli_tracker` Python package
+(`pauli_tracker.frames.map.Frames`); the file based functions in [interface](super), cf.
+[DependencyGraphSource](super::DependencyGraphSource), accept also just the storage.
+The *map* assigns to each frame the qubit whose measurement induced it, i.e., frame `i`
+belongs to the measurement of qubit `map[i]`, and a qubit depends on the measurements of
+the frames in which it has a Pauli correction, cf. [induced_order::get_order].
+*/
+
+use bitvec::{order::Lsb0, vec::BitVec};
+use hashbrown::{HashMap, HashSet};
+use pauli_tracker::{
+    boolean_vector::BooleanVector,
+    collection::Map,
+    pauli::PauliStack,
+    tracker::frames::induced_order::{self, PartialOrderGraph},
+};
+use thiserror::Error;
+
+use super::ConstraintsError;
+
+/// The [PauliStack]s of the frames, cf. the [module](self) documentation.
+pub type FramesStack = PauliStack<BitVec<u64, Lsb0>>;
+
+/// The storage of the frames, mapping the qubits to their [FramesStack]s.
+pub type FramesStorage = Map<FramesStack>;
+
+/// Errors when computing the dependency graph from the frames.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum FramesError {
+    #[error(
+        "qubit {qubit} has a correction in frame {frame}, but the map has only {len} \
+         entries"
+    )]
+    UnmappedFrame { qubit: usize, frame: usize, len: usize },
+    #[error("qubit {qubit} depends on qubit {dependency}, which is not in the frames")]
+    UnknownQubit { qubit: usize, dependency: usize },
+    #[error("the dependencies are cyclic: qubit {0} depends (indirectly) on itself")]
+    Cycle(usize),
+    #[error(transparent)]
+    Constraints(#[from] ConstraintsError),
+}
+
+/// Compute the dependency graph from the `frames` storage (qubits together with their
+/// Pauli stacks) and the `map`, cf. the [module](self) documentation. In contrast to
+/// [induced_order::get_order], the input is checked instead of panicking, and the output
+/// is deterministic, i.e., the layers and the dependencies are sorted.
+pub fn get_order<'l, B: BooleanVector + 'l>(
+    frames: impl IntoIterator<Item = (usize, &'l PauliStack<B>)>,
+    map: &[usize],
+) -> Result<PartialOrderGraph, FramesError> {
+    let mut frames: Vec<_> = frames.into_iter().collect();
+    frames.sort_by_key(|(qubit, _)| *qubit);
+    if frames.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut dependencies = HashMap::with_capacity(frames.len());
+    for (qubit, stack) in frames.iter() {
+        let mut deps = HashSet::new();
+        let flags =
+            stack.z.iter_vals().enumerate().chain(stack.x.iter_vals().enumerate());
+        for (frame, _) in flags.filter(|(_, flag)| *flag) {
+            let dependency = *map.get(frame).ok_or(FramesError::UnmappedFrame {
+                qubit: *qubit,
+                frame,
+                len: map.len(),
+            })?;
+            deps.insert(dependency);
+        }
+        dependencies.insert(*qubit, deps);
+    }
+    for (qubit, _) in frames.iter() {
+        let unknown = dependencies[qubit]
+            .iter()
+            .filter(|dep| !dependencies.contains_key(*dep))
+            .min();
+        if let Some(dependency) = unknown {
+            return Err(FramesError::UnknownQubit {
+                qubit: *qubit,
+                dependency: *dependency,
+            });
+        }
+    }
+    check_acyclic(&dependencies)?;
+
+    let mut graph = induced_order::get_order(frames, map);
+    induced_order::sort_layers_by_bits(&mut graph);
+    for (_, deps) in graph.iter_mut().flatten() {
+        deps.sort_unstable();
+    }
+    Ok(graph)
+}
+
+// Kahn's algorithm; if it gets stuck, we follow the remaining dependencies until we hit a
+// qubit twice, which is then on a cycle
+fn check_acyclic(
+    dependencies: &HashMap<usize, HashSet<usize>>,
+) -> Result<(), FramesError> {
+    let mut open: HashMap<usize, usize> = dependencies
+        .iter()
+        .map(|(qubit, deps)| (*qubit, deps.len()))
+        .collect();
+    let mut dependents: HashMap<usize, Vec<usize>> = HashMap::new();
+    for (qubit, deps) in dependencies {
+        for dep in deps {
+            dependents.entry(*dep).or_default().push(*qubit);
+        }
+    }
+    let mut ready: Vec<usize> = open
+        .iter()
+        .filter(|(_, num)| **num == 0)
+        .map(|(qubit, _)| *qubit)
+        .collect();
+    while let Some(qubit) = ready.pop() {
+        open.remove(&qubit);
+        for dependent in dependents.get(&qubit).into_iter().flatten() {
+            let num = open.get_mut(dependent).expect("not resolved yet");
+            *num -= 1;
+            if *num == 0 {
+                ready.push(*dependent);
+            }
+        }
+    }
+    let Some(mut qubit) = open.keys().min().copied() else {
+        return Ok(());
+    };
+    let mut visited = HashSet::new();
+    while visited.insert(qubit) {
+        qubit = *dependencies[&qubit]
+            .iter()
+            .filter(|dep| open.contains_key(*dep))
+            .min()
+            .expect("an unresolved qubit has an unresolved dependency");
+    }
+    Err(FramesError::Cycle(qubit))
+}
+
+#[cfg(test)]
+mod tests {
+    use pauli_tracker::collection::Iterable;
+
+    use super::*;
+
+    fn stack(z: &str, x: &str) -> FramesStack {
+        PauliStack::try_from_str(z, x).unwrap()
+    }
+
+    #[test]
+    fn order() {
+        // cf. the example of induced_order::get_order
+        let storage: FramesStorage = [
+            (0, stack("", "")),
+            (1, stack("10", "00")),
+            (2, stack("01", "10")),
+            (3, stack("1", "0")),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(
+            get_order(storage.iter_pairs(), &[0, 3]).unwrap(),
+            vec![vec![(0, vec![])], vec![(1, vec![0]), (3, vec![0])], vec![(2, vec![3])]]
+        );
+        assert_eq!(
+            get_order(storage.iter_pairs(), &[0]).unwrap_err(),
+            FramesError::UnmappedFrame { qubit: 2, frame: 1, len: 1 }
+        );
+        assert_eq!(
+            get_order(storage.iter_pairs(), &[0, 4]).unwrap_err(),
+            FramesError::UnknownQubit { qubit: 2, dependency: 4 }
+        );
+        // 3 depends on itself
+        assert_eq!(
+            get_order(storage.iter_pairs(), &[3, 1]).unwrap_err(),
+            FramesError::Cycle(3)
+        );
+    }
+}
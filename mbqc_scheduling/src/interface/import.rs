@@ -0,0 +1,548 @@
+/*!
+Importers for spacial graphs in common graph formats, cf. [GraphFormat].
+
+The node labels in the input are mapped to contiguous ids automatically: if all labels
+are non-negative integers, the ids follow their numerical order (so a graph whose nodes
+are already numbered from 0 keeps its numbering), otherwise the order in which the
+labels appear. The original labels are kept in [ImportedGraph::labels]. Loops and
+multi-edges are skipped, and edges are undirected. The formats that declare the number of
+nodes (DIMACS, graph6 and sparse6) may declare at most [MAX_NODES] nodes.
+
+The file based functions in [interface](super), e.g.,
+[run_serialized](super::run_serialized), accept the names of the [GraphFormat]s as
+format of the spacial graph (or `"auto"` for the according file extensions). If the
+nodes have been relabeled, the other inputs and the paths use the original labels, cf.
+[labels](super::labels). GraphML requires the cargo feature `graphml`.
+*/
+
+use std::{
+    fmt, fs,
+    io::{self, Read},
+    path::Path,
+    str::FromStr,
+};
+
+use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::format::STDIO;
+use crate::scheduler::space::{GraphBuffer, SpacialGraph};
+
+/// The maximum number of nodes that an input may declare, cf. the [module](self)
+/// documentation. The declared nodes are allocated before the edges are read, so larger
+/// numbers, e.g., from untrusted input, are rejected.
+pub const MAX_NODES: usize = 1 << 24;
+
+/// The supported graph formats.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum GraphFormat {
+    /// One edge per line, given by the two node labels separated by whitespace or a
+    /// comma; a further column, e.g., an edge weight, is ignored, and a single label
+    /// declares a node without edges. Everything after a `#` or `%` is a comment. The
+    /// name is `edge-list`, and the extensions are `.edges` and `.el`.
+    EdgeList,
+    /// The DIMACS graph format, with a `p edge <nodes> <edges>` line and `e <u> <v>`
+    /// lines for the edges (the nodes are numbered from 1); `c` lines are comments. The
+    /// name is `dimacs`, and the extensions are `.dimacs` and `.col`.
+    Dimacs,
+    /// The [graph6](https://users.cecs.anu.edu.au/~bdm/data/formats.txt) format (only
+    /// the first graph is read). Data starting with `:` is read as sparse6. The name is
+    /// `graph6`, and the extension is `.g6`.
+    Graph6,
+    /// The [sparse6](https://users.cecs.anu.edu.au/~bdm/data/formats.txt) format (only
+    /// the first graph is read). The name is `sparse6`, and the extension is `.s6`.
+    Sparse6,
+    /// GraphML, reading the `node` and `edge` elements. The name is `graphml`, and the
+    /// extension is `.graphml`.
+    GraphMl,
+    /// A dense adjacency matrix, one row per line, with the entries separated by
+    /// whitespace or commas; a nonzero entry at `(i, j)` or `(j, i)` is an edge between
+    /// `i` and `j`. The name is `adjacency-matrix`, and the extension is `.adj`.
+    AdjacencyMatrix,
+}
+
+/// A graph imported with [import].
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ImportedGraph {
+    /// The graph with contiguous node ids.
+    pub graph: SpacialGraph,
+    /// The original labels of the nodes, i.e., node `i` in the `graph` has the label
+    /// `labels[i]`.
+    pub labels: Vec<String>,
+}
+
+/// Errors when importing a graph.
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error("unknown graph format: {0}")]
+    UnknownFormat(String),
+    #[error("line {line}: {message}")]
+    Parse { line: usize, message: String },
+    #[error("invalid {format} data: {message}")]
+    Invalid { format: GraphFormat, message: String },
+    #[error("the graphml format requires the graphml feature")]
+    MissingFeature,
+    #[error(transparent)]
+    Io(#[from] io::Error),
+}
+
+impl GraphFormat {
+    /// The [GraphFormat] with the given extension, if any.
+    pub fn from_extension(extension: &str) -> Option<Self> {
+        match extension {
+            "edges" | "el" => Some(GraphFormat::EdgeList),
+            "dimacs" | "col" => Some(GraphFormat::Dimacs),
+            "g6" => Some(GraphFormat::Graph6),
+            "s6" => Some(GraphFormat::Sparse6),
+            "graphml" => Some(GraphFormat::GraphMl),
+            "adj" => Some(GraphFormat::AdjacencyMatrix),
+            _ => None,
+        }
+    }
+
+    /// The [GraphFormat] described by the `format` string for the file at `path`, if it
+    /// is one, i.e., either its name or `"auto"` together with its extension.
+    pub fn new(format: &str, path: &Path) -> Option<Self> {
+        if format == "auto" {
+            path.extension()
+                .and_then(|extension| extension.to_str())
+                .and_then(Self::from_extension)
+        } else {
+            format.parse().ok()
+        }
+    }
+}
+
+impl FromStr for GraphFormat {
+    type Err = ImportError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "edge-list" | "edgelist" => Ok(GraphFormat::EdgeList),
+            "dimacs" => Ok(GraphFormat::Dimacs),
+            "graph6" => Ok(GraphFormat::Graph6),
+            "sparse6" => Ok(GraphFormat::Sparse6),
+            "graphml" => Ok(GraphFormat::GraphMl),
+            "adjacency-matrix" => Ok(GraphFormat::AdjacencyMatrix),
+            _ => Err(ImportError::UnknownFormat(s.to_owned())),
+        }
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GraphFormat::EdgeList => "edge-list",
+            GraphFormat::Dimacs => "dimacs",
+            GraphFormat::Graph6 => "graph6",
+            GraphFormat::Sparse6 => "sparse6",
+            GraphFormat::GraphMl => "graphml",
+            GraphFormat::AdjacencyMatrix => "adjacency-matrix",
+        })
+    }
+}
+
+/// Import a graph from the `input` in the given `format`.
+pub fn import(input: &str, format: GraphFormat) -> Result<ImportedGraph, ImportError> {
+    let mut builder = Builder::default();
+    match format {
+        GraphFormat::EdgeList => edge_list(input, &mut builder)?,
+        GraphFormat::Dimacs => dimacs(input, &mut builder)?,
+        GraphFormat::Graph6 | GraphFormat::Sparse6 => {
+            let data = input.lines().map(str::trim).find(|line| !line.is_empty());
+            let data = data.unwrap_or_default();
+            let data = data
+                .strip_prefix(">>graph6<<")
+                .or_else(|| data.strip_prefix(">>sparse6<<"))
+                .unwrap_or(data);
+            match data.strip_prefix(':') {
+                Some(data) => sparse6(data.as_bytes(), &mut builder),
+                None if format == GraphFormat::Graph6 => {
+                    graph6(data.as_bytes(), &mut builder)
+                },
+                None => Err("sparse6 data has to start with ':'".to_owned()),
+            }
+            .map_err(|message| ImportError::Invalid { format, message })?
+        },
+        GraphFormat::GraphMl => {
+            #[cfg(feature = "graphml")]
+            graphml(input, &mut builder)
+                .map_err(|message| ImportError::Invalid { format, message })?;
+            #[cfg(not(feature = "graphml"))]
+            return Err(ImportError::MissingFeature);
+        },
+        GraphFormat::AdjacencyMatrix => adjacency_matrix(input, &mut builder)?,
+    }
+    Ok(builder.finish())
+}
+
+/// Same as [import], but reading the input from the file at `path` ([STDIO] for stdin).
+pub fn import_file(
+    path: impl AsRef<Path>,
+    format: GraphFormat,
+) -> Result<ImportedGraph, ImportError> {
+    let path = path.as_ref();
+    let input = if path.as_os_str() == STDIO {
+        let mut input = String::new();
+        io::stdin().lock().read_to_string(&mut input)?;
+        input
+    } else {
+        fs::read_to_string(path)?
+    };
+    import(&input, format)
+}
+
+// collects the labels and the edges (in terms of the preliminary ids, i.e., the order in
+// which the labels appear)
+#[derive(Default)]
+struct Builder {
+    ids: HashMap<String, usize>,
+    labels: Vec<String>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl Builder {
+    fn node(&mut self, label: &str) -> usize {
+        if let Some(id) = self.ids.get(label) {
+            return *id;
+        }
+        let id = self.labels.len();
+        self.ids.insert(label.to_owned(), id);
+        self.labels.push(label.to_owned());
+        id
+    }
+
+    fn edge(&mut self, left: &str, right: &str) {
+        let edge = (self.node(left), self.node(right));
+        self.edges.push(edge);
+    }
+
+    // for the formats that have no labels, but only node numbers
+    fn nodes(&mut self, num_nodes: usize) {
+        for node in 0..num_nodes {
+            self.node(&node.to_string());
+        }
+    }
+
+    fn finish(self) -> ImportedGraph {
+        let Self { labels, edges, .. } = self;
+        let numbers = labels
+            .iter()
+            .map(|label| label.parse::<usize>())
+            .collect::<Result<Vec<_>, _>>();
+        let mut order: Vec<usize> = (0..labels.len()).collect();
+        if let Ok(numbers) = numbers {
+            order.sort_by_key(|id| numbers[*id]);
+        }
+        // order[new] = old
+        let mapping: HashMap<usize, usize> =
+            order.iter().enumerate().map(|(new, old)| (*old, new)).collect();
+        let graph = GraphBuffer::new(&edges, labels.len(), Some(&mapping), true);
+        let mut labels: Vec<Option<String>> = labels.into_iter().map(Some).collect();
+        ImportedGraph {
+            graph: graph.as_buffer().to_vec(),
+            labels: order
+                .into_iter()
+                .map(|old| labels[old].take().expect("order is a permutation"))
+                .collect(),
+        }
+    }
+}
+
+// strip comments starting with one of the `markers` and split into tokens
+fn tokens<'l>(line: &'l str, markers: &[char]) -> Vec<&'l str> {
+    let line = match line.find(markers) {
+        Some(comment) => &line[..comment],
+        None => line,
+    };
+    line.split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|token| !token.is_empty())
+        .collect()
+}
+
+fn parse_error(line: usize, message: impl Into<String>) -> ImportError {
+    ImportError::Parse {
+        line: line + 1,
+        message: message.into(),
+    }
+}
+
+fn edge_list(input: &str, builder: &mut Builder) -> Result<(), ImportError> {
+    for (number, line) in input.lines().enumerate() {
+        match tokens(line, &['#', '%'])[..] {
+            [] => {},
+            [node] => {
+                builder.node(node);
+            },
+            [left, right] | [left, right, _] => builder.edge(left, right),
+            _ => return Err(parse_error(number, "expected one or two node labels")),
+        }
+    }
+    Ok(())
+}
+
+fn dimacs(input: &str, builder: &mut Builder) -> Result<(), ImportError> {
+    let mut num_nodes = None;
+    for (number, line) in input.lines().enumerate() {
+        match line.split_whitespace().collect::<Vec<_>>()[..] {
+            [] | ["c", ..] => {},
+            ["p", _, nodes, _] => {
+                let nodes = nodes
+                    .parse()
+                    .map_err(|_| parse_error(number, "invalid number of nodes"))?;
+                let nodes = check_num_nodes(nodes)
+                    .map_err(|message| parse_error(number, message))?;
+                for node in 1..=nodes {
+                    builder.node(&node.to_string());
+                }
+                num_nodes = Some(nodes);
+            },
+            ["e", left, right] => {
+                let Some(num_nodes) = num_nodes else {
+                    return Err(parse_error(number, "edge before the problem line"));
+                };
+                for node in [left, right] {
+                    let node_number = node.parse::<usize>();
+                    if !node_number.is_ok_and(|node| (1..=num_nodes).contains(&node)) {
+                        return Err(parse_error(number, format!("invalid node {node}")));
+                    }
+                }
+                builder.edge(left, right);
+            },
+            _ => return Err(parse_error(number, "expected a c, p or e line")),
+        }
+    }
+    Ok(())
+}
+
+fn adjacency_matrix(input: &str, builder: &mut Builder) -> Result<(), ImportError> {
+    let mut rows = Vec::new();
+    for (number, line) in input.lines().enumerate() {
+        let row = tokens(line, &['#', '%']);
+        if row.is_empty() {
+            continue;
+        }
+        let row = row
+            .into_iter()
+            .map(|entry| entry.parse::<f64>().map(|entry| entry != 0.))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| parse_error(number, "invalid matrix entry"))?;
+        rows.push((number, row));
+    }
+    builder.nodes(rows.len());
+    for (i, (number, row)) in rows.iter().enumerate() {
+        if row.len() != rows.len() {
+            return Err(parse_error(
+                *number,
+                format!("expected {} entries, since the matrix is square", rows.len()),
+            ));
+        }
+        for (j, entry) in row.iter().enumerate() {
+            if *entry && i < j || *entry && i > j && !rows[j].1[i] {
+                builder.edge(&i.to_string(), &j.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_num_nodes(num_nodes: usize) -> Result<usize, String> {
+    if num_nodes > MAX_NODES {
+        return Err(format!(
+            "{num_nodes} nodes are more than the maximum of {MAX_NODES}"
+        ));
+    }
+    Ok(num_nodes)
+}
+
+// the 6-bit values of the graph6/sparse6 encoding
+fn sixes(data: &[u8]) -> Result<Vec<u8>, String> {
+    data.iter()
+        .map(|byte| match byte {
+            63..=126 => Ok(byte - 63),
+            _ => Err(format!("invalid character {:?}", *byte as char)),
+        })
+        .collect()
+}
+
+// the number of nodes, N(n) in the specification, and the remaining data
+fn num_nodes(data: &[u8]) -> Result<(usize, &[u8]), String> {
+    let number = |data: &[u8]| {
+        sixes(data).map(|sixes| {
+            sixes.into_iter().fold(0, |number, six| (number << 6) | six as usize)
+        })
+    };
+    let (num_nodes, rest) = match data {
+        [126, 126, rest @ ..] if rest.len() >= 6 => (number(&rest[..6])?, &rest[6..]),
+        [126, rest @ ..] if rest.len() >= 3 => (number(&rest[..3])?, &rest[3..]),
+        [first, rest @ ..] if *first != 126 => (number(&[*first])?, rest),
+        _ => return Err("invalid number of nodes".to_owned()),
+    };
+    Ok((check_num_nodes(num_nodes)?, rest))
+}
+
+// the bits of the 6-bit values, most significant first
+fn bits(data: &[u8]) -> Result<impl Iterator<Item = bool>, String> {
+    Ok(sixes(data)?
+        .into_iter()
+        .flat_map(|six| (0..6).rev().map(move |bit| (six >> bit) & 1 == 1)))
+}
+
+fn graph6(data: &[u8], builder: &mut Builder) -> Result<(), String> {
+    let (num_nodes, data) = num_nodes(data)?;
+    if data.len() * 6 < num_nodes * num_nodes.saturating_sub(1) / 2 {
+        return Err("too few bits for the adjacency matrix".to_owned());
+    }
+    builder.nodes(num_nodes);
+    let mut bits = bits(data)?;
+    for j in 1..num_nodes {
+        for i in 0..j {
+            if bits.next().ok_or("too few bits for the adjacency matrix")? {
+                builder.edge(&i.to_string(), &j.to_string());
+            }
+        }
+    }
+    Ok(())
+}
+
+fn sparse6(data: &[u8], builder: &mut Builder) -> Result<(), String> {
+    let (num_nodes, data) = num_nodes(data)?;
+    builder.nodes(num_nodes);
+    // the number of bits needed to represent num_nodes - 1
+    let mut k = 1;
+    while 1 << k < num_nodes {
+        k += 1;
+    }
+    let mut bits = bits(data)?;
+    let mut v = 0;
+    // the last bits are padding, so stop when there are not enough bits left
+    while let Some(b) = bits.next() {
+        let mut x = 0;
+        for _ in 0..k {
+            let Some(bit) = bits.next() else {
+                return Ok(());
+            };
+            x = (x << 1) | bit as usize;
+        }
+        if b {
+            v += 1;
+        }
+        if x >= num_nodes || v >= num_nodes {
+            break;
+        } else if x > v {
+            v = x;
+        } else {
+            builder.edge(&x.to_string(), &v.to_string());
+        }
+    }
+    Ok(())
+}
+
+#[cfg(feature = "graphml")]
+fn graphml(input: &str, builder: &mut Builder) -> Result<(), String> {
+    use quick_xml::{Reader, events::Event};
+
+    let mut reader = Reader::from_str(input);
+    let attribute = |element: &quick_xml::events::BytesStart, name: &str| {
+        element
+            .try_get_attribute(name)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| format!("missing attribute {name}"))?
+            .unescape_value()
+            .map(|value| value.into_owned())
+            .map_err(|e| e.to_string())
+    };
+    loop {
+        match reader.read_event().map_err(|e| e.to_string())? {
+            Event::Start(element) | Event::Empty(element) => {
+                match element.local_name().as_ref() {
+                    b"node" => {
+                        builder.node(&attribute(&element, "id")?);
+                    },
+                    b"edge" => builder.edge(
+                        &attribute(&element, "source")?,
+                        &attribute(&element, "target")?,
+                    ),
+                    _ => {},
+                }
+            },
+            Event::Eof => break,
+            _ => {},
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // the triangle 0 - 1 - 2 - 0 plus the isolated node 3
+    fn triangle() -> SpacialGraph {
+        vec![vec![1, 2], vec![0, 2], vec![1, 0], vec![]]
+    }
+
+    fn sorted(mut graph: SpacialGraph) -> SpacialGraph {
+        for neighbors in graph.iter_mut() {
+            neighbors.sort();
+        }
+        graph
+    }
+
+    #[test]
+    fn formats() {
+        let expected = sorted(triangle());
+        for (input, format) in [
+            ("# triangle\n0 1\n1,2\n2 0 5\n0 1\n3\n", GraphFormat::EdgeList),
+            ("c triangle\np edge 4 3\ne 1 2\ne 2 3\ne 3 1\n", GraphFormat::Dimacs),
+            // as written by networkx
+            ("Cw", GraphFormat::Graph6),
+            (":CcJ", GraphFormat::Sparse6),
+            ("0 1 1 0\n1 0 1 0\n1 1 0 0\n0 0 0 0\n", GraphFormat::AdjacencyMatrix),
+        ] {
+            let imported = import(input, format).unwrap();
+            assert_eq!(sorted(imported.graph), expected, "{format}");
+            assert_eq!(imported.labels.len(), 4, "{format}");
+        }
+    }
+
+    #[test]
+    fn labels() {
+        let imported = import("b a\n10 a\n", GraphFormat::EdgeList).unwrap();
+        assert_eq!(imported.labels, ["b", "a", "10"]);
+        assert_eq!(sorted(imported.graph), [vec![1], vec![0, 2], vec![1]]);
+        // numerical labels are sorted
+        let imported = import("3 1\n1 2\n", GraphFormat::EdgeList).unwrap();
+        assert_eq!(imported.labels, ["1", "2", "3"]);
+        assert_eq!(sorted(imported.graph), [vec![1, 2], vec![0], vec![0]]);
+    }
+
+    #[test]
+    fn too_many_nodes() {
+        for (input, format) in [
+            ("p edge 18446744073709551615 0\n", GraphFormat::Dimacs),
+            ("~~~~~~~~", GraphFormat::Graph6),
+            (":~~~~~~~~", GraphFormat::Sparse6),
+            // 63 nodes without the adjacency matrix
+            ("~??~", GraphFormat::Graph6),
+        ] {
+            assert!(import(input, format).is_err(), "{format}");
+        }
+    }
+
+    #[cfg(feature = "graphml")]
+    #[test]
+    fn graphml() {
+        let input = r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <graph id="G" edgedefault="undirected">
+    <node id="n0"/><node id="n1"/><node id="n2"/><node id="n3"></node>
+    <edge source="n0" target="n1"/><edge source="n1" target="n2"/>
+    <edge source="n2" target="n0"/>
+  </graph>
+</graphml>"#;
+        let imported = import(input, GraphFormat::GraphMl).unwrap();
+        assert_eq!(sorted(imported.graph), sorted(triangle()));
+        assert_eq!(imported.labels, ["n0", "n1", "n2", "n3"]);
+    }
+}
@@ -0,0 +1,365 @@
+/*!
+Arbitrary node labels, cf. [run_labeled](super::run_labeled).
+
+The algorithms work on nodes that are numbered contiguously from 0, so that the nodes
+can index into vectors. A [Labeling] maps other node labels, e.g., sparse integers or
+strings, to these ids and back: the ids are given in the order in which the nodes appear
+in the labeled spacial graph, i.e., in a [LabeledSpacialGraph].
+
+The file based functions in [interface](super), e.g.,
+[run_serialized](super::run_serialized), detect labeled spacial graphs, i.e., a
+[LabeledSpacialGraph] or a [LabeledSpacialGraphInput] with [NodeLabel]s, in the
+self-describing formats. The dependency graph, the constraints and the paths are then
+read and written with the same labels.
+*/
+
+use std::{convert::Infallible, fmt, hash::Hash, str::FromStr};
+
+use hashbrown::HashMap;
+use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::{
+    Constraints, ConstraintsError, Path, SpacialGraph, SpacialGraphInput, Steps,
+};
+
+/// A node label that is either a number or a name. It is serialized untagged, i.e., as
+/// plain number or string, so a list of them may mix numbers and names.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum NodeLabel {
+    Number(usize),
+    Name(String),
+}
+
+/// A spacial graph with labeled nodes: a list of the nodes together with their
+/// neighbors, cf. [SpacialGraph].
+pub type LabeledSpacialGraph<L> = Vec<(L, Vec<L>)>;
+
+/// A dependency graph with labeled nodes, cf. the `time_ordering` argument of
+/// [run](super::run).
+pub type LabeledPartialOrderGraph<L> = Vec<Vec<(L, Vec<L>)>>;
+
+/// The [SpacialGraphInput] for a [LabeledSpacialGraph]; the
+/// `weights` are given in the order of the nodes in the `graph`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LabeledSpacialGraphInput<L> {
+    pub graph: LabeledSpacialGraph<L>,
+    #[serde(default)]
+    pub weights: Option<Vec<usize>>,
+    #[serde(default = "Vec::new")]
+    pub outputs: Vec<L>,
+}
+
+/// A one-to-one mapping between node labels and the node ids used internally, i.e., the
+/// numbers from 0 to `len() - 1`.
+#[derive(Clone, Debug)]
+pub struct Labeling<L> {
+    labels: Vec<L>,
+    ids: HashMap<L, usize>,
+}
+
+/// Errors when translating node labels into node ids.
+#[derive(Debug, Clone, PartialEq, Eq, Error)]
+pub enum LabelError {
+    #[error("the label {0} is used for more than one node")]
+    Duplicate(String),
+    #[error("the label {0} is not a node of the spacial graph")]
+    Unknown(String),
+    #[error(transparent)]
+    Constraints(#[from] ConstraintsError),
+}
+
+impl<L: Clone + Eq + Hash + fmt::Display> Labeling<L> {
+    /// Create the labeling where the node with id `i` has the label `labels[i]`. Fails if
+    /// a label appears more than once.
+    pub fn new(labels: Vec<L>) -> Result<Self, LabelError> {
+        let mut ids = HashMap::with_capacity(labels.len());
+        for (id, label) in labels.iter().enumerate() {
+            if ids.insert(label.clone(), id).is_some() {
+                return Err(LabelError::Duplicate(label.to_string()));
+            }
+        }
+        Ok(Self { labels, ids })
+    }
+
+    /// Create the labeling from the order of the nodes in the `graph`, and translate the
+    /// `graph` into a [SpacialGraph] with the according ids.
+    pub fn from_spacial_graph(
+        graph: &[(L, Vec<L>)],
+    ) -> Result<(Self, SpacialGraph), LabelError> {
+        let labeling = Self::new(graph.iter().map(|(label, _)| label.clone()).collect())?;
+        let graph = graph
+            .iter()
+            .map(|(_, neighbors)| labeling.ids(neighbors))
+            .collect::<Result<_, _>>()?;
+        Ok((labeling, graph))
+    }
+
+    /// Translate the `input` into the labels; the inverse of
+    /// [from_spacial_graph](Self::from_spacial_graph) (together with the outputs).
+    pub fn label_spacial_graph(
+        &self,
+        input: SpacialGraphInput,
+    ) -> LabeledSpacialGraphInput<L> {
+        let labels = |ids: Vec<usize>| -> Vec<L> {
+            ids.into_iter().map(|id| self.label(id).clone()).collect()
+        };
+        LabeledSpacialGraphInput {
+            graph: input
+                .graph
+                .into_iter()
+                .enumerate()
+                .map(|(id, neighbors)| (self.label(id).clone(), labels(neighbors)))
+                .collect(),
+            weights: input.weights,
+            outputs: labels(input.outputs),
+        }
+    }
+
+    /// The number of nodes.
+    pub fn len(&self) -> usize {
+        self.labels.len()
+    }
+
+    /// Whether there are no nodes.
+    pub fn is_empty(&self) -> bool {
+        self.labels.is_empty()
+    }
+
+    /// The labels, ordered by the ids.
+    pub fn labels(&self) -> &[L] {
+        &self.labels
+    }
+
+    /// The label of the node `id`.
+    ///
+    /// # Panics
+    /// If `id` is not smaller than [len](Self::len).
+    pub fn label(&self, id: usize) -> &L {
+        &self.labels[id]
+    }
+
+    /// The id of the node with the `label`.
+    pub fn id(&self, label: &L) -> Result<usize, LabelError> {
+        self.ids
+            .get(label)
+            .copied()
+            .ok_or_else(|| LabelError::Unknown(label.to_string()))
+    }
+
+    /// The ids of the nodes with the `labels`.
+    pub fn ids(&self, labels: &[L]) -> Result<Vec<usize>, LabelError> {
+        labels.iter().map(|label| self.id(label)).collect()
+    }
+
+    /// Translate a [LabeledPartialOrderGraph] into the ids.
+    pub fn dependency_graph(
+        &self,
+        graph: &[Vec<(L, Vec<L>)>],
+    ) -> Result<PartialOrderGraph, LabelError> {
+        graph
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|(node, dependencies)| {
+                        Ok((self.id(node)?, self.ids(dependencies)?))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Translate the `constraints` into the ids.
+    pub fn constraints(
+        &self,
+        constraints: &Constraints<L>,
+    ) -> Result<Constraints, LabelError> {
+        constraints.map_nodes(|label| self.id(label))
+    }
+
+    /// Translate the `steps` of a path into the ids.
+    pub fn steps(&self, steps: &[Vec<L>]) -> Result<Steps, LabelError> {
+        steps.iter().map(|step| self.ids(step)).collect()
+    }
+
+    /// Translate the steps of the `path` into the labels.
+    pub fn label_path(&self, path: Path) -> Path<L> {
+        path.map_nodes(|id| self.label(id).clone())
+    }
+
+    /// Translate the steps of the `path` into the ids.
+    pub fn id_path(&self, path: Path<L>) -> Result<Path, LabelError> {
+        let Path {
+            time,
+            space,
+            volume,
+            idle,
+            optimality,
+            steps,
+        } = path;
+        Ok(Path {
+            time,
+            space,
+            volume,
+            idle,
+            optimality,
+            steps: self.steps(&steps)?,
+        })
+    }
+}
+
+impl Labeling<NodeLabel> {
+    /// Create the labeling from the [import](super::import)ed labels, cf.
+    /// [NodeLabel::from_str]; None if the labels are just the ids.
+    pub fn from_imported(labels: Vec<String>) -> Option<Self> {
+        if labels.iter().enumerate().all(|(id, label)| *label == id.to_string()) {
+            return None;
+        }
+        let labels = labels.iter().map(|label| label.parse().expect("infallible"));
+        // the imported labels are distinct strings, which are parsed into distinct labels
+        Some(Self::new(labels.collect()).expect("imported labels are distinct"))
+    }
+
+    /// Translate a dependency graph whose nodes are numbers, e.g., the qubits of Pauli
+    /// tracker [frames](super::frames), into the ids, taking the numbers as labels.
+    pub fn numbered_dependency_graph(
+        &self,
+        graph: &PartialOrderGraph,
+    ) -> Result<PartialOrderGraph, LabelError> {
+        let id = |node: usize| self.id(&NodeLabel::Number(node));
+        graph
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .map(|(node, dependencies)| {
+                        let dependencies = dependencies.iter().map(|dep| id(*dep));
+                        Ok((id(*node)?, dependencies.collect::<Result<_, _>>()?))
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Constraints<NodeLabel> {
+    /// Translate the labels into the ids with the `labeling`. If there is no `labeling`,
+    /// the numbers are taken as ids (and names are unknown).
+    pub fn resolve(
+        &self,
+        labeling: Option<&Labeling<NodeLabel>>,
+    ) -> Result<Constraints, LabelError> {
+        match labeling {
+            Some(labeling) => labeling.constraints(self),
+            None => self.map_nodes(|label| match label {
+                NodeLabel::Number(id) => Ok(*id),
+                NodeLabel::Name(_) => Err(LabelError::Unknown(label.to_string())),
+            }),
+        }
+    }
+}
+
+impl From<Constraints> for Constraints<NodeLabel> {
+    fn from(constraints: Constraints) -> Self {
+        constraints
+            .map_nodes(|id| Ok::<_, Infallible>(NodeLabel::Number(*id)))
+            .expect("infallible")
+    }
+}
+
+impl fmt::Display for NodeLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NodeLabel::Number(number) => write!(f, "{number}"),
+            NodeLabel::Name(name) => write!(f, "{name:?}"),
+        }
+    }
+}
+
+impl FromStr for NodeLabel {
+    type Err = Infallible;
+    /// A [NodeLabel::Number] if `s` is the canonical representation of a number (e.g.,
+    /// not `"007"`), otherwise a [NodeLabel::Name].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s.parse::<usize>() {
+            Ok(number) if number.to_string() == s => NodeLabel::Number(number),
+            _ => NodeLabel::Name(s.to_owned()),
+        })
+    }
+}
+
+impl From<usize> for NodeLabel {
+    fn from(number: usize) -> Self {
+        NodeLabel::Number(number)
+    }
+}
+
+impl From<String> for NodeLabel {
+    fn from(name: String) -> Self {
+        NodeLabel::Name(name)
+    }
+}
+
+impl From<&str> for NodeLabel {
+    fn from(name: &str) -> Self {
+        NodeLabel::Name(name.to_owned())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relabel() {
+        let graph: LabeledSpacialGraph<NodeLabel> = vec![
+            ("a".into(), vec![7.into()]),
+            (7.into(), vec!["a".into(), 3.into()]),
+            (3.into(), vec![7.into()]),
+        ];
+        let (labeling, ids) = Labeling::from_spacial_graph(&graph).unwrap();
+        assert_eq!(ids, vec![vec![1], vec![0, 2], vec![1]]);
+        let order = vec![vec![(3.into(), vec![])], vec![("a".into(), vec![3.into()])]];
+        assert_eq!(
+            labeling.dependency_graph(&order).unwrap(),
+            vec![vec![(2, vec![])], vec![(0, vec![2])]]
+        );
+        let path = Path {
+            time: 2,
+            space: 2,
+            volume: 0,
+            idle: 0,
+            optimality: Default::default(),
+            steps: vec![vec![2], vec![0]],
+        };
+        let labeled = labeling.label_path(path.clone());
+        assert_eq!(labeled.steps, vec![vec![3.into()], vec!["a".into()]]);
+        assert_eq!(labeling.id_path(labeled).unwrap().steps, path.steps);
+
+        assert_eq!(labeling.id(&"b".into()), Err(LabelError::Unknown("\"b\"".into())));
+        assert_eq!(
+            Labeling::new(vec![NodeLabel::from(1), 1.into()]).unwrap_err(),
+            LabelError::Duplicate("1".into())
+        );
+        let constraints = Constraints {
+            deadlines: vec![(NodeLabel::from(3), 1)],
+            ..Default::default()
+        };
+        assert_eq!(constraints.resolve(Some(&labeling)).unwrap().deadlines, [(2, 1)]);
+        assert_eq!(constraints.resolve(None).unwrap().deadlines, [(3, 1)]);
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!("12".parse(), Ok(NodeLabel::Number(12)));
+        assert_eq!("012".parse(), Ok(NodeLabel::Name("012".into())));
+        assert_eq!("-1".parse(), Ok(NodeLabel::Name("-1".into())));
+        assert_eq!(
+            serde_json::from_str::<Vec<NodeLabel>>(r#"[3, "q3"]"#).unwrap(),
+            vec![3.into(), "q3".into()]
+        );
+    }
+}
@@ -7,7 +7,12 @@ The [interface] module contains the API to run the algorithm, together with the
 
 The modules [scheduler] contain the main logic [search]: [scheduler] implements the
 scheduling process and [search] implements based on that the search for the optimal
-schedule patterns.
+schedule patterns. The [bounds] module provides lower bounds on the costs, the [estimate]
+module estimates the size of the search tree, the [strategy] module chooses how to search
+based on that, and the [verify] module checks whether a given schedule pattern is valid.
+The [instance] module collects statistics about a problem instance, the [decompose]
+module splits it into independent components, the [reduce] module shrinks it with
+reduction rules, and the [window] module schedules large instances window by window.
 */
 
 macro_rules! non_semantic_default {
@@ -17,8 +22,16 @@ macro_rules! non_semantic_default {
     };
 }
 
+pub mod bounds;
+pub mod decompose;
+pub mod estimate;
+pub mod instance;
 pub mod interface;
 pub mod probabilistic;
+pub mod reduce;
 pub mod scheduler;
 pub mod search;
+pub mod strategy;
 pub mod timer;
+pub mod verify;
+pub mod window;
@@ -1,34 +1,416 @@
-use std::time::Duration;
+use std::{
+    path::Path,
+    process,
+    time::{Duration, Instant},
+};
 
-use cli::Args;
-use mbqc_scheduling::{interface, probabilistic::AcceptFunc};
+use cli::{
+    AcceptFuncArgs, Args, BenchArgs, ConstraintArgs, ConvertArgs, EstimateArgs,
+    EvaluateArgs, GraphArgs, ImportArgs, SearchArgs, StatsArgs, VerifyArgs,
+};
+use mbqc_scheduling::{
+    interface::{
+        self, Constraints, DependencyGraphSource, Limits, Metadata, Mode, Preprocessing,
+        Window, labels::NodeLabel,
+    },
+    probabilistic::{AcceptFunc, AcceptFuncConfig, HeavysideParameters},
+};
+use serde::Serialize;
 
 mod cli;
+mod signal;
 
 fn main() {
+    tracing_subscriber::fmt::init();
+    match cli::parse() {
+        cli::Subcommand::Schedule(args) => schedule(args),
+        cli::Subcommand::Estimate(args) => estimate(args),
+        cli::Subcommand::Verify(args) => verify(args),
+        cli::Subcommand::Evaluate(args) => evaluate(args),
+        cli::Subcommand::Convert(args) => convert(args),
+        cli::Subcommand::Import(args) => import(args),
+        cli::Subcommand::Stats(args) => stats(args),
+        cli::Subcommand::Bench(args) => bench(args),
+    }
+}
+
+fn schedule(args: Args) {
     let Args {
-        spacial_graph,
-        spacial_graph_format,
-        dependency_graph,
-        dependency_graph_format,
-        paths,
-        paths_format,
+        graphs,
+        paths: (paths, paths_format),
         search,
-        timeout,
-        nthreads,
-        probabilistic,
-        task_bound,
-    } = cli::parse();
-    tracing_subscriber::fmt::init();
+        checkpoint,
+        stats,
+        metadata,
+        bounds,
+    } = args;
+    let cancel = signal::install_handlers();
+    let constraints = read_constraints(&search.constraints);
     interface::run_serialized(
-        (spacial_graph, &spacial_graph_format),
-        (dependency_graph, &dependency_graph_format),
-        search,
-        timeout.map(|t| Duration::from_secs(t.into())),
-        nthreads,
-        task_bound,
-        probabilistic.then_some(AcceptFunc::BuiltinHeavyside),
+        spacial_graph(&graphs),
+        dependency_graph(&graphs),
+        mode(&search),
+        Limits {
+            cancel: Some(cancel),
+            ..limits(&search)
+        },
+        search.nthreads,
+        search.task_bound,
+        probabilistic(&search),
+        &constraints,
+        search.objective,
+        preprocessing(&search),
         (paths, &paths_format),
+        (checkpoint > 0).then(|| Duration::from_secs(checkpoint.into())),
+        stats
+            .as_ref()
+            .map(|(file, format)| (Path::new(file), format.as_str())),
+        metadata
+            .as_ref()
+            .map(|(file, format)| (Path::new(file), format.as_str())),
+        bounds
+            .as_ref()
+            .map(|(file, format)| (Path::new(file), format.as_str())),
     )
     .expect("path search failed")
 }
+
+fn estimate(args: EstimateArgs) {
+    let EstimateArgs {
+        graphs,
+        estimate: (estimate, estimate_format),
+        accept_func,
+        constraints,
+        objective,
+        probes,
+        seed,
+    } = args;
+    let constraints = read_constraints(&constraints);
+    interface::estimate_serialized(
+        spacial_graph(&graphs),
+        dependency_graph(&graphs),
+        self::accept_func(&accept_func),
+        &constraints,
+        objective,
+        probes,
+        seed,
+        (estimate, &estimate_format),
+    )
+    .expect("estimation failed")
+}
+
+fn verify(args: VerifyArgs) {
+    let VerifyArgs {
+        graphs,
+        paths: (paths, paths_format),
+        constraints,
+    } = args;
+    let constraints = read_constraints(&constraints);
+    let results = interface::verify_serialized(
+        spacial_graph(&graphs),
+        dependency_graph(&graphs),
+        &constraints,
+        (paths, &paths_format),
+    )
+    .expect("verification failed");
+    let mut valid = true;
+    for (index, result) in results.into_iter().enumerate() {
+        match result {
+            Ok(path) => println!(
+                "path {index}: valid; time {}, space {}, volume {}, idle {}",
+                path.time, path.space, path.volume, path.idle
+            ),
+            Err(e) => {
+                println!("path {index}: invalid; {e}");
+                valid = false;
+            },
+        }
+    }
+    if !valid {
+        process::exit(1);
+    }
+}
+
+fn evaluate(args: EvaluateArgs) {
+    let EvaluateArgs {
+        graphs,
+        paths: (paths, paths_format),
+        evaluated: (evaluated, evaluated_format),
+        constraints,
+        objective,
+    } = args;
+    let constraints = read_constraints(&constraints);
+    interface::evaluate_serialized(
+        spacial_graph(&graphs),
+        dependency_graph(&graphs),
+        &constraints,
+        objective,
+        (paths, &paths_format),
+        (evaluated, &evaluated_format),
+    )
+    .expect("evaluation failed")
+}
+
+fn convert(args: ConvertArgs) {
+    let ConvertArgs {
+        input: (input, input_format),
+        output: (output, output_format),
+        kind,
+    } = args;
+    interface::convert_serialized(kind, (input, &input_format), (output, &output_format))
+        .expect("conversion failed")
+}
+
+fn import(args: ImportArgs) {
+    let ImportArgs {
+        input: (input, input_format),
+        output: (output, output_format),
+        labels,
+    } = args;
+    interface::import_serialized(
+        (input, &input_format),
+        (output, &output_format),
+        labels
+            .as_ref()
+            .map(|(file, format)| (Path::new(file), format.as_str())),
+    )
+    .expect("import failed")
+}
+
+fn stats(args: StatsArgs) {
+    let StatsArgs { graphs, constraints, output } = args;
+    let constraints = read_constraints(&constraints);
+    let (graph, dependency_graph, labeling) =
+        interface::read_graphs(spacial_graph(&graphs), dependency_graph(&graphs))
+            .expect("failed to read the graphs");
+    let constraints = constraints
+        .resolve(labeling.as_ref())
+        .expect("failed to read the constraints");
+    let stats = interface::instance_stats(
+        &graph.graph,
+        graph.weights.as_deref(),
+        &graph.outputs,
+        &dependency_graph,
+        &constraints,
+    );
+    write_output(&stats, output);
+}
+
+#[derive(Serialize)]
+struct Bench {
+    // the wall-clock times of the single runs in seconds
+    seconds: Vec<f64>,
+    min_seconds: f64,
+    mean_seconds: f64,
+    max_seconds: f64,
+    // the time and the cost (according to the objective) of the paths of the last run
+    paths: Vec<(usize, usize)>,
+    // the metadata of the last run
+    metadata: Metadata,
+}
+
+fn bench(args: BenchArgs) {
+    let BenchArgs { graphs, search, repeat, output } = args;
+    let constraints = read_constraints(&search.constraints);
+    let (graph, dependency_graph, labeling) =
+        interface::read_graphs(spacial_graph(&graphs), dependency_graph(&graphs))
+            .expect("failed to read the graphs");
+    let constraints = constraints
+        .resolve(labeling.as_ref())
+        .expect("failed to read the constraints");
+
+    let mut seconds = Vec::new();
+    let mut last = None;
+    for _ in 0..repeat {
+        let start = Instant::now();
+        let probabilistic = probabilistic(&search);
+        let result = if let Preprocessing::Window(window) = preprocessing(&search) {
+            interface::run_windowed(
+                &graph.graph,
+                graph.weights.as_deref(),
+                &graph.outputs,
+                &dependency_graph,
+                window,
+                limits(&search),
+                search.nthreads,
+                search.task_bound,
+                probabilistic,
+                &constraints,
+                search.objective,
+            )
+        } else {
+            let run = match preprocessing(&search) {
+                Preprocessing::Decompose => interface::run_decomposed,
+                Preprocessing::Reduce => interface::run_reduced,
+                _ => interface::run_with_metadata,
+            };
+            run(
+                &graph.graph,
+                graph.weights.as_deref(),
+                &graph.outputs,
+                &dependency_graph,
+                mode(&search),
+                limits(&search),
+                search.nthreads,
+                search.task_bound,
+                probabilistic,
+                &constraints,
+                search.objective,
+            )
+        };
+        seconds.push(start.elapsed().as_secs_f64());
+        last = Some(result.expect("the constraints are invalid"));
+    }
+    let (paths, metadata) = last.expect("repeat is at least 1");
+
+    let bench = Bench {
+        min_seconds: seconds.iter().copied().fold(f64::INFINITY, f64::min),
+        mean_seconds: seconds.iter().sum::<f64>() / seconds.len() as f64,
+        max_seconds: seconds.iter().copied().fold(0., f64::max),
+        seconds,
+        paths: paths
+            .iter()
+            .map(|path| (path.time, path.cost(search.objective)))
+            .collect(),
+        metadata,
+    };
+    write_output(&bench, output);
+}
+
+fn spacial_graph(graphs: &GraphArgs) -> (&str, &str) {
+    (&graphs.spacial_graph.0, &graphs.spacial_graph.1)
+}
+
+fn dependency_graph(graphs: &GraphArgs) -> DependencyGraphSource {
+    let (file, format) = &graphs.dependency_graph;
+    match graphs.frames_map {
+        Some((ref map, ref map_format)) => DependencyGraphSource::Frames {
+            frames: (file.into(), format.clone()),
+            map: (map.into(), map_format.clone()),
+        },
+        None if graphs.edges => DependencyGraphSource::Edges(file.into(), format.clone()),
+        None => (file, format.as_str()).into(),
+    }
+}
+
+fn mode(search: &SearchArgs) -> Mode {
+    if let Some(portfolio) = search.portfolio {
+        Mode::Portfolio(portfolio)
+    } else if search.auto {
+        Mode::Auto
+    } else {
+        search.search.into()
+    }
+}
+
+fn preprocessing(search: &SearchArgs) -> Preprocessing {
+    if search.decompose {
+        Preprocessing::Decompose
+    } else if search.reduce {
+        Preprocessing::Reduce
+    } else if let Some(layers) = search.window {
+        Preprocessing::Window(Window {
+            layers: layers as usize,
+            commit: search.commit as usize,
+        })
+    } else {
+        Preprocessing::None
+    }
+}
+
+fn limits(search: &SearchArgs) -> Limits {
+    let seconds = |t: Option<u32>| t.map(|t| Duration::from_secs(t.into()));
+    Limits {
+        timeout: seconds(search.timeout),
+        stall_timeout: seconds(search.stall_timeout),
+        first_solution_timeout: seconds(search.first_solution_timeout),
+        node_budget: search.node_budget,
+        cancel: None,
+    }
+}
+
+// the accept function together with the seed; with a portfolio, they are always passed
+// on, since its strategies are seeded and may be probabilistic
+fn probabilistic(search: &SearchArgs) -> Option<(AcceptFunc, Option<u64>)> {
+    let accept_func = accept_func(&search.accept_func);
+    let accept_func = match search.portfolio {
+        Some(_) => Some(accept_func.unwrap_or_default()),
+        None => accept_func,
+    };
+    accept_func.map(|func| (func, search.seed))
+}
+
+// the accept function from the file, overwritten by the according options; None if the
+// search is not probabilistic
+fn accept_func(args: &AcceptFuncArgs) -> Option<AcceptFunc> {
+    let AcceptFuncArgs {
+        probabilistic,
+        ref config,
+        cutoff,
+        lin_num_total_nodes_exp,
+        exp_num_total_nodes_exp,
+        exp_num_remaining_nodes_exp,
+        exp_diff_exp,
+        exp_num_measured_nodes_exp,
+    } = *args;
+    let parametrized = cutoff.is_some()
+        || lin_num_total_nodes_exp.is_some()
+        || exp_num_total_nodes_exp.is_some()
+        || exp_num_remaining_nodes_exp.is_some()
+        || exp_diff_exp.is_some()
+        || exp_num_measured_nodes_exp.is_some();
+    let config = match config {
+        Some((file, format)) => interface::read_accept_func(file, format)
+            .expect("failed to read the accept function"),
+        None if probabilistic || parametrized => AcceptFuncConfig::default(),
+        None => return None,
+    };
+    if !parametrized {
+        return Some(config.into());
+    }
+    let mut param = match config {
+        AcceptFuncConfig::ParametrizedHeavyside(param) => param,
+        AcceptFuncConfig::BuiltinHeavyside => HeavysideParameters::default(),
+    };
+    param.cutoff = cutoff.unwrap_or(param.cutoff);
+    param.lin_num_total_nodes_exp =
+        lin_num_total_nodes_exp.unwrap_or(param.lin_num_total_nodes_exp);
+    param.exp_num_total_nodes_exp =
+        exp_num_total_nodes_exp.unwrap_or(param.exp_num_total_nodes_exp);
+    param.exp_num_remaining_nodes_exp =
+        exp_num_remaining_nodes_exp.unwrap_or(param.exp_num_remaining_nodes_exp);
+    param.exp_diff_exp = exp_diff_exp.unwrap_or(param.exp_diff_exp);
+    param.exp_num_measured_nodes_exp =
+        exp_num_measured_nodes_exp.unwrap_or(param.exp_num_measured_nodes_exp);
+    Some(AcceptFunc::ParametrizedHeavyside { param })
+}
+
+// write to the output file, if given, otherwise print as JSON
+fn write_output(value: &impl Serialize, output: Option<(String, String)>) {
+    match output {
+        Some((file, format)) => interface::write_serialized(file, &format, value)
+            .expect("failed to write the output"),
+        None => println!(
+            "{}",
+            serde_json::to_string_pretty(value).expect("failed to serialize the output")
+        ),
+    }
+}
+
+// the constraints from the file, overwritten by the according options
+fn read_constraints(args: &ConstraintArgs) -> Constraints<NodeLabel> {
+    let mut constraints = match args.constraints {
+        Some((ref file, ref format)) => interface::read_constraints(file, format)
+            .expect("failed to read the constraints"),
+        None => Constraints::default(),
+    };
+    if let Some(max_step_size) = args.max_step_size {
+        constraints.max_step_size = Some(max_step_size as usize);
+    }
+    if let Some(latency) = args.latency {
+        constraints.latency = latency as usize;
+    }
+    constraints.release_times.extend(args.release_times.iter().cloned());
+    constraints.deadlines.extend(args.deadlines.iter().cloned());
+    constraints
+}
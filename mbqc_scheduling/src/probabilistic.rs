@@ -1,6 +1,8 @@
 //! Probabilistic accept functions (a little bit like in Markov chains), for the search
 //! algorithm.
 
+use serde::{Deserialize, Serialize};
+
 /// The type of the underlying accept function for [AcceptFunc].
 ///
 /// Let `accept_func` be the accept function, then its signature is
@@ -19,6 +21,9 @@
 ///          // probabilities above 1. are allowed and mean that the path is always
 ///          // accepted
 /// ```
+/// If the search does not optimize the memory, but another
+/// [Objective](crate::scheduler::space::Objective), the memory arguments are replaced by
+/// the according cost (bounds).
 pub type Accept = dyn Fn(f64, f64, f64, f64, f64, f64, f64) -> f64 + Send + Sync;
 /// Boxed version of [Accept].
 pub type AcceptBox = Box<Accept>;
@@ -45,9 +50,12 @@ fn builtin_heavyside(
     }
 }
 
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 /// Parameters for the [AcceptFunc::ParametrizedHeavyside] accept function. Compare the
 /// documented function there, where `param` is this parameter struct.
+///
+/// The default parameters reproduce [AcceptFunc::BuiltinHeavyside].
 pub struct HeavysideParameters {
     pub cutoff: f64,
     pub lin_num_total_nodes_exp: i32,
@@ -57,6 +65,19 @@ pub struct HeavysideParameters {
     pub exp_num_measured_nodes_exp: i32,
 }
 
+impl Default for HeavysideParameters {
+    fn default() -> Self {
+        Self {
+            cutoff: 0.,
+            lin_num_total_nodes_exp: 2,
+            exp_num_total_nodes_exp: 1,
+            exp_num_remaining_nodes_exp: 1,
+            exp_diff_exp: 3,
+            exp_num_measured_nodes_exp: 1,
+        }
+    }
+}
+
 fn create_parametrized_heavyside(
     param: HeavysideParameters,
 ) -> impl Fn(f64, f64, f64, f64, f64, f64, f64) -> f64 {
@@ -148,4 +169,71 @@ impl AcceptFunc {
             AcceptFunc::Custom(f) => f,
         }
     }
+
+    /// Returns the serializable description of the accept function, or None if it is a
+    /// [Custom](AcceptFunc::Custom) one.
+    pub fn config(&self) -> Option<AcceptFuncConfig> {
+        match self {
+            AcceptFunc::BuiltinHeavyside => Some(AcceptFuncConfig::BuiltinHeavyside),
+            AcceptFunc::ParametrizedHeavyside { param } => {
+                Some(AcceptFuncConfig::ParametrizedHeavyside(*param))
+            },
+            AcceptFunc::Custom(_) => None,
+        }
+    }
+}
+
+/// A serializable description of the non-custom [AcceptFunc]s, e.g., to load them from a
+/// configuration file.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AcceptFuncConfig {
+    /// Cf. [AcceptFunc::BuiltinHeavyside].
+    #[default]
+    BuiltinHeavyside,
+    /// Cf. [AcceptFunc::ParametrizedHeavyside].
+    ParametrizedHeavyside(HeavysideParameters),
+}
+
+impl AcceptFuncConfig {
+    /// The parameters of the heavyside function; the default ones for
+    /// [BuiltinHeavyside](AcceptFuncConfig::BuiltinHeavyside).
+    pub fn parameters(&self) -> HeavysideParameters {
+        match self {
+            AcceptFuncConfig::BuiltinHeavyside => HeavysideParameters::default(),
+            AcceptFuncConfig::ParametrizedHeavyside(param) => *param,
+        }
+    }
+}
+
+impl From<AcceptFuncConfig> for AcceptFunc {
+    fn from(config: AcceptFuncConfig) -> Self {
+        match config {
+            AcceptFuncConfig::BuiltinHeavyside => AcceptFunc::BuiltinHeavyside,
+            AcceptFuncConfig::ParametrizedHeavyside(param) => {
+                AcceptFunc::ParametrizedHeavyside { param }
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_parameters_are_builtin() {
+        let builtin = AcceptFunc::BuiltinHeavyside.get_accept_func();
+        let parametrized = AcceptFunc::from(AcceptFuncConfig::ParametrizedHeavyside(
+            HeavysideParameters::default(),
+        ))
+        .get_accept_func();
+        for (a, b, c, d, e, f, g) in [
+            (0., 10., 3., 0., 4., 5., 20.),
+            (0., 10., 9., 0., 4., 1., 20.),
+            (0., 3., 3., 0., 4., 5., 20.),
+        ] {
+            assert_eq!(builtin(a, b, c, d, e, f, g), parametrized(a, b, c, d, e, f, g));
+        }
+    }
 }
@@ -0,0 +1,448 @@
+/*!
+Reduction rules that shrink a problem instance before searching, cf.
+[interface::run_reduced](crate::interface::run_reduced).
+
+Some decisions do not have to be searched, because one option is never worse than the
+others. The rules here [remove](reduce) nodes whose measurement step can be decided in
+advance, so that the search runs on a smaller [kernel](Reduction::kernel), and the paths
+of the kernel are then [lifted](Reduction::lift) back to the full instance:
+
+- An *isolated* node, i.e., a node without neighbors, dependencies and dependents, is
+  measured in the first step.
+- A *pendant* node, i.e., a node without dependents and with exactly one neighbor that
+  is measured, is measured in the same step as its neighbor, if all its dependencies are
+  (indirect) dependencies of the neighbor. Measuring it earlier would initialize the
+  neighbor earlier, and measuring it later would keep it in memory longer.
+
+The rules are applied repeatedly, so, for example, isolated chains and trees are removed
+completely and measured in the first step.
+
+The rules never increase the time, the [volume](Objective::Volume) or the
+[idle](Objective::Idle) time of a path, since the removed nodes are only in memory in the
+step where they are measured. Therefore, the Pareto front of the kernel lifts exactly to
+the Pareto front of the full instance (with the volume shifted by the weight of the
+removed nodes). This is not true for the [space](Objective::Space): adding a node to the
+step of its neighbor increases the memory in this step, which might be the bottleneck.
+So, for the space, only nodes with weight 0 are removed.
+
+Furthermore, the rules are not applied at all if the `max_step_size` is constrained, or
+if the [Constraints] can force a path to wait, i.e., if there are latencies or release
+times: then the paths contain empty steps, where the search waits for the waiting nodes,
+and a removed node might have been useful to fill such a step. Nodes with deadlines are
+never removed.
+*/
+
+use crate::{
+    decompose::Instance,
+    interface::{Constraints, Optimality, Path},
+    scheduler::{
+        space::{Objective, RefSpacialGraph},
+        time::RefPartialOrderGraph,
+    },
+    search,
+};
+
+/// The result of [reduce]: the kernel together with the information how to lift its
+/// paths.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reduction {
+    /// The kernel, i.e., the instance restricted to the nodes that have not been
+    /// removed.
+    pub kernel: Instance,
+    /// The removed nodes, in the order in which they have been removed, each together
+    /// with the node in whose step it is measured (None means the first step).
+    pub removed: Vec<(usize, Option<usize>)>,
+    instance: Instance,
+    objective: Objective,
+}
+
+/// Apply the reduction rules, cf. the [module](self) documentation, as long as possible.
+/// Regarding the parameters, cf. [interface::run](crate::interface::run).
+///
+/// # Examples
+/// ```
+/// # use mbqc_scheduling::{
+/// #     interface::Constraints, reduce::reduce, scheduler::space::Objective,
+/// # };
+/// // 0 - 1 - 2, with 1 depending on 0; 3 is isolated
+/// let graph = vec![vec![1], vec![0, 2], vec![1], vec![]];
+/// let order = vec![vec![(0, vec![]), (2, vec![]), (3, vec![])], vec![(1, vec![0])]];
+/// let constraints = Constraints::default();
+/// let reduction = reduce(&graph, None, &[], &order, &constraints, Objective::Volume);
+/// // 2 is measured together with 1 and 3 in the first step, but 1 cannot be measured
+/// // together with 0, since it depends on it
+/// assert_eq!(reduction.removed, vec![(2, Some(1)), (3, None)]);
+/// assert_eq!(reduction.kernel.nodes, vec![0, 1]);
+/// // for the space, nothing can be removed (without weights)
+/// let reduction = reduce(&graph, None, &[], &order, &constraints, Objective::Space);
+/// assert!(reduction.removed.is_empty());
+/// ```
+pub fn reduce(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    objective: Objective,
+) -> Reduction {
+    let num_bits = spacial_graph.len();
+    let waits = constraints.latency > 0
+        || constraints.edge_latencies.iter().any(|(_, _, latency)| *latency > 0)
+        || !constraints.release_times.is_empty();
+    let removed = if constraints.max_step_size.is_none() && !waits {
+        Reducer::new(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            constraints,
+            objective,
+        )
+        .run()
+    } else {
+        Vec::new()
+    };
+    let mut is_removed = vec![false; num_bits];
+    for (node, _) in removed.iter() {
+        is_removed[*node] = true;
+    }
+    let restrict = |nodes: &[usize]| {
+        Instance::restrict(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            constraints,
+            nodes,
+        )
+    };
+    let remaining: Vec<usize> = (0..num_bits).filter(|bit| !is_removed[*bit]).collect();
+    Reduction {
+        kernel: restrict(&remaining),
+        removed,
+        instance: restrict(&(0..num_bits).collect::<Vec<_>>()),
+        objective,
+    }
+}
+
+impl Reduction {
+    /// Lift a `path` of the [kernel](Reduction::kernel) to a path of the full instance.
+    /// The costs are recalculated, while the [Optimality] is kept, since the reduction
+    /// rules are exact (the lower bound of a gap is shifted by the cost difference).
+    ///
+    /// # Panics
+    /// Panics if the `path` is not a valid path of the kernel.
+    pub fn lift(&self, path: Path) -> Path {
+        let kernel_cost = path.cost(self.objective);
+        let optimality = path.optimality;
+        let mut steps = self.kernel.to_original(path).steps;
+        let mut step_of = vec![0; self.instance.nodes.len()];
+        for (i, step) in steps.iter().enumerate() {
+            for node in step {
+                step_of[*node] = i;
+            }
+        }
+        for (node, partner) in self.removed.iter().rev() {
+            let step = partner.map_or(0, |partner| step_of[partner]);
+            if step == steps.len() {
+                steps.push(Vec::new());
+            }
+            steps[step].push(*node);
+            step_of[*node] = step;
+        }
+        let mut lifted = self
+            .instance
+            .verify(&steps)
+            .expect("the reductions preserve the validity of the paths");
+        lifted.optimality = match optimality {
+            Optimality::Gap { lower_bound } => Optimality::Gap {
+                lower_bound: lower_bound + lifted.cost(self.objective) - kernel_cost,
+            },
+            optimality => optimality,
+        };
+        lifted
+    }
+
+    /// [Lift](Reduction::lift) all `paths` of the kernel. If there is nothing to measure
+    /// in the kernel, the search does not return any path, so in this case, if nodes have
+    /// been removed, the single path that measures only the removed nodes is returned.
+    pub fn lift_all(&self, paths: Vec<Path>) -> Vec<Path> {
+        let kernel = &self.kernel;
+        let num_bits = kernel.spacial_graph.len();
+        if paths.is_empty()
+            && !self.removed.is_empty()
+            && search::all_outputs(num_bits, &kernel.time_ordering, &kernel.outputs).len()
+                == num_bits
+        {
+            return vec![self.lift(Path {
+                time: 0,
+                space: 0,
+                volume: 0,
+                idle: 0,
+                optimality: Optimality::Optimal,
+                steps: Vec::new(),
+            })];
+        }
+        paths.into_iter().map(|path| self.lift(path)).collect()
+    }
+}
+
+struct Reducer {
+    neighbors: Vec<Vec<usize>>,
+    dependencies: Vec<Vec<usize>>,
+    num_dependents: Vec<usize>,
+    is_output: Vec<bool>,
+    // whether the node may be removed at all, independent of the graphs
+    removable: Vec<bool>,
+    is_removed: Vec<bool>,
+    // the generation markers for the ancestor search
+    visited: Vec<usize>,
+    generation: usize,
+}
+
+impl Reducer {
+    fn new(
+        spacial_graph: RefSpacialGraph,
+        weights: Option<&[usize]>,
+        outputs: &[usize],
+        time_ordering: RefPartialOrderGraph,
+        constraints: &Constraints,
+        objective: Objective,
+    ) -> Self {
+        let num_bits = spacial_graph.len();
+        let mut is_output = vec![false; num_bits];
+        for output in search::all_outputs(num_bits, time_ordering, outputs) {
+            is_output[output] = true;
+        }
+        let mut dependencies = vec![Vec::new(); num_bits];
+        let mut num_dependents = vec![0; num_bits];
+        for (bit, deps) in time_ordering.iter().flatten() {
+            let mut deps = deps.clone();
+            deps.sort_unstable();
+            deps.dedup();
+            for dep in deps.iter() {
+                num_dependents[*dep] += 1;
+            }
+            dependencies[*bit] = deps;
+        }
+        let mut removable: Vec<bool> = (0..num_bits)
+            .map(|bit| {
+                !is_output[bit]
+                    && (objective != Objective::Space
+                        || weights.is_some_and(|weights| weights[bit] == 0))
+            })
+            .collect();
+        for (bit, _) in constraints.deadlines.iter() {
+            removable[*bit] = false;
+        }
+        Self {
+            neighbors: spacial_graph.to_vec(),
+            dependencies,
+            num_dependents,
+            is_output,
+            removable,
+            is_removed: vec![false; num_bits],
+            visited: vec![0; num_bits],
+            generation: 0,
+        }
+    }
+
+    fn run(mut self) -> Vec<(usize, Option<usize>)> {
+        let mut removed = Vec::new();
+        let mut stack: Vec<usize> = (0..self.neighbors.len()).rev().collect();
+        while let Some(bit) = stack.pop() {
+            if self.is_removed[bit]
+                || !self.removable[bit]
+                || self.num_dependents[bit] > 0
+            {
+                continue;
+            }
+            let partner = match self.neighbors[bit].len() {
+                0 if self.dependencies[bit].is_empty() => None,
+                1 if self.is_pendant(bit, self.neighbors[bit][0]) => {
+                    Some(self.neighbors[bit][0])
+                },
+                _ => continue,
+            };
+            self.is_removed[bit] = true;
+            removed.push((bit, partner));
+            for dep in self.dependencies[bit].iter() {
+                self.num_dependents[*dep] -= 1;
+                stack.push(*dep);
+            }
+            if let Some(neighbor) = partner {
+                self.neighbors[neighbor].retain(|node| *node != bit);
+                stack.push(neighbor);
+            }
+        }
+        removed
+    }
+
+    // whether `bit` can be measured in the same step as its only `neighbor`
+    fn is_pendant(&mut self, bit: usize, neighbor: usize) -> bool {
+        if self.is_output[neighbor] {
+            return false;
+        }
+        self.dependencies[bit].is_empty() || self.are_ancestors(bit, neighbor)
+    }
+
+    // whether all dependencies of `bit` are (indirect) dependencies of `node`; the
+    // removed nodes have no dependents, so they are never visited
+    fn are_ancestors(&mut self, bit: usize, node: usize) -> bool {
+        self.generation += 1;
+        let targets = &self.dependencies[bit];
+        let mut missing = targets.len();
+        let mut stack = vec![node];
+        while let Some(current) = stack.pop() {
+            for dep in self.dependencies[current].iter() {
+                if self.visited[*dep] == self.generation {
+                    continue;
+                }
+                self.visited[*dep] = self.generation;
+                if targets.binary_search(dep).is_ok() {
+                    missing -= 1;
+                    if missing == 0 {
+                        return true;
+                    }
+                }
+                stack.push(*dep);
+            }
+        }
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::{
+        interface::{self, edges},
+        scheduler::space::SpacialGraph,
+    };
+
+    const OBJECTIVES: [Objective; 3] =
+        [Objective::Space, Objective::Volume, Objective::Idle];
+
+    fn front(paths: &[Path], objective: Objective) -> Vec<(usize, usize)> {
+        paths.iter().map(|path| (path.time, path.cost(objective))).collect()
+    }
+
+    fn search(instance: &Instance, objective: Objective) -> Vec<Path> {
+        interface::run_with_metadata(
+            &instance.spacial_graph,
+            instance.weights.as_deref(),
+            &instance.outputs,
+            &instance.time_ordering,
+            true,
+            None,
+            1,
+            None,
+            None,
+            &instance.constraints,
+            objective,
+        )
+        .unwrap()
+        .0
+    }
+
+    // the exact Pareto front of the full instance is the lifted front of the kernel
+    #[test]
+    fn exact() {
+        let mut rng = Pcg64::seed_from_u64(42);
+        let mut num_removed = [0; 3];
+        for _ in 0..300 {
+            let num_bits = rng.gen_range(1..=6);
+            let mut graph: SpacialGraph = vec![Vec::new(); num_bits];
+            let mut dependencies = Vec::new();
+            for i in 0..num_bits {
+                for j in i + 1..num_bits {
+                    if rng.gen_bool(0.35) {
+                        graph[i].push(j);
+                        graph[j].push(i);
+                    }
+                    if rng.gen_bool(0.2) {
+                        dependencies.push((i, j));
+                    }
+                }
+            }
+            let order = edges::get_order(num_bits, &dependencies).unwrap();
+            let weights = rng
+                .gen_bool(0.5)
+                .then(|| (0..num_bits).map(|_| rng.gen_range(0..=2)).collect::<Vec<_>>());
+            let outputs: Vec<usize> = (0..num_bits)
+                .filter(|bit| {
+                    !dependencies.iter().any(|(dep, _)| dep == bit) && rng.gen_bool(0.15)
+                })
+                .collect();
+            // the rules are only applied if nothing has to wait, cf. the next test
+            let constraints = Constraints {
+                deadlines: if rng.gen_bool(0.2) {
+                    vec![(rng.gen_range(0..num_bits), rng.gen_range(0..3))]
+                } else {
+                    Vec::new()
+                },
+                ..Default::default()
+            };
+
+            for (objective, num_removed) in OBJECTIVES.into_iter().zip(&mut num_removed) {
+                let reduction = reduce(
+                    &graph,
+                    weights.as_deref(),
+                    &outputs,
+                    &order,
+                    &constraints,
+                    objective,
+                );
+                *num_removed += reduction.removed.len();
+                let lifted = reduction.lift_all(search(&reduction.kernel, objective));
+                assert_eq!(
+                    front(&lifted, objective),
+                    front(&search(&reduction.instance, objective), objective),
+                    "{graph:?}, {weights:?}, {outputs:?}, {order:?}, {constraints:?}, \
+                     {objective:?}"
+                );
+            }
+        }
+        // make sure that the rules are actually tested
+        assert!(num_removed.iter().all(|num| *num > 0), "{num_removed:?}");
+    }
+
+    // why the pendant rule is not applied for the space: 0 - 1, 1 - 2, 1 - 3 and the
+    // pendant 4 - 1, where 2 and 3 depend on 1; measuring 1 initializes 2 and 3, so
+    // measuring 4 in the same step needs four qubits, while measuring it before (after 0
+    // initialized 1) needs only three
+    #[test]
+    fn pendant_space() {
+        let graph = vec![vec![1], vec![0, 2, 3, 4], vec![1], vec![1], vec![1]];
+        let order = vec![
+            vec![(0, vec![]), (1, vec![]), (4, vec![])],
+            vec![(2, vec![1]), (3, vec![1])],
+        ];
+        let constraints = Constraints::default();
+        let reduction = reduce(&graph, None, &[], &order, &constraints, Objective::Space);
+        assert!(reduction.removed.is_empty());
+        let full = search(&reduction.instance, Objective::Space);
+        assert_eq!(full.iter().map(|path| path.space).min(), Some(3));
+
+        let forced = Reduction {
+            kernel: Instance::restrict(
+                &graph,
+                None,
+                &[],
+                &order,
+                &constraints,
+                &[0, 1, 2, 3],
+            ),
+            removed: vec![(4, Some(1))],
+            ..reduction
+        };
+        let lifted: Vec<Path> = search(&forced.kernel, Objective::Space)
+            .into_iter()
+            .map(|path| forced.lift(path))
+            .collect();
+        assert_eq!(lifted.iter().map(|path| path.space).min(), Some(4));
+    }
+}
@@ -10,7 +10,7 @@
 mod combinatoric;
 
 pub use combinatoric::Partition;
-use space::{AlreadyMeasured, Graph};
+use space::{AlreadyMeasured, Graph, Objective};
 use time::{MeasurableSet, NotMeasurable, Partitioner, PathGenerator};
 use tree::{Focus, FocusIterator, Step, Sweep};
 
@@ -36,12 +36,39 @@ pub mod tree;
 pub struct Scheduler<'l, T> {
     time: PathGenerator<'l, T>,
     space: Graph<'l>,
+    objective: Objective,
 }
 
 impl<'l, T> Scheduler<'l, T> {
-    /// Create a new scheduler.
+    /// Create a new scheduler, optimizing the memory, cf. [Scheduler::with_objective].
     pub fn new(time: PathGenerator<'l, T>, space: Graph<'l>) -> Self {
-        Self { time, space }
+        Self {
+            time,
+            space,
+            objective: Objective::default(),
+        }
+    }
+
+    /// Set the [Objective] whose cost is returned at the leafs, cf. [Scheduler::cost].
+    pub fn with_objective(mut self, objective: Objective) -> Self {
+        self.objective = objective;
+        self
+    }
+
+    /// Get the [Objective].
+    pub fn objective(&self) -> Objective {
+        self.objective
+    }
+
+    /// Get the (so far) cost according to the [Objective], cf. [Graph::cost].
+    pub fn cost(&self) -> usize {
+        self.space.cost(self.objective)
+    }
+
+    /// Get a lower bound on the final cost according to the [Objective], cf.
+    /// [Graph::cost_bound].
+    pub fn cost_bound(&self) -> usize {
+        self.space.cost_bound(self.objective)
     }
 
     /// Get a reference to the underlying [PathGenerator].
@@ -50,7 +77,7 @@ impl<'l, T> Scheduler<'l, T> {
     }
 
     /// Get a reference to the underlying [Graph].
-    pub fn space(&self) -> &Graph {
+    pub fn space(&self) -> &Graph<'l> {
         &self.space
     }
 }
@@ -79,6 +106,7 @@ impl<T: MeasurableSet> Focus<&[usize]> for Scheduler<'_, T> {
         Ok(Self {
             time: new_time,
             space: new_space,
+            objective: self.objective,
         })
     }
 }
@@ -102,17 +130,14 @@ impl FocusIterator for Scheduler<'_, Partitioner> {
             Self {
                 time: new_time,
                 space: new_space,
+                objective: self.objective,
             },
             mess,
         ))
     }
 
     fn at_leaf(&self) -> Option<Self::LeafItem> {
-        self.time
-            .measurable()
-            .set()
-            .is_empty()
-            .then_some(self.space.max_memory())
+        self.time.is_finished().then_some(self.cost())
     }
 }
 
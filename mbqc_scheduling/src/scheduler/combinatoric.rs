@@ -1,19 +1,19 @@
 use std::ops::Range;
 
-use itertools::{Either, Itertools, Powerset};
+use itertools::{Combinations, Either, Itertools};
 
 /// An iterator over all partitions of a set.
 #[derive(Debug, Clone)]
 pub struct Partition<T> {
     pub set: T,
-    iter: Powerset<Range<usize>>,
+    iter: Subsets,
 }
 
 impl<T: Default> Default for Partition<T> {
     fn default() -> Self {
         Self {
             set: T::default(),
-            iter: (0..0).powerset(),
+            iter: Subsets::new(0, 0),
         }
     }
 }
@@ -21,7 +21,16 @@ impl<T: Default> Default for Partition<T> {
 impl<T> Partition<T> {
     /// Create a new partition iterator.
     pub fn new(set: T, len: usize) -> Self {
-        Self { set, iter: (0..len).powerset() }
+        Self::with_min_size(set, len, 0)
+    }
+
+    /// Create a new partition iterator, which only iterates over the partitions where the
+    /// first part has at least `min_size` elements.
+    pub fn with_min_size(set: T, len: usize, min_size: usize) -> Self {
+        Self {
+            set,
+            iter: Subsets::new(len, min_size),
+        }
     }
 }
 
@@ -48,3 +57,37 @@ where
         }
     }
 }
+
+// basically itertools' Powerset, but we can start at a minimum subset size
+#[derive(Debug, Clone)]
+struct Subsets {
+    len: usize,
+    size: usize,
+    combinations: Combinations<Range<usize>>,
+}
+
+impl Subsets {
+    fn new(len: usize, min_size: usize) -> Self {
+        Self {
+            len,
+            size: min_size,
+            combinations: (0..len).combinations(min_size),
+        }
+    }
+}
+
+impl Iterator for Subsets {
+    type Item = Vec<usize>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(subset) = self.combinations.next() {
+                return Some(subset);
+            }
+            if self.size >= self.len {
+                return None;
+            }
+            self.size += 1;
+            self.combinations = (0..self.len).combinations(self.size);
+        }
+    }
+}
@@ -12,6 +12,7 @@ to create the edges.
 */
 
 use hashbrown::HashMap;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use super::tree::Focus;
@@ -38,6 +39,31 @@ pub enum State {
     Measured,
 }
 
+/// The cost that is optimized when searching for paths, cf. [Graph::cost].
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum Objective {
+    /// The maximum memory, cf. [Graph::max_memory].
+    #[default]
+    Space,
+    /// The space-time volume, cf. [Graph::volume].
+    Volume,
+    /// The qubit idle time, cf. [Graph::idle].
+    Idle,
+}
+
 /// A buffer that holds the edges of a graph.
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct GraphBuffer {
@@ -48,12 +74,22 @@ pub struct GraphBuffer {
 /// between them.
 ///
 /// While initializing and measuring qubits, the graph keeps track of the required
-/// quantum memory.
+/// quantum memory. Per default, every qubit occupies one memory slot, but the nodes can
+/// be weighted, cf. [Graph::with_weights]. Output qubits, which are never measured, can
+/// be marked with [Graph::with_outputs].
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct Graph<'l> {
     nodes: Nodes<'l>,
     current_memory: usize,
     max_memory: usize,
+    weights: Option<&'l [usize]>,
+    outputs: &'l [usize],
+    volume: usize,
+    idle: usize,
+    // the unweighted number of qubits in memory
+    num_in_memory: usize,
+    // the weight of all qubits that are not measured yet
+    remaining_weight: usize,
 }
 
 // not sure how idiomatic it is to do this macro stuff here; a proc macro might be
@@ -137,29 +173,156 @@ impl<'l> Graph<'l> {
                 .collect(),
             current_memory: 0,
             max_memory: 0,
+            weights: None,
+            outputs: &[],
+            volume: 0,
+            idle: 0,
+            num_in_memory: 0,
+            remaining_weight: graph_buffer.len(),
+        }
+    }
+
+    /// Weight the nodes, i.e., node `i` occupies `weights[i]` memory slots when it is
+    /// in memory (None means that every node has weight 1). The memory is then the sum
+    /// of the weights of the nodes in memory.
+    ///
+    /// # Panics
+    /// Panics if the graph has already been focused, or if the number of weights does not
+    /// match the number of nodes.
+    pub fn with_weights(mut self, weights: Option<&'l [usize]>) -> Self {
+        self.assert_unfocused();
+        if let Some(weights) = weights {
+            assert_eq!(
+                weights.len(),
+                self.nodes.len(),
+                "the number of weights does not match the number of nodes"
+            );
+        }
+        self.weights = weights;
+        self.init_costs();
+        self
+    }
+
+    /// Mark the `outputs` as output qubits, i.e., qubits that are never measured. They
+    /// are initialized as usual, when one of their neighbors is measured, and then stay
+    /// in memory until the end. An output that is never initialized this way, e.g., an
+    /// isolated one, is prepared after the last step. So at the end, all output qubits
+    /// are in memory, and the maximum memory is at least the (weighted) number of output
+    /// qubits. The costs that account for the single steps, i.e., the
+    /// [current memory](Self::current_memory), the [volume](Self::volume) and the
+    /// [idle](Self::idle) time, include an output only from its initialization on.
+    ///
+    /// # Panics
+    /// Panics if the graph has already been focused.
+    pub fn with_outputs(mut self, outputs: &'l [usize]) -> Self {
+        self.assert_unfocused();
+        self.outputs = outputs;
+        self.init_costs();
+        self
+    }
+
+    /// Mark the `bits` as initialized, i.e., they are already in memory at the
+    /// beginning, e.g., because they are the frontier of a partially executed schedule.
+    /// This has to be called after [Graph::with_weights] and [Graph::with_outputs].
+    ///
+    /// # Panics
+    /// Panics if the graph has already been focused.
+    pub fn with_initialized(mut self, bits: &[usize]) -> Self {
+        self.assert_unfocused();
+        for bit in bits {
+            self.initialize(*bit);
+        }
+        self.max_memory = self.max_memory.max(self.current_memory);
+        self
+    }
+
+    fn assert_unfocused(&self) {
+        assert!(
+            self.nodes.iter().all(|(state, _)| *state == State::Sleeping),
+            "the graph has already been focused"
+        );
+    }
+
+    fn init_costs(&mut self) {
+        let output_weight: usize = self.outputs.iter().map(|bit| self.weight(*bit)).sum();
+        self.remaining_weight = match self.weights {
+            Some(weights) => weights.iter().sum(),
+            None => self.nodes.len(),
+        } - output_weight;
+        self.max_memory = output_weight;
+    }
+
+    /// Get the output qubits, cf. [Graph::with_outputs].
+    pub fn outputs(&self) -> &[usize] {
+        self.outputs
+    }
+
+    /// Get the weight of the node `bit`, cf. [Graph::with_weights].
+    pub fn weight(&self, bit: usize) -> usize {
+        match self.weights {
+            Some(weights) => weights[bit],
+            None => 1,
         }
     }
 
     /// Get a reference to the nodes of the graph.
-    pub fn nodes(&self) -> &[Node] {
+    pub fn nodes(&self) -> &[Node<'l>] {
         &self.nodes
     }
 
-    /// Get the number of qubits which are currently in quantum memory.
+    /// Get the number of qubits which are currently in quantum memory (weighted, if there
+    /// are weights); this does not include the outputs that have not been initialized
+    /// yet, cf. [Graph::with_outputs].
     pub fn current_memory(&self) -> usize {
         self.current_memory
     }
 
-    /// Get the maximum required quantum memory (so far).
+    /// Get the maximum required quantum memory (so far; this includes all output qubits,
+    /// since they are in memory at the end).
     pub fn max_memory(&self) -> usize {
         self.max_memory
     }
 
+    /// Get the space-time volume (so far), i.e., the sum of the required quantum memory
+    /// over all steps (including the qubits that are measured in the step).
+    pub fn volume(&self) -> usize {
+        self.volume
+    }
+
+    /// Get the qubit idle time (so far), i.e., the sum over all qubits of the number of
+    /// steps in which they have been in memory without being measured. In contrast to
+    /// the memory, this is not weighted.
+    pub fn idle(&self) -> usize {
+        self.idle
+    }
+
+    /// Get the (so far) cost according to the `objective`.
+    pub fn cost(&self, objective: Objective) -> usize {
+        match objective {
+            Objective::Space => self.max_memory,
+            Objective::Volume => self.volume,
+            Objective::Idle => self.idle,
+        }
+    }
+
+    /// Get a lower bound on the final cost according to the `objective`, i.e., the cost
+    /// after all qubits are measured.
+    pub fn cost_bound(&self, objective: Objective) -> usize {
+        match objective {
+            Objective::Space => self.max_memory,
+            // every (non-output) qubit is in memory in the step where it is measured
+            Objective::Volume => self.volume + self.remaining_weight,
+            Objective::Idle => self.idle,
+        }
+    }
+
     fn initialize(&mut self, bit: usize) {
+        let weight = self.weight(bit);
         match &mut self.nodes[bit].0 {
             state @ State::Sleeping => {
                 *state = State::InMemory;
-                self.current_memory += 1;
+                self.current_memory += weight;
+                self.num_in_memory += 1;
             },
             State::InMemory => (),
             State::Measured => {},
@@ -170,14 +333,18 @@ impl<'l> Graph<'l> {
 macro_rules! impl_measure {
     ($name:ident, $check:tt) => {
         fn $name(&mut self, bit: usize) -> return_type!($check) {
+            let weight = self.weight(bit);
             let node = &mut self.nodes[bit];
             match node.0 {
                 State::Sleeping => {
                     // corrected later on in self.update_memory
-                    self.current_memory += 1;
+                    self.current_memory += weight;
                     node.0 = State::Measured;
                 },
-                State::InMemory => node.0 = State::Measured,
+                State::InMemory => {
+                    node.0 = State::Measured;
+                    self.num_in_memory -= 1;
+                },
                 State::Measured => {
                     return return_error!($check, bit);
                 },
@@ -217,11 +384,20 @@ macro_rules! return_ok {
 impl<'l> Graph<'l> {
     impl_measure!(measure, checked);
 
-    fn update_memory(&mut self, len: usize) {
+    fn update_memory(&mut self, measure_set: &[usize]) {
         if self.current_memory > self.max_memory {
             self.max_memory = self.current_memory;
         }
-        self.current_memory -= len; // correct ...
+        self.volume += self.current_memory;
+        // correct ...
+        let measured_weight = match self.weights {
+            Some(weights) => measure_set.iter().map(|bit| weights[*bit]).sum::<usize>(),
+            None => measure_set.len(),
+        };
+        self.current_memory -= measured_weight;
+        self.remaining_weight -= measured_weight;
+        // the qubits which are still in memory idle in this step
+        self.idle += self.num_in_memory;
     }
 }
 
@@ -239,7 +415,7 @@ impl<'l> Focus<&[usize]> for Graph<'l> {
         for bit in measure_set {
             self.measure(*bit)?;
         }
-        self.update_memory(measure_set.len());
+        self.update_memory(measure_set);
         Ok(())
     }
 }
@@ -251,7 +427,7 @@ impl<'l> Graph<'l> {
         for bit in measure_set {
             self.measure_unchecked(*bit);
         }
-        self.update_memory(measure_set.len());
+        self.update_memory(measure_set);
     }
     pub(super) fn focus_unchecked(&self, measure_set: &[usize]) -> Self {
         let mut new = self.clone();
@@ -313,6 +489,12 @@ mod tests {
                 ],
                 current_memory: 0,
                 max_memory: 0,
+                weights: None,
+                outputs: &[],
+                volume: 0,
+                idle: 0,
+                num_in_memory: 0,
+                remaining_weight: 4,
             }
         );
     }
@@ -332,10 +514,47 @@ mod tests {
         manually.nodes[1].0 = InMemory;
         manually.current_memory = 2; // 4 -> 2
         manually.max_memory = 4;
+        manually.volume = 4;
+        manually.idle = 2;
+        manually.num_in_memory = 2;
+        manually.remaining_weight = 3;
         let mut graph = init_graph;
         graph.focus_inplace(&[2]).unwrap();
         graph.focus_inplace(&[3]).unwrap();
         manually.max_memory = 3; // current_memory: 3 -> 2 -> 3 -> 2
+        manually.volume = 6;
+        manually.idle = 4;
         assert_eq!(graph, manually);
     }
+
+    #[test]
+    fn weighted() {
+        let buffer = example_graph();
+        let weights = [1, 2, 3, 4, 5];
+        let mut graph = Graph::new(buffer.as_buffer()).with_weights(Some(&weights));
+        graph.focus_inplace(&[2]).unwrap();
+        // 0, 2 and 3 are in memory, then 2 is measured
+        assert_eq!(graph.max_memory(), 8);
+        assert_eq!(graph.current_memory(), 5);
+        graph.focus_inplace(&[3]).unwrap();
+        // 1 is initialized, then 3 is measured
+        assert_eq!(graph.max_memory(), 8);
+        assert_eq!(graph.current_memory(), 3);
+    }
+
+    #[test]
+    fn outputs() {
+        let buffer = example_graph();
+        let mut graph = Graph::new(buffer.as_buffer()).with_outputs(&[1, 4]);
+        assert_eq!(graph.max_memory(), 2);
+        assert_eq!(graph.cost_bound(Objective::Volume), 3);
+        graph.focus_inplace(&[0]).unwrap();
+        graph.focus_inplace(&[2, 3]).unwrap();
+        // the output 1 stays in memory, 4 is isolated, so it is only initialized at the
+        // end
+        assert_eq!(graph.max_memory(), 3);
+        assert_eq!(graph.current_memory(), 1);
+        assert_eq!(graph.volume(), 6);
+        assert_eq!(graph.idle(), 3);
+    }
 }
@@ -10,22 +10,24 @@ introduced by quantum measurements, e.g., as in MBQC, and captured by a Pauli tr
 [MBQC]: https://doi.org/10.48550/arXiv.0910.1116
 */
 
-use std::hash::BuildHasherDefault;
+use std::{borrow::Cow, cmp, hash::BuildHasherDefault};
 
-use hashbrown::HashMap;
+use bitvec::vec::BitVec;
+use hashbrown::{HashMap, HashSet};
 use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
 use rustc_hash::FxHasher;
 use thiserror::Error;
 
 use super::{
-    tree::{Focus, FocusIterator, Step, Sweep},
     Partition,
+    tree::{Focus, FocusIterator, Step, Sweep},
 };
 
 pub type RefPartialOrderGraph<'l> = &'l [Vec<(usize, Vec<usize>)>];
 
 type DepsCounters = HashMap<usize, usize, BuildHasherDefault<FxHasher>>;
 type Dependents = Vec<Vec<usize>>;
+type Latencies = Vec<Vec<usize>>;
 
 /// A buffer that holds the dependency structure implied by a [PartialOrderGraph], in a
 /// form that is more efficient for the [PathGenerator]. It will be initialized once and
@@ -33,6 +35,20 @@ type Dependents = Vec<Vec<usize>>;
 #[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub struct DependencyBuffer {
     dependents: Dependents,
+    // the latencies of the edges to the dependents (same layout as `dependents`); empty
+    // if there are no latencies at all, so that we can skip them cheaply
+    latencies: Latencies,
+    max_latency: usize,
+    latency: usize,
+    edge_latencies: Vec<(usize, usize, usize)>,
+    outputs: Vec<usize>,
+    release_times: Vec<(usize, usize)>,
+    deadline_config: Vec<(usize, usize)>,
+    // the deadline for each bit (usize::MAX if there is none); empty if there are no
+    // deadlines at all
+    deadlines: Vec<usize>,
+    max_release_time: usize,
+    dropped_dependencies: usize,
 }
 
 impl DependencyBuffer {
@@ -41,12 +57,290 @@ impl DependencyBuffer {
     pub fn new(len: usize) -> Self {
         Self {
             dependents: vec![Vec::new(); len],
+            ..Default::default()
         }
     }
 
+    /// Set the classical feed-forward latencies, i.e., the number of steps that have to
+    /// pass, additionally to the usual one step, after measuring a qubit before its
+    /// dependents can be measured. `latency` is the default for all dependency edges,
+    /// and `edge_latencies` overwrites it for specific edges, given as `(dependency,
+    /// dependent, latency)` (in the same ids as the graph passed to
+    /// [PathGenerator::from_dependency_graph]). If such an edge is not a direct
+    /// dependency in the graph, but an implied one, i.e., the dependent depends on the
+    /// dependency via others, it is added as a direct dependency; if the dependent does
+    /// not depend on the dependency at all, the latency is ignored.
+    ///
+    /// This has to be done before the buffer is passed to
+    /// [PathGenerator::from_dependency_graph].
+    pub fn set_latencies(
+        &mut self,
+        latency: usize,
+        edge_latencies: &[(usize, usize, usize)],
+    ) {
+        self.latency = latency;
+        self.edge_latencies = edge_latencies.to_vec();
+    }
+
+    /// Set the output qubits, which are never measured, i.e., they are ignored if they
+    /// are in the graph passed to [PathGenerator::from_dependency_graph] (and if a qubit
+    /// depends on an output qubit, this dependency is ignored). The ids are the same as
+    /// in that graph.
+    ///
+    /// This has to be done before the buffer is passed to
+    /// [PathGenerator::from_dependency_graph].
+    pub fn set_outputs(&mut self, outputs: &[usize]) {
+        self.outputs = outputs.to_vec();
+    }
+
+    /// Set release times, given as `(bit, step)`, i.e., `bit` cannot be measured before
+    /// `step` (the steps are counted from 0). If the qubits must also not be initialized
+    /// before their release time, one has to include their neighbors in the
+    /// `release_times` (the [PathGenerator] does not know about the neighbors). The ids
+    /// are the same as in the graph passed to [PathGenerator::from_dependency_graph].
+    ///
+    /// This has to be done before the buffer is passed to
+    /// [PathGenerator::from_dependency_graph].
+    pub fn set_release_times(&mut self, release_times: &[(usize, usize)]) {
+        self.release_times = release_times.to_vec();
+    }
+
+    /// Set deadlines, given as `(bit, step)`, i.e., `bit` has to be measured in `step`
+    /// at the latest (the steps are counted from 0). When iterating over the paths with
+    /// a [Partitioner], steps after which a deadline cannot be met anymore are skipped,
+    /// cf. [PathGenerator::missed_deadline]. The ids are the same as in the graph passed
+    /// to [PathGenerator::from_dependency_graph].
+    ///
+    /// This has to be done before the buffer is passed to
+    /// [PathGenerator::from_dependency_graph].
+    pub fn set_deadlines(&mut self, deadlines: &[(usize, usize)]) {
+        self.deadline_config = deadlines.to_vec();
+    }
+
     pub fn num_bits(&self) -> usize {
         self.dependents.len()
     }
+
+    /// The maximum latency over all dependency edges (only meaningful after the buffer
+    /// has been filled by [PathGenerator::from_dependency_graph]).
+    pub fn max_latency(&self) -> usize {
+        self.max_latency
+    }
+
+    /// The number of dependencies that have been dropped by
+    /// [PathGenerator::from_dependency_graph], because they are implied by other
+    /// dependencies, cf. [reduce_dependencies] (only meaningful after the buffer has been
+    /// filled).
+    pub fn num_dropped_dependencies(&self) -> usize {
+        self.dropped_dependencies
+    }
+
+    // forget the dependency structure of the previous graph, but not the configuration
+    fn reset(&mut self) {
+        for bit_dependents in self.dependents.iter_mut() {
+            bit_dependents.clear();
+        }
+        self.latencies.clear();
+        self.max_latency = 0;
+        self.deadlines.clear();
+        self.max_release_time = 0;
+        self.dropped_dependencies = 0;
+    }
+
+    // requires that the dependents are already resolved; the `edge_latencies` are the
+    // mapped self.edge_latencies
+    fn resolve_latencies(&mut self, edge_latencies: &[(usize, usize, usize)]) {
+        if self.latency == 0 && edge_latencies.iter().all(|(_, _, l)| *l == 0) {
+            self.latencies = Latencies::new();
+            self.max_latency = 0;
+            return;
+        }
+        let edge_latencies: HashMap<(usize, usize), usize> = edge_latencies
+            .iter()
+            .map(|(dependency, dependent, latency)| ((*dependency, *dependent), *latency))
+            .collect();
+        self.latencies = self
+            .dependents
+            .iter()
+            .enumerate()
+            .map(|(bit, dependents)| {
+                dependents
+                    .iter()
+                    .map(|dependent| {
+                        *edge_latencies.get(&(bit, *dependent)).unwrap_or(&self.latency)
+                    })
+                    .collect()
+            })
+            .collect();
+        self.max_latency =
+            self.latencies.iter().flatten().copied().max().unwrap_or_default();
+    }
+}
+
+// add the edges with a specific latency as direct dependencies, if the dependency is
+// implied (a latency on an implied dependency is not covered by the dependencies that
+// imply it, if it is larger than the sum of their latencies); edges between independent
+// bits are ignored
+fn add_latency_edges(
+    graph: &mut PartialOrderGraph,
+    edge_latencies: &[(usize, usize, usize)],
+) {
+    let positions: HashMap<usize, (usize, usize)> = graph
+        .iter()
+        .enumerate()
+        .flat_map(|(layer_idx, layer)| {
+            layer
+                .iter()
+                .enumerate()
+                .map(move |(idx, (bit, _))| (*bit, (layer_idx, idx)))
+        })
+        .collect();
+    for (dependency, dependent, _) in edge_latencies {
+        let (Some((dependency_layer, _)), Some((layer_idx, idx))) =
+            (positions.get(dependency), positions.get(dependent))
+        else {
+            continue;
+        };
+        if dependency_layer < layer_idx
+            && !graph[*layer_idx][*idx].1.contains(dependency)
+            && depends_on(graph, &positions, (*layer_idx, *idx), *dependency)
+        {
+            graph[*layer_idx][*idx].1.push(*dependency);
+        }
+    }
+}
+
+// whether the bit at `position` depends, directly or indirectly, on `dependency`; we only
+// walk the layers after the one of the `dependency`, since the others cannot depend on it
+fn depends_on(
+    graph: &PartialOrderGraph,
+    positions: &HashMap<usize, (usize, usize)>,
+    position: (usize, usize),
+    dependency: usize,
+) -> bool {
+    let dependency_layer = positions[&dependency].0;
+    let mut visited = HashSet::new();
+    let mut stack = vec![position];
+    while let Some((layer_idx, idx)) = stack.pop() {
+        for dep in graph[layer_idx][idx].1.iter() {
+            if *dep == dependency {
+                return true;
+            }
+            if let Some(position) = positions.get(dep) {
+                if position.0 > dependency_layer && visited.insert(*dep) {
+                    stack.push(*position);
+                }
+            }
+        }
+    }
+    false
+}
+
+/// Remove the dependencies in the `graph` that are implied by other dependencies
+/// (transitive reduction) and duplicate dependencies, returning how many have been
+/// removed. The order of the remaining dependencies is kept.
+///
+/// This requires that the dependencies of a node are in earlier layers, as it is the
+/// case for the graphs from
+/// [induced_order](pauli_tracker::tracker::frames::induced_order). The layers are
+/// processed in order, and for each node, only its ancestors in the layers that are
+/// spanned by its dependencies are visited (via the already reduced dependencies). The
+/// memory is linear, and so is the time if the dependencies reach back only a bounded
+/// number of layers (in general, a transitive reduction is as hard as a matrix
+/// multiplication).
+///
+/// # Examples
+/// ```
+/// # use mbqc_scheduling::scheduler::time::reduce_dependencies;
+/// // 0 -> 1 -> 2 and 0 -> 2
+/// let mut graph = vec![vec![(0, vec![])], vec![(1, vec![0])], vec![(2, vec![0, 1])]];
+/// assert_eq!(reduce_dependencies(&mut graph), 1);
+/// assert_eq!(graph, vec![vec![(0, vec![])], vec![(1, vec![0])], vec![(2, vec![1])]]);
+/// ```
+pub fn reduce_dependencies(graph: &mut PartialOrderGraph) -> usize {
+    let num_bits = graph
+        .iter()
+        .flatten()
+        .flat_map(|(bit, deps)| deps.iter().chain([bit]))
+        .max()
+        .map_or(0, |max| max + 1);
+    // (layer, index in the layer) of the bits; None for bits that are not in the graph
+    let mut positions = vec![None; num_bits];
+    for (layer_idx, layer) in graph.iter().enumerate() {
+        for (idx, (bit, _)) in layer.iter().enumerate() {
+            positions[*bit] = Some((layer_idx, idx));
+        }
+    }
+    // the bits with the current mark are the (direct or indirect) dependencies of the
+    // current bit, via the dependencies that are kept so far
+    let mut marks = vec![0; num_bits];
+    let mut mark = 0;
+    let mut order = Vec::new();
+    let mut keep = Vec::new();
+    let mut stack = Vec::new();
+    let mut dropped = 0;
+
+    for layer_idx in 0..graph.len() {
+        let (done, rest) = graph.split_at_mut(layer_idx);
+        for (_, deps) in rest[0].iter_mut() {
+            mark += 1;
+            let layer = |bit: usize| positions[bit].map_or(0, |(layer, _)| layer);
+            let min_layer = deps.iter().map(|dep| layer(*dep)).min().unwrap_or_default();
+            // a dependency can only be implied by dependencies in later layers; the sort
+            // is stable, so that the first one of duplicates is kept
+            order.clear();
+            order.extend(0..deps.len());
+            order.sort_by_key(|idx| cmp::Reverse(layer(deps[*idx])));
+            keep.clear();
+            keep.resize(deps.len(), true);
+
+            for idx in order.iter() {
+                let dep = deps[*idx];
+                if marks[dep] == mark {
+                    keep[*idx] = false;
+                    continue;
+                }
+                marks[dep] = mark;
+                stack.extend(positions[dep].filter(|(layer, _)| *layer < layer_idx));
+                while let Some((layer, idx)) = stack.pop() {
+                    for ancestor in done[layer][idx].1.iter() {
+                        // the bits before the `min_layer` cannot be dependencies, and
+                        // neither can their ancestors
+                        if marks[*ancestor] == mark
+                            || positions[*ancestor]
+                                .is_some_and(|(layer, _)| layer < min_layer)
+                        {
+                            continue;
+                        }
+                        marks[*ancestor] = mark;
+                        stack.extend(
+                            positions[*ancestor].filter(|(layer, _)| *layer < layer_idx),
+                        );
+                    }
+                }
+            }
+
+            let len = deps.len();
+            let mut keep = keep.iter();
+            deps.retain(|_| *keep.next().expect("one flag per dependency"));
+            dropped += len - deps.len();
+        }
+    }
+    dropped
+}
+
+// the state of the feed-forward latencies; it is trivial, i.e., no allocations, if there
+// are no latencies
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Delays {
+    // the number of steps done so far
+    step: usize,
+    // the earliest step in which a not yet measurable bit can be measured, because of the
+    // latencies of its already measured dependencies
+    earliest: DepsCounters,
+    // bits whose dependencies are all measured, but which still have to wait for the
+    // latency; (bit, earliest step)
+    waiting: Vec<(usize, usize)>,
 }
 
 type Set = Vec<usize>;
@@ -78,19 +372,24 @@ pub struct PathGenerator<'l, T /* Measurable */> {
     // HashMap, this does not really change the lookup time, rather the removing might
     // cause a slight overhead, and also we have the additional time and space overhead
     // when cloning it
-    dependents: &'l Dependents,
+    buffer: &'l DependencyBuffer,
+    max_step_size: Option<usize>,
+    delays: Delays,
 }
 
 impl<'l, T> PathGenerator<'l, T> {
     fn new(
         measureable: T,
         deps_counter: DepsCounters,
-        dependents: &'l Dependents,
+        delays: Delays,
+        buffer: &'l DependencyBuffer,
     ) -> Self {
         Self {
             measurable: measureable,
             deps_counter,
-            dependents,
+            buffer,
+            max_step_size: None,
+            delays,
         }
     }
 
@@ -107,12 +406,31 @@ impl<'l, T> PathGenerator<'l, T> {
 
     /// How many nodes are left to be measured.
     pub fn num_remaining_nodes(&self) -> usize {
-        self.deps_counter.len()
+        self.deps_counter.len() + self.delays.waiting.len()
     }
 
     /// Check whether there are qubits that cannot be measured yet.
     pub fn has_unmeasureable(&self) -> bool {
-        !self.deps_counter.is_empty()
+        !self.deps_counter.is_empty() || !self.delays.waiting.is_empty()
+    }
+
+    /// Get the number of steps that have been done so far.
+    pub fn step(&self) -> usize {
+        self.delays.step
+    }
+
+    /// Get the qubits whose dependencies are all measured, but which cannot be measured
+    /// yet because of the feed-forward latency or their release time, together with the
+    /// step in which they become measurable, cf. [DependencyBuffer::set_latencies] and
+    /// [DependencyBuffer::set_release_times].
+    pub fn waiting(&self) -> &[(usize, usize)] {
+        &self.delays.waiting
+    }
+
+    /// Get the maximum number of qubits that are measured in one step, if bounded, cf.
+    /// [with_max_step_size](Self::with_max_step_size).
+    pub fn max_step_size(&self) -> Option<usize> {
+        self.max_step_size
     }
 }
 
@@ -121,30 +439,48 @@ impl<'l, T: MeasurableSet> PathGenerator<'l, T> {
     /// going to own the dependency structure implied by the `graph`, so that it can be
     /// reused again.
     ///
+    /// Dependencies that are implied by other dependencies are dropped, cf.
+    /// [reduce_dependencies] and [DependencyBuffer::num_dropped_dependencies], unless
+    /// there are edge specific latencies (then a direct dependency might be stricter
+    /// than an indirect one); in that case, the edges with a specific latency are added
+    /// if they are missing, cf. [DependencyBuffer::set_latencies].
+    ///
     /// # Panics
     /// Panics if the dependency_buffer has a length smaller than the number of qubits
-    /// in the `graph`
+    /// in the `graph`, or if a deadline, cf. [DependencyBuffer::set_deadlines], is for a
+    /// qubit outside of the buffer
     pub fn from_dependency_graph(
         graph: RefPartialOrderGraph,
         dependency_buffer: &'l mut DependencyBuffer,
         bit_mapping: Option<&HashMap<usize, usize>>,
     ) -> Self {
-        let dependents = &mut dependency_buffer.dependents;
-
+        dependency_buffer.reset();
         if graph.is_empty() {
-            return Self {
-                measurable: T::default(),
-                deps_counter: DepsCounters::default(),
-                dependents,
-            };
+            return Self::new(
+                T::default(),
+                DepsCounters::default(),
+                Delays::default(),
+                dependency_buffer,
+            );
         }
 
+        let mut edge_latencies = dependency_buffer.edge_latencies.clone();
+        let mut outputs = dependency_buffer.outputs.clone();
+        let mut release_times = dependency_buffer.release_times.clone();
+        let mut deadlines = dependency_buffer.deadline_config.clone();
+
+        // with uniform latencies, an indirect dependency takes at least two steps, plus
+        // the latencies, while a direct one takes only one step, plus the latency
+        let reduce = edge_latencies
+            .iter()
+            .all(|(_, _, latency)| *latency == dependency_buffer.latency);
+
         // one could/should? do some similar macro stuff as in super::space to get rid
         // of one loop run ...
-        let mut cloned_graph: PartialOrderGraph;
-        let graph = if let Some(bit_mapping) = bit_mapping {
-            cloned_graph = graph.to_vec();
-            for layer in cloned_graph.iter_mut() {
+        // the graph is only cloned if it has to be modified
+        let mut graph = Cow::Borrowed(graph);
+        if let Some(bit_mapping) = bit_mapping {
+            for layer in graph.to_mut().iter_mut() {
                 for (bit, deps) in layer {
                     update!(bit; bit_mapping);
                     for dep in deps.iter_mut() {
@@ -152,56 +488,214 @@ impl<'l, T: MeasurableSet> PathGenerator<'l, T> {
                     }
                 }
             }
-            &cloned_graph
+            for (dependency, dependent, _) in edge_latencies.iter_mut() {
+                update!(dependency; bit_mapping);
+                update!(dependent; bit_mapping);
+            }
+            for output in outputs.iter_mut() {
+                update!(output; bit_mapping);
+            }
+            for (bit, _) in release_times.iter_mut().chain(deadlines.iter_mut()) {
+                update!(bit; bit_mapping);
+            }
+        }
+        if !outputs.is_empty() {
+            let num_bits = dependency_buffer.dependents.len();
+            let mut is_output = BitVec::<usize>::repeat(false, num_bits);
+            // outputs outside of the buffer cannot be in the graph
+            for output in outputs.iter().filter(|output| **output < num_bits) {
+                is_output.set(*output, true);
+            }
+            for layer in graph.to_mut().iter_mut() {
+                layer.retain(|(bit, _)| !is_output[*bit]);
+                for (_, deps) in layer {
+                    deps.retain(|dep| !is_output[*dep]);
+                }
+            }
+        }
+        // do this after removing the outputs, since a dependency that is only implied
+        // via an output is not implied anymore
+        if reduce {
+            dependency_buffer.dropped_dependencies = reduce_dependencies(graph.to_mut());
         } else {
-            graph
-        };
+            add_latency_edges(graph.to_mut(), &edge_latencies);
+        }
+        if dependency_buffer.dropped_dependencies > 0 {
+            tracing::debug!(
+                "dropped {} redundant dependencies",
+                dependency_buffer.dropped_dependencies
+            );
+        }
 
-        fn resolve(bit: usize, rest: &[Vec<(usize, Vec<usize>)>], look: &mut Dependents) {
-            let mut dependents = Vec::new();
-            for layer in rest {
-                for (dep, deps) in layer {
-                    if deps.contains(&bit) {
-                        dependents.push(*dep);
-                    }
-                }
+        // the dependents are in the order of the layers, i.e., of the graph
+        let dependents = &mut dependency_buffer.dependents;
+        for (bit, deps) in graph.iter().flatten() {
+            for dep in deps {
+                dependents[*dep].push(*bit);
             }
-            look[bit] = dependents;
         }
 
         let mut measureable = Vec::new();
         let mut deps = HashMap::default();
 
+        // the release times are handled as latencies that are already there at the
+        // beginning
+        let mut delays = Delays::default();
+        for (bit, release_time) in release_times.iter() {
+            if *release_time > 0 {
+                let ready = delays.earliest.entry(*bit).or_default();
+                *ready = cmp::max(*ready, *release_time);
+            }
+        }
+        let mut push_free = |bit: usize| match delays.earliest.remove(&bit) {
+            Some(ready) => delays.waiting.push((bit, ready)),
+            None => measureable.push(bit),
+        };
+
         let mut graph_iter = graph.iter();
 
         let first = graph_iter.next().unwrap();
-        let rest = graph_iter.as_ref();
         for (bit, _) in first {
-            resolve(*bit, rest, dependents);
-            measureable.push(*bit);
+            push_free(*bit);
         }
 
-        while let Some(layer) = graph_iter.next() {
-            let rest = graph_iter.as_ref();
+        for layer in graph_iter {
             for (bit, dependency) in layer {
-                resolve(*bit, rest, dependents);
-                deps.insert(*bit, dependency.len());
+                // only happens if all dependencies were outputs
+                if dependency.is_empty() {
+                    push_free(*bit);
+                } else {
+                    deps.insert(*bit, dependency.len());
+                }
             }
         }
 
-        let measureable = T::init(measureable);
-        Self {
-            measurable: measureable,
-            deps_counter: deps,
-            dependents,
+        dependency_buffer.resolve_latencies(&edge_latencies);
+        dependency_buffer.max_release_time =
+            release_times.iter().map(|(_, step)| *step).max().unwrap_or_default();
+        dependency_buffer.deadlines = if deadlines.is_empty() {
+            Vec::new()
+        } else {
+            let mut resolved = vec![usize::MAX; dependency_buffer.dependents.len()];
+            for (bit, deadline) in deadlines {
+                resolved[bit] = cmp::min(resolved[bit], deadline);
+            }
+            resolved
+        };
+
+        Self::new(T::init(measureable), deps, delays, dependency_buffer)
+    }
+
+    /// Restrict the number of qubits that are measured in one step to `max_step_size`
+    /// (None means unbounded). This only restricts the steps that are iterated over when
+    /// using a [Partitioner]; manually focused steps are not checked.
+    ///
+    /// # Panics
+    /// Panics if `max_step_size` is `Some(0)`.
+    pub fn with_max_step_size(mut self, max_step_size: Option<usize>) -> Self {
+        assert_ne!(max_step_size, Some(0), "max_step_size must be at least 1");
+        self.max_step_size = max_step_size;
+        self.measurable = self.init_measurable(self.measurable.set().to_vec());
+        self
+    }
+
+    /// Check whether all qubits have been measured.
+    pub fn is_finished(&self) -> bool {
+        self.measurable.set().is_empty() && self.delays.waiting.is_empty()
+    }
+
+    /// A lower bound on the number of steps that are needed to measure all remaining
+    /// qubits.
+    pub fn min_remaining_steps(&self) -> usize {
+        let num_measurable = self.measurable.set().len();
+        let num_unmeasurable = self.num_remaining_nodes();
+        let bound = if num_measurable == 0 {
+            0
+        } else if num_unmeasurable == 0 {
+            1
+        } else {
+            2
+        };
+        // a qubit that cannot be measured before step `ready` needs all the steps up to
+        // and including `ready`
+        let bound = self
+            .delays
+            .waiting
+            .iter()
+            .map(|(_, ready)| ready)
+            .chain(self.delays.earliest.values())
+            .fold(bound, |bound, ready| {
+                cmp::max(bound, (ready + 1).saturating_sub(self.delays.step))
+            });
+        match self.max_step_size {
+            // the unmeasurable qubits can only be measured after at least one step
+            Some(max) => cmp::max(
+                bound,
+                cmp::max(
+                    (num_measurable + num_unmeasurable).div_ceil(max),
+                    num_unmeasurable.div_ceil(max) + (num_unmeasurable > 0) as usize,
+                ),
+            ),
+            None => bound,
+        }
+    }
+
+    /// An upper bound on the number of steps of any path from here on, including the
+    /// steps that have already been done. Without latencies, this is just the number of
+    /// qubits, but with latencies there might be idle steps, where nothing is measured
+    /// while waiting for the feed-forward.
+    pub fn max_path_length(&self) -> usize {
+        self.delays.step
+            + (self.measurable.set().len() + self.num_remaining_nodes())
+                * (1 + self.buffer.max_latency)
+            + self.buffer.max_release_time
+    }
+
+    /// Check whether there is a qubit that cannot be measured anymore before its
+    /// deadline, cf. [DependencyBuffer::set_deadlines], and if so, return it together
+    /// with its deadline.
+    pub fn missed_deadline(&self) -> Option<(usize, usize)> {
+        let deadlines = &self.buffer.deadlines;
+        if deadlines.is_empty() {
+            return None;
+        }
+        let step = self.delays.step;
+        let missed = |bit: usize, earliest: usize| {
+            (deadlines[bit] < earliest).then_some((bit, deadlines[bit]))
+        };
+        self.measurable
+            .set()
+            .iter()
+            .find_map(|bit| missed(*bit, step))
+            .or_else(|| {
+                self.delays
+                    .waiting
+                    .iter()
+                    .find_map(|(bit, ready)| missed(*bit, *ready))
+            })
+            .or_else(|| {
+                // the dependencies have to be measured first
+                self.deps_counter.keys().find_map(|bit| {
+                    let ready =
+                        self.delays.earliest.get(bit).copied().unwrap_or_default();
+                    missed(*bit, cmp::max(step + 1, ready))
+                })
+            })
+    }
+
+    fn init_measurable(&self, set: Vec<usize>) -> T {
+        match self.max_step_size {
+            Some(max) => T::init_bounded(set, max),
+            None => T::init(set),
         }
     }
 
     // check whether the measure_set is really measurable and return the new
     // measurable set
     fn partition(&self, measure_set: &[usize]) -> Result<Vec<usize>, NotMeasurable> {
-        let mut new_measurable_set =
-            Vec::with_capacity(self.measurable.set().len() - measure_set.len());
+        let mut new_measurable_set = Vec::with_capacity(
+            self.measurable.set().len().saturating_sub(measure_set.len()),
+        );
         let mut copy_measure_set = measure_set.to_vec();
         for e in self.measurable.set().iter() {
             if let Some(p) = copy_measure_set.iter().position(|m| m == e) {
@@ -222,29 +716,53 @@ impl<'l, T: MeasurableSet> PathGenerator<'l, T> {
     /// # Panics
     /// Panics if measure_set contains a bit with a dependent that is already resolved.
     fn update_unchecked(
-        // always self.look; don't use self because of borrow problems
-        look: &Dependents,
+        // always self.buffer; don't use self because of borrow problems
+        buffer: &DependencyBuffer,
         deps: &mut DepsCounters, // might be self.deps
+        delays: &mut Delays,     // might be self.delays
         measure_set: &[usize],
         new_measurable_set: &mut Vec<usize>,
     ) {
+        let step = delays.step;
+        delays.step += 1;
         for measure in measure_set.iter() {
-            let dependents = &look[*measure];
-            for bit in dependents {
+            let dependents = &buffer.dependents[*measure];
+            let latencies = buffer.latencies.get(*measure);
+            for (i, bit) in dependents.iter().enumerate() {
                 let dependency_count = deps
                     .get_mut(bit)
                     .unwrap_or_else(|| panic!("the {bit} is already resolved"));
                 *dependency_count -= 1;
+                if let Some(&latency) = latencies.and_then(|l| l.get(i)) {
+                    if latency > 0 {
+                        let ready = delays.earliest.entry(*bit).or_default();
+                        *ready = cmp::max(*ready, step + 1 + latency);
+                    }
+                }
                 if *dependency_count == 0 {
                     match deps.remove(bit) {
                         Some(_) => {},
                         // already checked above with the get_mut
                         None => unreachable!(),
                     }
-                    new_measurable_set.push(*bit);
+                    match delays.earliest.remove(bit) {
+                        Some(ready) if ready > delays.step => {
+                            delays.waiting.push((*bit, ready))
+                        },
+                        _ => new_measurable_set.push(*bit),
+                    }
                 }
             }
         }
+        let now = delays.step;
+        delays.waiting.retain(|&(bit, ready)| {
+            if ready <= now {
+                new_measurable_set.push(bit);
+                false
+            } else {
+                true
+            }
+        });
     }
 
     /// # Panics
@@ -255,13 +773,21 @@ impl<'l, T: MeasurableSet> PathGenerator<'l, T> {
         mut new_measureable_set: Vec<usize>,
     ) -> Self {
         let mut deps = self.deps_counter.clone();
+        let mut delays = self.delays.clone();
         Self::update_unchecked(
-            self.dependents,
+            self.buffer,
             &mut deps,
+            &mut delays,
             measure_set,
             &mut new_measureable_set,
         );
-        Self::new(T::init(new_measureable_set), deps, self.dependents)
+        Self {
+            measurable: self.init_measurable(new_measureable_set),
+            deps_counter: deps,
+            buffer: self.buffer,
+            max_step_size: self.max_step_size,
+            delays,
+        }
     }
 }
 
@@ -280,12 +806,13 @@ impl<T: MeasurableSet> Focus<&[usize]> for PathGenerator<'_, T> {
         let mut new_measureable_set = self.partition(measure_set)?;
         // self.partition already catches ensures the input is okay
         Self::update_unchecked(
-            self.dependents,
+            self.buffer,
             &mut self.deps_counter,
+            &mut self.delays,
             measure_set,
             &mut new_measureable_set,
         );
-        self.measurable = T::init(new_measureable_set);
+        self.measurable = self.init_measurable(new_measureable_set);
         Ok(())
     }
 }
@@ -299,20 +826,28 @@ impl FocusIterator for PathGenerator<'_, Partitioner> {
         Self: Sized,
     {
         // let (measuring, new_measurable_set) = self.measurable.next()?;
-        let (new_measurable_set, measuring) = self.measurable.next()?;
-        if measuring.is_empty() {
-            return None;
-        }
-        Some((
+        loop {
+            let (new_measurable_set, measuring) = self.measurable.next()?;
+            // an idle step (this partition comes only once, as the last one) makes only
+            // sense if there are qubits waiting for the latency or their release; then it
+            // is not only needed if nothing is measurable, but it can also be better than
+            // measuring right away, e.g., because the measured qubits would initialize
+            // their neighbors, which then have to wait in memory for the waiting ones
+            if measuring.is_empty() && self.delays.waiting.is_empty() {
+                return None;
+            }
             // we know that the input is fine, because it is a partition of
             // self.measurable
-            self.focus_unchecked(&measuring, new_measurable_set),
-            measuring,
-        ))
+            let next = self.focus_unchecked(&measuring, new_measurable_set);
+            // skip infeasible branches
+            if next.missed_deadline().is_none() {
+                return Some((next, measuring));
+            }
+        }
     }
 
     fn at_leaf(&self) -> Option<Self::LeafItem> {
-        self.measurable.set.is_empty().then_some(())
+        self.is_finished().then_some(())
     }
 }
 
@@ -341,6 +876,13 @@ pub trait MeasurableSet: sealed::Sealed + Default {
     /// Create a new instance of the type from a set of measurable qubits.
     fn init(set: Vec<usize>) -> Self;
 
+    /// Like [init](Self::init), but at most `max_step_size` of the qubits are measured
+    /// at once (if the type is iterating over the measurement steps).
+    fn init_bounded(set: Vec<usize>, max_step_size: usize) -> Self {
+        let _ = max_step_size;
+        Self::init(set)
+    }
+
     /// Get the set of measurable qubits.
     fn set(&self) -> &[usize];
 }
@@ -361,6 +903,13 @@ impl MeasurableSet for Partition<Vec<usize>> {
         Partition::new(set, len)
     }
 
+    fn init_bounded(set: Vec<usize>, max_step_size: usize) -> Self {
+        let len = set.len();
+        // the first part of the partition is the new measurable set, the second part is
+        // measured
+        Partition::with_min_size(set, len, len.saturating_sub(max_step_size))
+    }
+
     fn set(&self) -> &[usize] {
         &self.set
     }
@@ -426,6 +975,234 @@ mod tests {
         );
     }
 
+    #[test]
+    fn redundant_dependencies() {
+        // the example ordering with the implied (and duplicated) dependency of 2 on 0
+        let ordering = vec![
+            vec![(0, vec![])],
+            vec![(3, vec![0]), (1, vec![0])],
+            vec![(2, vec![0, 3, 0])],
+        ];
+        let mut reduced = ordering.clone();
+        assert_eq!(reduce_dependencies(&mut reduced), 2);
+        assert_eq!(reduced, example_ordering());
+
+        // a chain where every bit depends on all the previous ones, and two independent
+        // bits depending on the end of the chain and on the bit that is not in the graph
+        let mut chain: PartialOrderGraph =
+            (0..10).map(|bit| vec![(bit, (0..bit).rev().collect())]).collect();
+        chain.push(vec![(10, vec![42, 9, 3]), (11, vec![42, 0, 42])]);
+        assert_eq!(reduce_dependencies(&mut chain), 36 + 1 + 1);
+        let mut expected: PartialOrderGraph = (0..10)
+            .map(|bit| vec![(bit, (0..bit).rev().take(1).collect())])
+            .collect();
+        expected.push(vec![(10, vec![42, 9]), (11, vec![42, 0])]);
+        assert_eq!(chain, expected);
+
+        let mut buffer = DependencyBuffer::new(5);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &ordering,
+            &mut buffer,
+            None,
+        );
+        assert_eq!(time.buffer.num_dropped_dependencies(), 2);
+        let mut example_buffer = DependencyBuffer::new(5);
+        let example = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut example_buffer,
+            None,
+        );
+        assert_eq!(get_all_paths(time), get_all_paths(example));
+
+        // via the output 3, the dependency on 0 is not implied anymore
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_outputs(&[3]);
+        let _ = PathGenerator::<Partitioner>::from_dependency_graph(
+            &ordering,
+            &mut buffer,
+            None,
+        );
+        assert_eq!(buffer.num_dropped_dependencies(), 1);
+        assert_eq!(buffer.dependents[0], vec![1, 2]);
+
+        // the buffer is reset, also for an empty graph
+        let _ =
+            PathGenerator::<Partitioner>::from_dependency_graph(&[], &mut buffer, None);
+        assert_eq!(buffer.num_dropped_dependencies(), 0);
+        assert!(buffer.dependents.iter().all(Vec::is_empty));
+
+        // with edge specific latencies, nothing is dropped
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_latencies(0, &[(0, 2, 3)]);
+        let _ = PathGenerator::<Partitioner>::from_dependency_graph(
+            &ordering,
+            &mut buffer,
+            None,
+        );
+        assert_eq!(buffer.num_dropped_dependencies(), 0);
+    }
+
+    #[test]
+    fn bounded_paths() {
+        let mut buffer = DependencyBuffer::new(5);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        )
+        .with_max_step_size(Some(1));
+
+        assert_eq!(
+            get_all_paths(time),
+            vec![
+                vec![vec![0], vec![1], vec![3], vec![2]],
+                vec![vec![0], vec![3], vec![2], vec![1]],
+                vec![vec![0], vec![3], vec![1], vec![2]],
+            ]
+        );
+
+        let ordering = [(0..5).map(|i| (i, vec![])).collect()];
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &ordering,
+            &mut buffer,
+            None,
+        )
+        .with_max_step_size(Some(2));
+        assert_eq!(time.min_remaining_steps(), 3);
+        let paths = get_all_paths(time);
+        // the shortest paths are the ordered partitions into blocks of sizes 2, 2 and 1,
+        // i.e., there are 3 * 5!/(2!2!1!) of them
+        assert!(paths.iter().all(|path| path.iter().all(|step| step.len() <= 2)));
+        assert_eq!(paths.iter().filter(|path| path.len() == 3).count(), 90);
+    }
+
+    #[test]
+    fn output_paths() {
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_outputs(&[3]);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        );
+        // 2 only depends on the output 3, so it is measurable right away
+        assert_eq!(
+            get_all_paths(time),
+            vec![
+                vec![vec![0, 2], vec![1]],
+                vec![vec![2], vec![0], vec![1]],
+                vec![vec![0], vec![2, 1]],
+                vec![vec![0], vec![1], vec![2]],
+                vec![vec![0], vec![2], vec![1]],
+            ]
+        );
+    }
+
+    #[test]
+    fn latency_paths() {
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_latencies(1, &[(0, 1, 0)]);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        );
+        assert_eq!(time.min_remaining_steps(), 2);
+
+        // idle steps are also done when something is measurable, as long as there are
+        // qubits waiting
+        assert_eq!(
+            get_all_paths(time),
+            vec![
+                vec![vec![0], vec![1], vec![3], vec![], vec![2]],
+                vec![vec![0], vec![], vec![1, 3], vec![], vec![2]],
+                vec![vec![0], vec![], vec![3], vec![1], vec![2]],
+                vec![vec![0], vec![], vec![3], vec![], vec![1, 2]],
+                vec![vec![0], vec![], vec![3], vec![], vec![2], vec![1]],
+                vec![vec![0], vec![], vec![3], vec![], vec![1], vec![2]],
+                vec![vec![0], vec![], vec![1], vec![3], vec![], vec![2]],
+            ]
+        );
+
+        buffer.set_latencies(0, &[(0, 3, 2)]);
+        let mut time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        );
+        time.focus_inplace(&[0]).unwrap();
+        assert_eq!(time.waiting(), [(3, 3)]);
+        assert_eq!(time.min_remaining_steps(), 3);
+        assert_eq!(
+            get_all_paths(time),
+            vec![
+                vec![vec![1], vec![], vec![3], vec![2]],
+                vec![vec![], vec![1], vec![3], vec![2]],
+                vec![vec![], vec![], vec![1, 3], vec![2]],
+                vec![vec![], vec![], vec![3], vec![1, 2]],
+                vec![vec![], vec![], vec![3], vec![2], vec![1]],
+                vec![vec![], vec![], vec![3], vec![1], vec![2]],
+                vec![vec![], vec![], vec![1], vec![3], vec![2]],
+            ]
+        );
+    }
+
+    #[test]
+    fn independent_latency() {
+        // 2 does not depend on 1, even though 1 is in an earlier layer, so the latency is
+        // ignored
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_latencies(0, &[(1, 2, 3)]);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        );
+        assert!(time.buffer.dependents[1].is_empty());
+        let mut example_buffer = DependencyBuffer::new(5);
+        let example = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut example_buffer,
+            None,
+        );
+        assert_eq!(get_all_paths(time), get_all_paths(example));
+    }
+
+    #[test]
+    fn release_and_deadline_paths() {
+        let mut buffer = DependencyBuffer::new(5);
+        buffer.set_release_times(&[(1, 2)]);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        );
+        assert_eq!(time.max_path_length(), 6);
+        assert_eq!(
+            get_all_paths(time),
+            vec![
+                vec![vec![0], vec![3], vec![2, 1]],
+                vec![vec![0], vec![3], vec![1], vec![2]],
+                vec![vec![0], vec![3], vec![2], vec![1]],
+                vec![vec![0], vec![], vec![3, 1], vec![2]],
+                vec![vec![0], vec![], vec![1], vec![3], vec![2]],
+                vec![vec![0], vec![], vec![3], vec![1, 2]],
+                vec![vec![0], vec![], vec![3], vec![2], vec![1]],
+                vec![vec![0], vec![], vec![3], vec![1], vec![2]],
+            ]
+        );
+
+        buffer.set_release_times(&[]);
+        buffer.set_deadlines(&[(1, 1)]);
+        let time = PathGenerator::<Partitioner>::from_dependency_graph(
+            &example_ordering(),
+            &mut buffer,
+            None,
+        )
+        .with_max_step_size(Some(1));
+        assert_eq!(get_all_paths(time), vec![vec![vec![0], vec![1], vec![3], vec![2]]]);
+    }
+
     #[test]
     #[cfg_attr(miri, ignore)]
     fn max() {
@@ -14,113 +14,577 @@ input.
 // here, then at the according threaded versions in [threaded]; they are all very similar
 // and I don't want to repeat myself in the comments
 
-use std::{cmp, collections::HashMap, time::Duration};
+use std::{
+    cmp,
+    collections::HashMap,
+    mem,
+    sync::{Condvar, Mutex},
+    thread,
+    time::Duration,
+};
 
 use rand::{
-    distributions::{Distribution, Uniform},
     SeedableRng,
+    distributions::{Distribution, Uniform},
 };
 use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    interface::Path,
+    bounds,
+    interface::{Constraints, Limits, Optimality, Path},
     probabilistic::{Accept, AcceptBox},
     scheduler::{
-        space::{Graph, RefSpacialGraph},
-        time::{DependencyBuffer, Partitioner, PathGenerator, RefPartialOrderGraph},
-        tree::{Focus, FocusIterator, Step, Sweep},
         Partition, Scheduler,
+        space::{Graph, Objective, RefSpacialGraph},
+        time::{DependencyBuffer, Partitioner, PathGenerator, RefPartialOrderGraph},
+        tree::{Focus, Step, Sweep},
     },
-    timer::Timer,
+    timer::{StopReason, Timer},
 };
 
 pub type Steps = Vec<Vec<usize>>;
 
+mod portfolio;
 mod threaded;
 
+pub use portfolio::{Portfolio, PortfolioStrategy, portfolio};
+
+/// Periodically get the [Path]s that have been found so far during a [search], e.g., to
+/// write them to a file, so that they are not lost when the process is killed.
+#[derive(Clone, Copy)]
+pub struct Checkpoint<'a> {
+    /// The time between two checkpoints.
+    pub interval: Duration,
+    /// Called with the paths found so far, if they have changed since the last call.
+    pub callback: &'a (dyn Fn(&[Path]) + Sync),
+}
+
+/// Statistics about a [search], e.g., to tune the [AcceptFunc] parameters.
+///
+/// A forward step in the search tree is `explored` and then either `pruned`, because it
+/// cannot lead to a better path than one already found, `rejected` by the probabilistic
+/// [AcceptFunc], or `accepted`.
+///
+/// [AcceptFunc]: crate::probabilistic::AcceptFunc
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SearchStats {
+    /// The number of forward steps (nodes in the search tree) that have been explored.
+    pub explored: u64,
+    /// The number of explored steps that have been skipped because of the bound given by
+    /// the best paths found so far.
+    pub pruned: u64,
+    /// The number of explored steps that have been skipped by the probabilistic
+    /// [AcceptFunc].
+    ///
+    /// [AcceptFunc]: crate::probabilistic::AcceptFunc
+    pub rejected: u64,
+    /// The number of explored steps that have been accepted, i.e., the search went into
+    /// them.
+    pub accepted: u64,
+    /// The number of complete paths that have been found (each one was better than the
+    /// paths found before with the same length).
+    pub solutions: u64,
+    /// The maximum depth, i.e., path length, that the search has reached.
+    pub max_depth: usize,
+    /// Whether the search has been stopped by one of the [Limits], i.e., it did not
+    /// finish.
+    pub timed_out: bool,
+    /// Which of the [Limits] stopped the search, if `timed_out`.
+    #[serde(default)]
+    pub stop_reason: Option<StopReason>,
+    /// Whether the search did not find any path before it was stopped, so that the
+    /// [time-optimal path](get_time_optimal) has been taken instead.
+    #[serde(default)]
+    pub fallback: bool,
+    /// The seed of the random number generator, if the search was probabilistic; it is
+    /// set by the [interface](crate::interface) functions, which draw it if none is
+    /// given, cf. [Metadata::seed](crate::interface::Metadata::seed).
+    #[serde(default)]
+    pub seed: Option<u64>,
+    /// Whether the search has been stopped early because a path reaching the lower
+    /// [bounds] on the time and the cost has been found.
+    #[serde(default)]
+    pub bound_reached: bool,
+    /// If the search was multi-threaded, the statistics of the single threads (the other
+    /// fields are the sums, respectively the maximum depth and whether any thread timed
+    /// out); otherwise empty.
+    pub threads: Vec<SearchStats>,
+}
+
+impl SearchStats {
+    // add the counts of `other` to `self`, without the thread breakdown
+    pub(crate) fn merge(&mut self, other: &SearchStats) {
+        self.explored += other.explored;
+        self.pruned += other.pruned;
+        self.rejected += other.rejected;
+        self.accepted += other.accepted;
+        self.solutions += other.solutions;
+        self.max_depth = cmp::max(self.max_depth, other.max_depth);
+        self.timed_out |= other.timed_out;
+        self.stop_reason = self.stop_reason.or(other.stop_reason);
+        self.fallback |= other.fallback;
+        self.seed = self.seed.or(other.seed);
+        self.bound_reached |= other.bound_reached;
+    }
+}
+
+// the lower bounds on the time and the cost of any path
+#[derive(Clone, Copy, Debug)]
+struct LowerBound {
+    time: usize,
+    cost: usize,
+}
+
+impl LowerBound {
+    // whether a path that reaches both bounds has been found; then it is the only optimal
+    // path (up to equivalent ones) and we can stop
+    fn reached(&self, best_memory: &[usize]) -> bool {
+        best_memory.get(self.time).is_some_and(|best| *best <= self.cost)
+    }
+}
+
 /// The **trivial** time-optimal schedule. Regarding the parameters, cf.
 /// [interface::run](crate::interface::run).
+///
+/// If the [Constraints] bound the step size, the layers are split, preferring the qubits
+/// with the longest chain of dependents (in this case the schedule is not necessarily
+/// time-optimal anymore; the qubits with the earliest deadlines are preferred). If there
+/// are feed-forward latencies or release times, idle steps (empty measurement sets) are
+/// inserted when nothing can be measured. If a deadline is missed, no path is returned.
+///
+/// # Panics
+/// Panics if the `max_step_size` of the `constraints` is `Some(0)` or if the
+/// `constraints` contain nodes that are not in the `spacial_graph`, cf.
+/// [Constraints::validate].
 // PERF: This function can be clearly optimized: currently we are using the full Scheduler
 // with manual scheduling, however, since we know the measurement steps are just the
 // layers in the time_order DAG, we could just use space::Graph directly and feed in the
 // layers to calculate the memory usage.
 pub fn get_time_optimal(
     spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
     time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
 ) -> Vec<Path> {
+    assert_ne!(constraints.max_step_size, Some(0), "max_step_size must be at least 1");
     // more efficient data structure for the input such that referencing it is fairly
     // cheap
-    let mut dependency_buffer = DependencyBuffer::new(spacial_graph.len());
+    let outputs = all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let mut dependency_buffer =
+        new_dependency_buffer(spacial_graph, &outputs, constraints);
     let graph_buffer = spacial_graph;
 
     let mut scheduler = Scheduler::<Vec<usize>>::new(
         PathGenerator::from_dependency_graph(time_ordering, &mut dependency_buffer, None),
-        Graph::new(graph_buffer),
+        Graph::new(graph_buffer).with_weights(weights).with_outputs(&outputs),
     );
 
+    let heights = constraints
+        .max_step_size
+        .map(|_| dependency_heights(spacial_graph.len(), time_ordering));
+    let mut deadlines = vec![usize::MAX; spacial_graph.len()];
+    for (bit, deadline) in constraints.deadlines.iter() {
+        deadlines[*bit] = cmp::min(deadlines[*bit], *deadline);
+    }
+
     let mut path = Vec::new();
     let mut max_memory = 0;
 
     // greedily measuring as much as possible
-    while !scheduler.time().measurable().is_empty() {
-        let measurable_set = scheduler.time().measurable().clone();
+    while !scheduler.time().is_finished() {
+        let mut measurable_set = scheduler.time().measurable().clone();
+        if let (Some(max), Some(heights)) = (constraints.max_step_size, &heights) {
+            if measurable_set.len() > max {
+                measurable_set
+                    .sort_by_key(|bit| (deadlines[*bit], cmp::Reverse(heights[*bit])));
+                measurable_set.truncate(max);
+            }
+        }
         scheduler.focus_inplace(&measurable_set).expect(
             "weird error; there must be something wrong with the dependency graph",
         );
         path.push(measurable_set);
         max_memory = cmp::max(max_memory, scheduler.space().max_memory());
+        if let Some((bit, deadline)) = scheduler.time().missed_deadline() {
+            tracing::warn!("cannot measure {bit} before its deadline {deadline}");
+            return Vec::new();
+        }
     }
 
     vec![Path {
         time: path.len(),
         space: max_memory,
+        volume: scheduler.space().volume(),
+        idle: scheduler.space().idle(),
+        optimality: Optimality::BestFound,
         steps: path,
     }]
 }
 
+// replay the steps to get all the costs
+fn to_path(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    initialized: &[usize],
+    steps: Steps,
+) -> Path {
+    let mut graph = Graph::new(spacial_graph)
+        .with_weights(weights)
+        .with_outputs(outputs)
+        .with_initialized(initialized);
+    for step in steps.iter() {
+        graph.focus_inplace(step).expect("the steps come from the search");
+    }
+    Path {
+        time: steps.len(),
+        space: graph.max_memory(),
+        volume: graph.volume(),
+        idle: graph.idle(),
+        optimality: Optimality::BestFound,
+        steps,
+    }
+}
+
+// if the search finished without skipping anything probabilistically, all found paths
+// are optimal; otherwise, we can only prove it if they reach the lower bound
+pub(crate) fn optimality(cost: usize, lower_bound: usize, complete: bool) -> Optimality {
+    if complete || cost <= lower_bound {
+        Optimality::Optimal
+    } else if lower_bound == 0 {
+        Optimality::BestFound
+    } else {
+        Optimality::Gap { lower_bound }
+    }
+}
+
+pub(crate) fn new_dependency_buffer(
+    spacial_graph: RefSpacialGraph,
+    outputs: &[usize],
+    constraints: &Constraints,
+) -> DependencyBuffer {
+    let mut dependency_buffer = DependencyBuffer::new(spacial_graph.len());
+    dependency_buffer.set_latencies(constraints.latency, &constraints.edge_latencies);
+    dependency_buffer.set_outputs(outputs);
+    dependency_buffer
+        .set_release_times(&release_times(spacial_graph, &constraints.release_times));
+    dependency_buffer.set_deadlines(&constraints.deadlines);
+    dependency_buffer
+}
+
+// the release times of the qubits when taking into account that a qubit is initialized
+// when one of its neighbors is measured, i.e., a qubit cannot be measured before the
+// release times of its neighbors
+pub(crate) fn release_times(
+    spacial_graph: RefSpacialGraph,
+    release_times: &[(usize, usize)],
+) -> Vec<(usize, usize)> {
+    let mut res = vec![0; spacial_graph.len()];
+    for (bit, release_time) in release_times {
+        for bit in spacial_graph[*bit].iter().chain([bit]) {
+            res[*bit] = cmp::max(res[*bit], *release_time);
+        }
+    }
+    res.into_iter()
+        .enumerate()
+        .filter(|(_, release_time)| *release_time > 0)
+        .collect()
+}
+
+// the explicit outputs plus the qubits that are not in the time ordering, since they are
+// never measured
+pub(crate) fn all_outputs(
+    num_bits: usize,
+    time_ordering: RefPartialOrderGraph,
+    outputs: &[usize],
+) -> Vec<usize> {
+    let mut is_output = vec![true; num_bits];
+    for (bit, _) in time_ordering.iter().flatten() {
+        is_output[*bit] = false;
+    }
+    for output in outputs {
+        is_output[*output] = true;
+    }
+    (0..num_bits).filter(|bit| is_output[*bit]).collect()
+}
+
+// the length of the longest chain of dependents for each bit
+fn dependency_heights(
+    num_bits: usize,
+    time_ordering: RefPartialOrderGraph,
+) -> Vec<usize> {
+    let mut heights = vec![0; num_bits];
+    // the layers are topologically sorted, so going backwards, a height is final before
+    // it is propagated to the dependencies
+    for layer in time_ordering.iter().rev() {
+        for (bit, deps) in layer {
+            for dep in deps {
+                heights[*dep] = cmp::max(heights[*dep], heights[*bit] + 1);
+            }
+        }
+    }
+    heights
+}
+
 type MappedPaths = HashMap<usize, (usize, Vec<Vec<usize>>)>;
+// the best paths found so far by all threads, for the checkpoints
+type Incumbent = Mutex<MappedPaths>;
 
 /// Perform a depth-first search through the tree that is (dynamically) spanned through
 /// the possible patterns for time and/or space optimality. Regarding the parameters, cf.
 /// [interface::run](crate::interface::run).
+///
+/// Returns the found [Path]s together with [SearchStats] about the search. If the search
+/// is stopped by the `limits` before any path has been found, the
+/// [time-optimal path](get_time_optimal) is returned instead, so that the result is only
+/// empty if there is no path at all (cf. [SearchStats::fallback]). If a [Checkpoint] is
+/// given, the paths found so far are passed to it periodically (they are not filtered
+/// with respect to optimality).
+#[allow(clippy::too_many_arguments)]
 pub fn search(
     spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
     time_ordering: RefPartialOrderGraph,
-    timeout: Option<Duration>,
+    limits: &Limits,
     nthreads: u16,
     probabilistic: Option<(AcceptBox, Option<u64>)>,
     task_bound: i64,
-) -> Vec<Path> {
-    let num_bits = spacial_graph.len();
-    let mut dependency_buffer = DependencyBuffer::new(num_bits);
+    constraints: &Constraints,
+    objective: Objective,
+    checkpoint: Option<Checkpoint>,
+) -> (Vec<Path>, SearchStats) {
+    search_from(
+        spacial_graph,
+        weights,
+        outputs,
+        time_ordering,
+        limits,
+        nthreads,
+        probabilistic,
+        task_bound,
+        constraints,
+        objective,
+        &[],
+        checkpoint,
+    )
+}
+
+/// Like [search], but the `initialized` nodes are already in memory at the beginning,
+/// e.g., because they are the frontier of a partially executed schedule (cf.
+/// [window](crate::window)). The costs of the returned [Path]s include them.
+#[allow(clippy::too_many_arguments)]
+pub fn search_from(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    limits: &Limits,
+    nthreads: u16,
+    probabilistic: Option<(AcceptBox, Option<u64>)>,
+    task_bound: i64,
+    constraints: &Constraints,
+    objective: Objective,
+    initialized: &[usize],
+    checkpoint: Option<Checkpoint>,
+) -> (Vec<Path>, SearchStats) {
+    let outputs = all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let mut dependency_buffer =
+        new_dependency_buffer(spacial_graph, &outputs, constraints);
     // let graph_buffer = GraphBuffer::from_sparse(spacial_graph);
     let graph_buffer = spacial_graph;
     let scheduler = Scheduler::<Partitioner>::new(
-        PathGenerator::from_dependency_graph(time_ordering, &mut dependency_buffer, None),
-        Graph::new(graph_buffer),
-    );
+        PathGenerator::from_dependency_graph(time_ordering, &mut dependency_buffer, None)
+            .with_max_step_size(constraints.max_step_size),
+        Graph::new(graph_buffer)
+            .with_weights(weights)
+            .with_outputs(&outputs)
+            .with_initialized(initialized),
+    )
+    .with_objective(objective);
+    // the best memory arrays below are indexed by the path length
+    let max_path_length = scheduler.time().max_path_length();
+    let bounds =
+        bounds::bounds(spacial_graph, weights, &outputs, time_ordering, constraints);
+    let lower_bound = LowerBound {
+        time: bounds.time,
+        // the cost bound of the root is a lower bound, too
+        cost: cmp::max(bounds.cost(objective), scheduler.cost_bound()),
+    };
+    let is_probabilistic = probabilistic.is_some();
 
-    let mut timer = Timer::new();
-    if let Some(timeout) = timeout {
-        timer.start(timeout);
-    }
+    let timer = limits.start_timer();
+    let incumbent = checkpoint.map(|_| Mutex::new(HashMap::new()));
+    let done = (Mutex::new(false), Condvar::new());
 
-    let results = if nthreads < 2 {
-        let (result, _) = if let Some(accept_func) = probabilistic {
-            do_probabilistic_search(scheduler.into_iter(), num_bits, &timer, accept_func)
+    let (results, mut stats) = thread::scope(|scope| {
+        if let (Some(checkpoint), Some(incumbent)) = (checkpoint, &incumbent) {
+            let (done, outputs) = (&done, &outputs);
+            scope.spawn(move || {
+                checkpoints(checkpoint, incumbent, done, |results| {
+                    to_paths(
+                        results,
+                        max_path_length,
+                        spacial_graph,
+                        weights,
+                        outputs,
+                        initialized,
+                        objective,
+                        lower_bound.cost,
+                        false,
+                    )
+                })
+            });
+        }
+
+        let res = if nthreads < 2 {
+            let (result, _, stats) = if let Some(accept_func) = probabilistic {
+                do_probabilistic_search(
+                    scheduler.into_iter(),
+                    max_path_length,
+                    lower_bound,
+                    (&timer, incumbent.as_ref()),
+                    accept_func,
+                )
+            } else {
+                do_search(
+                    scheduler.into_iter(),
+                    max_path_length,
+                    lower_bound,
+                    (&timer, incumbent.as_ref()),
+                )
+            };
+            (result, stats)
         } else {
-            do_search(scheduler.into_iter(), num_bits, &timer)
+            threaded::search(
+                nthreads,
+                max_path_length,
+                lower_bound,
+                scheduler,
+                task_bound,
+                probabilistic,
+                (&timer, incumbent.as_ref()),
+            )
         };
-        result
-    } else {
-        threaded::search(nthreads, num_bits, scheduler, task_bound, probabilistic, &timer)
-    };
 
+        *done.0.lock().expect("failed to lock done") = true;
+        done.1.notify_all();
+        res
+    });
+
+    let complete = !stats.timed_out && !is_probabilistic;
+    let mut paths = to_paths(
+        results,
+        max_path_length,
+        spacial_graph,
+        weights,
+        &outputs,
+        initialized,
+        objective,
+        lower_bound.cost,
+        complete,
+    );
+    finish(
+        &mut paths,
+        &mut stats,
+        &timer,
+        spacial_graph,
+        weights,
+        &outputs,
+        initialized,
+        time_ordering,
+        constraints,
+        objective,
+        lower_bound.cost,
+    );
+
+    (paths, stats)
+}
+
+// record why the search stopped, and fall back to the time-optimal path if it stopped
+// before finding any path
+#[allow(clippy::too_many_arguments)]
+fn finish(
+    paths: &mut Vec<Path>,
+    stats: &mut SearchStats,
+    timer: &Timer,
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    initialized: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    objective: Objective,
+    lower_bound: usize,
+) {
+    if !stats.timed_out {
+        return;
+    }
+    stats.stop_reason = timer.stop_reason();
+    if paths.is_empty() {
+        tracing::info!("no path found before stopping; taking the time-optimal path");
+        stats.fallback = true;
+        *paths =
+            get_time_optimal(spacial_graph, weights, outputs, time_ordering, constraints);
+        if !initialized.is_empty() {
+            // the time-optimal path doesn't know about the initialized nodes
+            for path in paths.iter_mut() {
+                let steps = mem::take(&mut path.steps);
+                *path = to_path(spacial_graph, weights, outputs, initialized, steps);
+            }
+        }
+        for path in paths.iter_mut() {
+            path.optimality = optimality(path.cost(objective), lower_bound, false);
+        }
+    }
+}
+
+// pass the `incumbent` paths to the `checkpoint` periodically, until we are `done`
+fn checkpoints(
+    checkpoint: Checkpoint,
+    incumbent: &Incumbent,
+    (done, cvar): &(Mutex<bool>, Condvar),
+    to_paths: impl Fn(MappedPaths) -> Vec<Path>,
+) {
+    let mut last = HashMap::new();
+    let mut finished = done.lock().expect("failed to lock done");
+    loop {
+        finished = cvar
+            .wait_timeout_while(finished, checkpoint.interval, |finished| !*finished)
+            .expect("failed to lock done")
+            .0;
+        if *finished {
+            return;
+        }
+        let results = incumbent.lock().expect("failed to lock incumbent").clone();
+        if results != last {
+            (checkpoint.callback)(&to_paths(results.clone()));
+            last = results;
+        }
+    }
+}
+
+// filter the results to the Pareto front and replay them to get the paths
+#[allow(clippy::too_many_arguments)]
+fn to_paths(
+    results: MappedPaths,
+    max_path_length: usize,
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    initialized: &[usize],
+    objective: Objective,
+    lower_bound: usize,
+    complete: bool,
+) -> Vec<Path> {
     // we don't want all results: let's say we have the results A and B, where time(A) <
-    // time(B) and also space(A) < space(B), then we can discard B
+    // time(B) and also cost(A) < cost(B), then we can discard B
     let mut filtered_results = HashMap::new();
-    // in the following array, the time cost is the index and the memory cost is the value
-    let mut best_memory_per_time_cost = vec![usize::MAX; num_bits + 1];
+    // in the following array, the time cost is the index and the (memory) cost is the
+    // value
+    let mut best_memory_per_time_cost = vec![usize::MAX; max_path_length + 1];
     for i in 0..best_memory_per_time_cost.len() {
         if let Some((mem, _)) = results.get(&i) {
             let m = best_memory_per_time_cost[i];
@@ -135,52 +599,74 @@ pub fn search(
 
     let mut sorted = filtered_results
         .into_iter()
-        .map(|(time, (space, steps))| Path { time, space, steps })
+        .map(|(_, (_, steps))| {
+            to_path(spacial_graph, weights, outputs, initialized, steps)
+        })
         .collect::<Vec<_>>();
     sorted.sort_by_key(|Path { time, .. }| *time);
 
+    for path in sorted.iter_mut() {
+        path.optimality = optimality(path.cost(objective), lower_bound, complete);
+    }
     sorted
 }
 
 // cf. crate::scheduler doc examples
 fn do_search(
     mut scheduler: Sweep<Scheduler<Partition<Vec<usize>>>>,
-    num_bits: usize,
-    timer: &Timer,
-) -> (MappedPaths, Vec<usize>) {
+    max_path_length: usize,
+    lower_bound: LowerBound,
+    (timer, incumbent): (&Timer, Option<&Incumbent>),
+) -> (MappedPaths, Vec<usize>, SearchStats) {
     let mut results = HashMap::new();
     let mut current_path = Vec::new();
     // in the following array, the time cost is the index and the memory cost is the value
-    let mut best_memory = vec![usize::MAX; num_bits + 1];
+    let mut best_memory = vec![usize::MAX; max_path_length + 1];
+    let mut stats = SearchStats::default();
     while let Some(step) = scheduler.next() {
         match step {
             Step::Forward(measure) => {
-                if forward(measure, &mut scheduler, &best_memory, &mut current_path) {
+                if forward(
+                    measure,
+                    &mut scheduler,
+                    &best_memory,
+                    lower_bound.cost,
+                    &mut current_path,
+                    &mut stats,
+                ) {
                     break;
                 }
+                timer.explored(1);
             },
             Step::Backward(leaf) => {
-                backward(leaf, &mut current_path, &mut best_memory, &mut results);
+                backward(
+                    leaf,
+                    &mut current_path,
+                    &mut best_memory,
+                    &mut results,
+                    &mut stats,
+                    (timer, incumbent),
+                );
+                if lower_bound.reached(&best_memory) {
+                    stats.bound_reached = true;
+                    break;
+                }
             },
         }
         if timer.finished() {
-            tracing::info!("timer: timeout");
+            tracing::info!("timer: {:?}", timer.stop_reason());
+            stats.timed_out = timer.timed_out();
             break;
         }
     }
 
-    (results, best_memory)
+    (results, best_memory, stats)
 }
 
+// `time` is already focused on the next step, which is not yet in `current_path`
 #[inline]
 fn minimum_path_length(time: &PathGenerator<Partitioner>, current_path: &Steps) -> usize {
-    if time.at_leaf().is_some() {
-        current_path.len() + 1
-    } else if time.has_unmeasureable() {
-        current_path.len() + 3
-    } else {
-        current_path.len() + 2
-    }
+    current_path.len() + 1 + time.min_remaining_steps()
 }
 
 #[inline]
@@ -188,34 +674,55 @@ fn forward(
     measure: Vec<usize>,
     scheduler: &mut Sweep<Scheduler<Partitioner>>,
     best_memory: &[usize],
+    cost_bound: usize,
     current_path: &mut Steps,
+    stats: &mut SearchStats,
 ) -> bool {
+    stats.explored += 1;
     let current = scheduler.current();
-    let space = current.space();
-    if space.max_memory()
+    if cmp::max(current.cost_bound(), cost_bound)
         >= best_memory[minimum_path_length(current.time(), current_path)]
     {
+        stats.pruned += 1;
         if scheduler.skip_current().is_err() {
             return true;
         }
     } else {
-        current_path.push(measure);
+        enter(measure, current_path, stats);
     }
     false
 }
 
+#[inline]
+fn enter(measure: Vec<usize>, current_path: &mut Steps, stats: &mut SearchStats) {
+    current_path.push(measure);
+    stats.accepted += 1;
+    stats.max_depth = cmp::max(stats.max_depth, current_path.len());
+}
+
 #[inline]
 fn backward(
     leaf: Option<usize>,
     current_path: &mut Steps,
     best_memory: &mut [usize],
     results: &mut MappedPaths,
+    stats: &mut SearchStats,
+    (timer, incumbent): (&Timer, Option<&Incumbent>),
 ) {
     if let Some(mem) = leaf {
+        stats.solutions += 1;
+        timer.improved();
         best_memory[current_path.len()] = mem;
         for m in best_memory[current_path.len() + 1..].iter_mut() {
             *m = cmp::min(*m, mem);
         }
+        if let Some(incumbent) = incumbent {
+            // another thread might have found a better one in the meantime
+            let mut incumbent = incumbent.lock().expect("failed to lock incumbent");
+            if incumbent.get(&current_path.len()).is_none_or(|(best, _)| mem < *best) {
+                incumbent.insert(current_path.len(), (mem, current_path.clone()));
+            }
+        }
         results.insert(current_path.len(), (mem, current_path.clone()));
     }
     current_path.pop();
@@ -225,13 +732,15 @@ fn backward(
 // whether we do this step/node or skip in in our possible-paths-tree
 fn do_probabilistic_search(
     mut scheduler: Sweep<Scheduler<Partition<Vec<usize>>>>,
-    num_bits: usize,
-    timer: &Timer,
+    max_path_length: usize,
+    lower_bound: LowerBound,
+    (timer, incumbent): (&Timer, Option<&Incumbent>),
     (accept_func, seed): (AcceptBox, Option<u64>),
-) -> (MappedPaths, Vec<usize>) {
+) -> (MappedPaths, Vec<usize>, SearchStats) {
     let mut results = HashMap::new();
     let mut current_path = Vec::new();
-    let mut best_memory = vec![usize::MAX; num_bits + 1];
+    let mut best_memory = vec![usize::MAX; max_path_length + 1];
+    let mut stats = SearchStats::default();
 
     let mut rng = if let Some(seed) = seed {
         Pcg64::seed_from_u64(seed)
@@ -241,9 +750,9 @@ fn do_probabilistic_search(
     let dist = Uniform::new(0., 1.);
 
     loop {
-        let space = scheduler.current().space();
-        let last_cur_mem = space.current_memory();
-        let last_max_mem = space.max_memory();
+        let current = scheduler.current();
+        let last_cur_mem = current_cost(current);
+        let last_max_mem = current.cost_bound();
         if let Some(step) = scheduler.next() {
             match step {
                 Step::Forward(measure) => {
@@ -251,30 +760,45 @@ fn do_probabilistic_search(
                         measure,
                         &mut scheduler,
                         &best_memory,
+                        lower_bound.cost,
                         &mut current_path,
                         last_cur_mem,
                         last_max_mem,
                         &mut rng,
                         &dist,
                         &accept_func,
+                        &mut stats,
                     ) {
                         break;
                     }
+                    timer.explored(1);
                 },
                 Step::Backward(leaf) => {
-                    backward(leaf, &mut current_path, &mut best_memory, &mut results);
+                    backward(
+                        leaf,
+                        &mut current_path,
+                        &mut best_memory,
+                        &mut results,
+                        &mut stats,
+                        (timer, incumbent),
+                    );
+                    if lower_bound.reached(&best_memory) {
+                        stats.bound_reached = true;
+                        break;
+                    }
                 },
             }
         } else {
             break;
         }
         if timer.finished() {
-            tracing::info!("timer: timeout");
+            tracing::info!("timer: {:?}", timer.stop_reason());
+            stats.timed_out = timer.timed_out();
             break;
         }
     }
 
-    (results, best_memory)
+    (results, best_memory, stats)
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -283,17 +807,20 @@ fn probabilistic_forward(
     measure: Vec<usize>,
     scheduler: &mut Sweep<Scheduler<Partitioner>>,
     best_memory: &[usize],
+    cost_bound: usize,
     current_path: &mut Steps,
     last_cur_mem: usize,
     last_max_mem: usize,
     rng: &mut impl rand::Rng,
     dist: &Uniform<f64>,
     accept_func: &Accept,
+    stats: &mut SearchStats,
 ) -> bool {
+    stats.explored += 1;
     let current = scheduler.current();
-    let space = current.space();
     let bound_best_mem = best_memory[minimum_path_length(current.time(), current_path)];
-    if space.max_memory() >= bound_best_mem {
+    if cmp::max(current.cost_bound(), cost_bound) >= bound_best_mem {
+        stats.pruned += 1;
         if scheduler.skip_current().is_err() {
             return true;
         }
@@ -304,15 +831,318 @@ fn probabilistic_forward(
             *best_memory.last().unwrap() as f64,
             last_max_mem as f64,
             last_cur_mem as f64,
-            space.current_memory() as f64,
+            current_cost(current) as f64,
             current.time().num_remaining_nodes() as f64,
-            space.nodes().len() as f64,
+            current.space().nodes().len() as f64,
         );
         if accept >= 1. || dist.sample(rng) < accept {
-            current_path.push(measure);
-        } else if scheduler.skip_current().is_err() {
-            return true;
+            enter(measure, current_path, stats);
+        } else {
+            stats.rejected += 1;
+            if scheduler.skip_current().is_err() {
+                return true;
+            }
         }
     }
     false
 }
+
+// the "current memory" for the accept function, cf. [Accept]
+#[inline]
+pub(crate) fn current_cost<T>(scheduler: &Scheduler<T>) -> usize {
+    match scheduler.objective() {
+        Objective::Space => scheduler.space().current_memory(),
+        _ => scheduler.cost_bound(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use super::*;
+    use crate::probabilistic::{AcceptFunc, AcceptFuncConfig, HeavysideParameters};
+
+    #[test]
+    fn stats() {
+        // 0 - 1 - 2 - 3 - 4, with a chain of dependencies 0 -> 2 -> 4
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        for nthreads in [1, 3] {
+            let (paths, stats) = search(
+                &graph,
+                None,
+                &[],
+                &ordering,
+                &Limits::default(),
+                nthreads,
+                None,
+                100,
+                &Constraints::default(),
+                Objective::Space,
+                None,
+            );
+            assert!(!paths.is_empty());
+            assert!(paths.iter().all(|path| path.optimality == Optimality::Optimal));
+            assert_eq!(stats.explored, stats.pruned + stats.rejected + stats.accepted);
+            assert_eq!(stats.rejected, 0);
+            assert!(stats.solutions >= paths.len() as u64);
+            assert!(!stats.timed_out);
+            assert!(stats.max_depth >= paths.last().unwrap().time);
+            if nthreads == 1 {
+                assert!(stats.threads.is_empty());
+            } else {
+                let explored = stats.threads.iter().map(|s| s.explored).sum::<u64>();
+                assert_eq!(stats.explored, explored);
+            }
+        }
+    }
+
+    #[test]
+    fn old_stats() {
+        // the stats as written before the newer fields existed
+        let stats: SearchStats = serde_json::from_str(
+            r#"{"explored": 3, "pruned": 1, "rejected": 0, "accepted": 2,
+                "solutions": 1, "max_depth": 2, "timed_out": false, "threads": []}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            SearchStats {
+                explored: 3,
+                pruned: 1,
+                accepted: 2,
+                solutions: 1,
+                max_depth: 2,
+                ..Default::default()
+            }
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        let (paths, stats) = search(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Limits {
+                node_budget: Some(0),
+                ..Default::default()
+            },
+            1,
+            None,
+            100,
+            &Constraints::default(),
+            Objective::Space,
+            None,
+        );
+        assert_eq!(stats.explored, 1);
+        assert!(stats.timed_out);
+        assert_eq!(stats.stop_reason, Some(StopReason::NodeBudget));
+        // stopped before the first path was found
+        assert!(stats.fallback);
+        let time_optimal =
+            get_time_optimal(&graph, None, &[], &ordering, &Constraints::default());
+        assert_eq!(paths.len(), 1);
+        assert_eq!(paths[0].steps, time_optimal[0].steps);
+    }
+
+    #[test]
+    fn waiting() {
+        // 0 - 1   2 - 3, where 1 and 3 depend on 2, which is released in step 2;
+        // measuring 0 right away would keep 1 in memory while 2 and 3 are in memory
+        let graph = vec![vec![1], vec![0], vec![3], vec![2]];
+        let ordering =
+            vec![vec![(0, vec![]), (2, vec![])], vec![(1, vec![2]), (3, vec![2])]];
+        let constraints = Constraints {
+            release_times: vec![(2, 2)],
+            ..Default::default()
+        };
+        let time_optimal = get_time_optimal(&graph, None, &[], &ordering, &constraints);
+        assert_eq!(time_optimal[0].space, 3);
+        let (paths, _) = search(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Limits::default(),
+            1,
+            None,
+            100,
+            &constraints,
+            Objective::Space,
+            None,
+        );
+        let best = paths.last().unwrap();
+        assert_eq!(best.space, 2);
+        assert!(best.steps[..2].iter().all(Vec::is_empty));
+        assert!(paths.iter().all(|path| path.optimality == Optimality::Optimal));
+    }
+
+    #[test]
+    fn checkpoint() {
+        let graph = vec![vec![1], vec![0]];
+        let incumbent: Incumbent =
+            Mutex::new(HashMap::from([(1, (2, vec![vec![0, 1]]))]));
+        let done = (Mutex::new(false), Condvar::new());
+        let written = Mutex::new(Vec::new());
+        let callback = |paths: &[Path]| {
+            let mut steps: Vec<_> = paths.iter().map(|path| path.steps.clone()).collect();
+            steps.sort();
+            written.lock().unwrap().push(steps);
+        };
+        let checkpoint = Checkpoint {
+            interval: Duration::from_millis(1),
+            callback: &callback,
+        };
+        // generous, since the checkpoints are written by another thread
+        let wait_for = |count: usize| {
+            let start = Instant::now();
+            while written.lock().unwrap().len() < count {
+                assert!(start.elapsed() < Duration::from_secs(10));
+                thread::sleep(Duration::from_millis(1));
+            }
+        };
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                checkpoints(checkpoint, &incumbent, &done, |results| {
+                    results
+                        .into_values()
+                        .map(|(_, steps)| to_path(&graph, None, &[], &[], steps))
+                        .collect()
+                })
+            });
+            wait_for(1);
+            // unchanged results are not written again
+            thread::sleep(Duration::from_millis(20));
+            assert_eq!(written.lock().unwrap().len(), 1);
+            incumbent.lock().unwrap().insert(2, (1, vec![vec![0], vec![1]]));
+            wait_for(2);
+            *done.0.lock().unwrap() = true;
+            done.1.notify_all();
+        });
+        assert_eq!(
+            written.into_inner().unwrap(),
+            vec![vec![vec![vec![0, 1]]], vec![vec![vec![0], vec![1]], vec![vec![0, 1]]]]
+        );
+    }
+
+    #[test]
+    fn portfolio_search() {
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        let run = |strategies| {
+            portfolio(
+                &graph,
+                None,
+                &[],
+                &ordering,
+                &Limits::default(),
+                strategies,
+                &Constraints::default(),
+                Objective::Space,
+            )
+        };
+        let costs = |paths: &[Path]| {
+            paths.iter().map(|path| (path.time, path.space)).collect::<Vec<_>>()
+        };
+        let (exact, _) = search(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Limits::default(),
+            1,
+            None,
+            100,
+            &Constraints::default(),
+            Objective::Space,
+            None,
+        );
+
+        let (paths, stats) = run(vec![
+            PortfolioStrategy::Exact,
+            PortfolioStrategy::Probabilistic {
+                accept_func: AcceptFunc::BuiltinHeavyside,
+                seed: Some(0),
+            },
+            PortfolioStrategy::Greedy { seed: Some(0) },
+        ]);
+        assert_eq!(costs(&paths), costs(&exact));
+        assert!(paths.iter().all(|path| path.optimality == Optimality::Optimal));
+        assert_eq!(stats.threads.len(), 3);
+        assert!(!stats.timed_out);
+
+        let (paths, stats) = run(vec![PortfolioStrategy::Greedy { seed: Some(0) }]);
+        assert!(!paths.is_empty());
+        // the greedy paths cannot be better than the optimal ones
+        for path in paths {
+            let optimal = exact.iter().rev().find(|p| p.time <= path.time).unwrap();
+            assert!(path.space >= optimal.space);
+        }
+        assert_eq!(stats.threads.len(), 1);
+    }
+
+    #[test]
+    fn portfolio_strategies() {
+        let portfolio = Portfolio {
+            exact: true,
+            probabilistic: 3,
+            greedy: 1,
+        };
+        let configs = |accept_func: fn() -> AcceptFunc| {
+            portfolio
+                .strategies(accept_func, 7)
+                .into_iter()
+                .filter_map(|strategy| match strategy {
+                    PortfolioStrategy::Probabilistic { accept_func, seed } => {
+                        Some((accept_func.config(), seed))
+                    },
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+        };
+        let param = |lin_num_total_nodes_exp| {
+            Some(AcceptFuncConfig::ParametrizedHeavyside(HeavysideParameters {
+                lin_num_total_nodes_exp,
+                ..Default::default()
+            }))
+        };
+        assert_eq!(
+            configs(|| AcceptFunc::BuiltinHeavyside),
+            vec![
+                (Some(AcceptFuncConfig::BuiltinHeavyside), Some(7)),
+                (param(1), Some(8)),
+                (param(0), Some(9)),
+            ]
+        );
+        // custom accept functions cannot be varied, only the seeds
+        assert_eq!(
+            configs(|| AcceptFunc::Custom(Box::new(|_, _, _, _, _, _, _| 1.))),
+            vec![(None, Some(7)), (None, Some(8)), (None, Some(9))]
+        );
+    }
+
+    #[test]
+    fn optimality_status() {
+        assert_eq!(optimality(5, 3, true), Optimality::Optimal);
+        assert_eq!(optimality(3, 3, false), Optimality::Optimal);
+        assert_eq!(optimality(5, 3, false), Optimality::Gap { lower_bound: 3 });
+        assert_eq!(optimality(5, 0, false), Optimality::BestFound);
+    }
+}
@@ -0,0 +1,376 @@
+// A portfolio of search strategies, each running in its own thread. Like in [threaded],
+// the strategies share the `best_memory`, so that they prune with each others' results.
+// If an exact search finishes, the results are optimal and the other strategies are
+// stopped.
+
+use std::{
+    cmp,
+    collections::HashMap,
+    sync::{
+        Mutex,
+        atomic::{AtomicUsize, Ordering},
+    },
+    thread,
+};
+
+use rand::{Rng, SeedableRng};
+use rand_pcg::Pcg64;
+use serde::{Deserialize, Serialize};
+
+use super::{LowerBound, MappedPaths, SearchStats, threaded};
+use crate::{
+    bounds,
+    interface::{Constraints, Limits, Path},
+    probabilistic::{AcceptFunc, HeavysideParameters},
+    scheduler::{
+        Scheduler,
+        space::{Graph, Objective, RefSpacialGraph},
+        time::{Partitioner, PathGenerator, RefPartialOrderGraph},
+        tree::FocusIterator,
+    },
+    timer::Timer,
+};
+
+/// The maximum number of dives of [PortfolioStrategy::Greedy], if it is the only kind of
+/// strategy in the portfolio (otherwise it dives until the other strategies are done).
+pub const GREEDY_DIVES: usize = 1000;
+/// [PortfolioStrategy::Greedy] considers at most that many children per node (the ones
+/// that measure the most qubits).
+pub const GREEDY_CHILDREN: usize = 256;
+/// After the first dive, [PortfolioStrategy::Greedy] chooses randomly among that many of
+/// the best children.
+pub const GREEDY_CANDIDATES: usize = 3;
+
+/// A strategy in a [portfolio] search.
+pub enum PortfolioStrategy {
+    /// The deterministic search.
+    Exact,
+    /// The probabilistic search with the given accept function and an optional seed.
+    Probabilistic {
+        accept_func: AcceptFunc,
+        seed: Option<u64>,
+    },
+    /// Greedy dives through the search tree, choosing the child with the lowest cost
+    /// bound, followed by randomized dives choosing among the best
+    /// [GREEDY_CANDIDATES] children (a simple local search around the greedy path), with
+    /// an optional seed.
+    Greedy { seed: Option<u64> },
+}
+
+/// The composition of a [portfolio], i.e., how many strategies of each kind are run, cf.
+/// [Mode::Portfolio](crate::interface::Mode::Portfolio).
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Portfolio {
+    /// Whether to run a [PortfolioStrategy::Exact] search.
+    pub exact: bool,
+    /// The number of [PortfolioStrategy::Probabilistic] searches. They use different
+    /// parameters, cf. [strategies](Self::strategies).
+    pub probabilistic: u16,
+    /// The number of [PortfolioStrategy::Greedy] searches.
+    pub greedy: u16,
+}
+
+impl Default for Portfolio {
+    /// One strategy of each kind.
+    fn default() -> Self {
+        Self {
+            exact: true,
+            probabilistic: 1,
+            greedy: 1,
+        }
+    }
+}
+
+impl Portfolio {
+    /// The number of strategies, i.e., the number of threads of the [portfolio].
+    pub fn len(&self) -> u16 {
+        u16::from(self.exact) + self.probabilistic + self.greedy
+    }
+
+    /// Whether there are no strategies.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// The strategies of the portfolio. The probabilistic searches get their accept
+    /// function from `accept_func`: the first one uses it as it is, and the others use
+    /// increasingly restrictive versions of it, i.e., with
+    /// [lin_num_total_nodes_exp](HeavysideParameters::lin_num_total_nodes_exp) lowered
+    /// by one per search (unless it is a [custom](AcceptFunc::Custom) one, which cannot
+    /// be changed). The strategies with randomness are seeded with consecutive seeds,
+    /// starting at `seed`.
+    pub fn strategies(
+        &self,
+        mut accept_func: impl FnMut() -> AcceptFunc,
+        seed: u64,
+    ) -> Vec<PortfolioStrategy> {
+        let mut seeds = (0..).map(|i| Some(seed.wrapping_add(i)));
+        let mut strategies = Vec::with_capacity(self.len().into());
+        if self.exact {
+            strategies.push(PortfolioStrategy::Exact);
+        }
+        for slot in 0..self.probabilistic {
+            let func = accept_func();
+            let func = match func.config() {
+                Some(config) if slot > 0 => {
+                    let param = config.parameters();
+                    AcceptFunc::ParametrizedHeavyside {
+                        param: HeavysideParameters {
+                            lin_num_total_nodes_exp: param.lin_num_total_nodes_exp
+                                - i32::from(slot),
+                            ..param
+                        },
+                    }
+                },
+                _ => func,
+            };
+            strategies.push(PortfolioStrategy::Probabilistic {
+                accept_func: func,
+                seed: seeds.next().expect("infinite"),
+            });
+        }
+        for _ in 0..self.greedy {
+            strategies.push(PortfolioStrategy::Greedy {
+                seed: seeds.next().expect("infinite"),
+            });
+        }
+        strategies
+    }
+}
+
+/// Run the `strategies` in parallel, each in its own thread, sharing the best paths
+/// found so far to prune the searches, and merge the results into one Pareto front.
+/// Regarding the other parameters, cf. [interface::run](crate::interface::run).
+///
+/// If one [PortfolioStrategy::Exact] search finishes, the found paths are optimal and the
+/// other strategies are stopped. The [SearchStats] contain the statistics of the single
+/// strategies in `threads`, in the same order as the `strategies`. As in
+/// [search](super::search), the time-optimal path is returned if the `limits` stop the
+/// strategies before any path has been found.
+#[allow(clippy::too_many_arguments)]
+pub fn portfolio(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    limits: &Limits,
+    strategies: Vec<PortfolioStrategy>,
+    constraints: &Constraints,
+    objective: Objective,
+) -> (Vec<Path>, SearchStats) {
+    let outputs = super::all_outputs(spacial_graph.len(), time_ordering, outputs);
+    let mut dependency_buffer =
+        super::new_dependency_buffer(spacial_graph, &outputs, constraints);
+    let scheduler = Scheduler::<Partitioner>::new(
+        PathGenerator::from_dependency_graph(time_ordering, &mut dependency_buffer, None)
+            .with_max_step_size(constraints.max_step_size),
+        Graph::new(spacial_graph).with_weights(weights).with_outputs(&outputs),
+    )
+    .with_objective(objective);
+    let max_path_length = scheduler.time().max_path_length();
+    let bounds =
+        bounds::bounds(spacial_graph, weights, &outputs, time_ordering, constraints);
+    let lower_bound = LowerBound {
+        time: bounds.time,
+        cost: cmp::max(bounds.cost(objective), scheduler.cost_bound()),
+    };
+
+    let timer = limits.start_timer();
+
+    let best_memory = Mutex::new(vec![usize::MAX; max_path_length + 1]);
+    let results: Mutex<MappedPaths> = Mutex::new(HashMap::new());
+    let num_searches = strategies
+        .iter()
+        .filter(|strategy| !matches!(strategy, PortfolioStrategy::Greedy { .. }))
+        .count();
+    // the number of running non-greedy strategies
+    let running = AtomicUsize::new(num_searches);
+
+    // the stats of the strategies, and whether they are complete exact searches
+    let strategy_stats: Vec<(SearchStats, bool)> = thread::scope(|scope| {
+        let handles = strategies
+            .into_iter()
+            .map(|strategy| {
+                let (scheduler, best_memory, results, timer, running) =
+                    (scheduler.clone(), &best_memory, &results, &timer, &running);
+                scope.spawn(move || {
+                    let mut complete = false;
+                    let (new_results, this_best_mem, stats) = match strategy {
+                        PortfolioStrategy::Exact => {
+                            let res = threaded::do_search(
+                                scheduler.into_iter(),
+                                None,
+                                best_memory,
+                                lower_bound,
+                                (timer, None),
+                            );
+                            if !res.2.timed_out {
+                                // the results are optimal, so the others can stop
+                                complete = true;
+                                timer.stop();
+                            }
+                            running.fetch_sub(1, Ordering::Relaxed);
+                            res
+                        },
+                        PortfolioStrategy::Probabilistic { accept_func, seed } => {
+                            let accept_func = accept_func.get_accept_func();
+                            let seed = seed.unwrap_or_else(|| rand::thread_rng().gen());
+                            let res = threaded::do_probabilistic_search(
+                                scheduler.into_iter(),
+                                None,
+                                best_memory,
+                                lower_bound,
+                                (timer, None),
+                                (&*accept_func, seed),
+                            );
+                            running.fetch_sub(1, Ordering::Relaxed);
+                            res
+                        },
+                        PortfolioStrategy::Greedy { seed } => greedy(
+                            scheduler,
+                            best_memory,
+                            lower_bound,
+                            timer,
+                            seed,
+                            (num_searches > 0).then_some(running),
+                        ),
+                    };
+                    threaded::merge_results(
+                        best_memory,
+                        results,
+                        new_results,
+                        this_best_mem,
+                    );
+                    (stats, complete)
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().expect("a portfolio thread panicked"))
+            .collect()
+    });
+
+    let complete = strategy_stats.iter().any(|(_, complete)| *complete);
+    let mut stats = SearchStats::default();
+    for (this_stats, _) in strategy_stats {
+        stats.merge(&this_stats);
+        stats.threads.push(this_stats);
+    }
+    // a limit may be hit while the exact search is finishing, but its paths are optimal
+    stats.timed_out &= !complete;
+
+    let mut paths = super::to_paths(
+        results.into_inner().expect("failed to lock results"),
+        max_path_length,
+        spacial_graph,
+        weights,
+        &outputs,
+        &[],
+        objective,
+        lower_bound.cost,
+        complete,
+    );
+    super::finish(
+        &mut paths,
+        &mut stats,
+        &timer,
+        spacial_graph,
+        weights,
+        &outputs,
+        &[],
+        time_ordering,
+        constraints,
+        objective,
+        lower_bound.cost,
+    );
+    (paths, stats)
+}
+
+// greedy dives, cf. [PortfolioStrategy::Greedy]; if `running` is given, we dive until it
+// is zero, otherwise at most GREEDY_DIVES times
+fn greedy(
+    scheduler: Scheduler<Partitioner>,
+    best_memory: &Mutex<Vec<usize>>,
+    lower_bound: LowerBound,
+    timer: &Timer,
+    seed: Option<u64>,
+    running: Option<&AtomicUsize>,
+) -> (MappedPaths, Vec<usize>, SearchStats) {
+    let mut results = HashMap::new();
+    let mut stats = SearchStats::default();
+    let mut this_best_mem =
+        best_memory.lock().expect("failed to lock best_memory").to_vec();
+    let mut rng = if let Some(seed) = seed {
+        Pcg64::seed_from_u64(seed)
+    } else {
+        Pcg64::from_entropy()
+    };
+
+    for dive in 0.. {
+        match running {
+            Some(running) if running.load(Ordering::Relaxed) == 0 => break,
+            None if dive == GREEDY_DIVES => break,
+            _ => {},
+        }
+        if timer.finished() {
+            stats.timed_out = timer.timed_out();
+            break;
+        }
+        threaded::sync(best_memory, &mut this_best_mem);
+        if lower_bound.reached(&this_best_mem) {
+            stats.bound_reached = true;
+            break;
+        }
+
+        let mut current = scheduler.clone();
+        let mut current_path = Vec::new();
+        loop {
+            if let Some(mem) = current.at_leaf() {
+                if mem < this_best_mem[current_path.len()] {
+                    super::backward(
+                        Some(mem),
+                        &mut current_path,
+                        &mut this_best_mem,
+                        &mut results,
+                        &mut stats,
+                        (timer, None),
+                    );
+                }
+                break;
+            }
+            let mut children = Vec::new();
+            let mut iter = current.clone();
+            for _ in 0..GREEDY_CHILDREN {
+                let Some((child, measure)) = iter.next_and_focus() else {
+                    break;
+                };
+                stats.explored += 1;
+                timer.explored(1);
+                if cmp::max(child.cost_bound(), lower_bound.cost)
+                    >= this_best_mem
+                        [current_path.len() + 1 + child.time().min_remaining_steps()]
+                {
+                    stats.pruned += 1;
+                } else {
+                    children.push((child, measure));
+                }
+            }
+            if children.is_empty() {
+                break;
+            }
+            // stable, so that for equal bounds the children measuring more come first
+            children.sort_by_key(|(child, _)| child.cost_bound());
+            let chosen = if dive == 0 {
+                0
+            } else {
+                rng.gen_range(0..cmp::min(GREEDY_CANDIDATES, children.len()))
+            };
+            let (child, measure) = children.swap_remove(chosen);
+            super::enter(measure, &mut current_path, &mut stats);
+            current = child;
+        }
+    }
+
+    (results, this_best_mem, stats)
+}
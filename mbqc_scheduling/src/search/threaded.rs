@@ -11,19 +11,25 @@
 // (We also lock the results, but I don't think this is a problem, because it is not often
 // updated)
 
-use std::{cmp::Ordering, collections::HashMap, ops::Deref, sync::Mutex};
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    ops::Deref,
+    sync::Mutex,
+    thread::{self, ThreadId},
+};
 
-use rand::{distributions::Uniform, Rng, SeedableRng};
+use rand::{Rng, SeedableRng, distributions::Uniform};
 use rand_pcg::Pcg64;
 use scoped_threadpool::Pool;
 
-use super::{MappedPaths, Steps};
+use super::{Incumbent, LowerBound, MappedPaths, SearchStats, Steps};
 use crate::{
     probabilistic::{Accept, AcceptBox},
     scheduler::{
+        Partition, Scheduler,
         time::Partitioner,
         tree::{FocusIterator, Step, Sweep},
-        Partition, Scheduler,
     },
     timer::Timer,
 };
@@ -34,16 +40,19 @@ const UPDATE_INTERVAL: usize = 1000;
 
 pub fn search(
     nthreads: u16,
-    num_bits: usize,
+    max_path_length: usize,
+    lower_bound: LowerBound,
     mut scheduler: Scheduler<Partitioner>,
     task_bound: i64,
     probabilistic: Option<(AcceptBox, Option<u64>)>,
-    timer: &Timer,
-) -> MappedPaths {
+    control: (&Timer, Option<&Incumbent>),
+) -> (MappedPaths, SearchStats) {
     let mut pool = Pool::new(nthreads as u32);
 
-    let best_memory = Mutex::new(vec![usize::MAX; num_bits + 1]);
+    let best_memory = Mutex::new(vec![usize::MAX; max_path_length + 1]);
     let results: Mutex<MappedPaths> = Mutex::new(HashMap::new());
+    // the stats are collected per thread, not per task
+    let thread_stats: Mutex<Vec<(ThreadId, SearchStats)>> = Mutex::new(Vec::new());
     let mut probabilistic = match probabilistic {
         Some((ref func, seed)) => Some((
             func.deref(),
@@ -64,8 +73,14 @@ pub fn search(
         // the accept_func; but maybe it is actually good to not do it, because this
         // increases the probability that we get at least some results
         while let Some((scheduler_focused, init_measure)) = scheduler.next_and_focus() {
+            if lower_bound
+                .reached(&best_memory.lock().expect("failed to lock best_memory"))
+            {
+                break;
+            }
             let best_memory = &best_memory;
             let results = &results;
+            let thread_stats = &thread_stats;
             let probabilistic = match probabilistic {
                 Some((func, ref mut rng)) => Some((func, rng.gen())),
                 None => None,
@@ -74,10 +89,12 @@ pub fn search(
                 task(
                     best_memory,
                     results,
+                    thread_stats,
                     scheduler_focused,
+                    lower_bound,
                     ntasks,
                     Some(init_measure),
-                    timer,
+                    control,
                     probabilistic,
                 )
             });
@@ -94,22 +111,41 @@ pub fn search(
             None => None,
         };
         let best_memory = &best_memory;
+        let thread_stats = &thread_stats;
         scope.execute(move || {
-            task(best_memory, results, scheduler, -1, None, timer, probabilistic)
+            task(
+                best_memory,
+                results,
+                thread_stats,
+                scheduler,
+                lower_bound,
+                -1,
+                None,
+                control,
+                probabilistic,
+            )
         });
     });
 
-    results.into_inner().unwrap()
+    let mut stats = SearchStats::default();
+    for (_, this_stats) in thread_stats.into_inner().unwrap() {
+        stats.merge(&this_stats);
+        stats.threads.push(this_stats);
+    }
+
+    (results.into_inner().unwrap(), stats)
 }
 
 #[allow(clippy::too_many_arguments)]
 fn task(
     best_memory: &Mutex<Vec<usize>>,
     results: &Mutex<MappedPaths>,
+    thread_stats: &Mutex<Vec<(ThreadId, SearchStats)>>,
     scheduler: Scheduler<Partitioner>,
+    lower_bound: LowerBound,
     ntasks: i64,
     measure: Option<Vec<usize>>,
-    timer: &Timer,
+    control: (&Timer, Option<&Incumbent>),
     probabilistic: Option<(&Accept, u64)>,
 ) {
     let _span = tracing::debug_span!("search task", ntasks).entered();
@@ -120,20 +156,47 @@ fn task(
         best_memory.lock().unwrap()
     );
 
-    let (mut new_results, this_best_mem) = if let Some(probabilistic) = probabilistic {
-        do_probabilistic_search(
-            scheduler.into_iter(),
-            measure.map(|e| vec![e]),
-            best_memory,
-            timer,
-            probabilistic,
-        )
-    } else {
-        do_search(scheduler.into_iter(), measure.map(|e| vec![e]), best_memory, timer)
-    };
+    let (new_results, this_best_mem, this_stats) =
+        if let Some(probabilistic) = probabilistic {
+            do_probabilistic_search(
+                scheduler.into_iter(),
+                measure.map(|e| vec![e]),
+                best_memory,
+                lower_bound,
+                control,
+                probabilistic,
+            )
+        } else {
+            do_search(
+                scheduler.into_iter(),
+                measure.map(|e| vec![e]),
+                best_memory,
+                lower_bound,
+                control,
+            )
+        };
 
     tracing::debug!("DONE: results {:?}; best_memory {:?}", new_results, this_best_mem,);
 
+    {
+        let mut thread_stats = thread_stats.lock().expect("failed to lock thread_stats");
+        let id = thread::current().id();
+        match thread_stats.iter_mut().find(|(thread, _)| *thread == id) {
+            Some((_, stats)) => stats.merge(&this_stats),
+            None => thread_stats.push((id, this_stats)),
+        }
+    }
+
+    merge_results(best_memory, results, new_results, this_best_mem);
+}
+
+// merge the results of a finished search into the shared ones
+pub(super) fn merge_results(
+    best_memory: &Mutex<Vec<usize>>,
+    results: &Mutex<MappedPaths>,
+    mut new_results: MappedPaths,
+    this_best_mem: Vec<usize>,
+) {
     if new_results.is_empty() {
         return;
     }
@@ -162,43 +225,58 @@ fn update(
     best_memory: &Mutex<Vec<usize>>,
     this_best_mem: &mut [usize],
     update_counter: &mut usize,
+    (stats, reported): (&SearchStats, &mut u64),
     timer: &Timer,
 ) -> bool {
     //
     if *update_counter == UPDATE_INTERVAL {
-        best_memory
-            .lock()
-            .expect("failed to lock best_memory")
-            .iter_mut()
-            .zip(this_best_mem.iter_mut())
-            .for_each(|(shared_mem, this_mem)| match (*this_mem).cmp(shared_mem) {
-                Ordering::Greater => *this_mem = *shared_mem,
-                Ordering::Less => *shared_mem = *this_mem,
-                Ordering::Equal => {},
-            });
+        sync(best_memory, this_best_mem);
         *update_counter = 1; // zero means we never updated; NEVER SET IT TO 0!!!!!!!!
-        timer.finished()
+        let finished = timer.explored(stats.explored - *reported);
+        *reported = stats.explored;
+        finished
     } else {
         *update_counter += 1;
         false
     }
 }
 
-fn do_search(
+// exchange the best memories with the shared ones, such that both are the minimum
+pub(super) fn sync(best_memory: &Mutex<Vec<usize>>, this_best_mem: &mut [usize]) {
+    best_memory
+        .lock()
+        .expect("failed to lock best_memory")
+        .iter_mut()
+        .zip(this_best_mem.iter_mut())
+        .for_each(|(shared_mem, this_mem)| match (*this_mem).cmp(shared_mem) {
+            Ordering::Greater => *this_mem = *shared_mem,
+            Ordering::Less => *shared_mem = *this_mem,
+            Ordering::Equal => {},
+        });
+}
+
+pub(super) fn do_search(
     mut scheduler: Sweep<Scheduler<Partition<Vec<usize>>>>,
     init_path: Option<Steps>,
     best_memory: &Mutex<Vec<usize>>,
-    timer: &Timer,
-) -> (MappedPaths, Vec<usize>) {
+    lower_bound: LowerBound,
+    (timer, incumbent): (&Timer, Option<&Incumbent>),
+) -> (MappedPaths, Vec<usize>, SearchStats) {
     let mut results = HashMap::new();
     let was_initialized = init_path.is_some();
     let mut current_path = init_path.unwrap_or_default();
+    let mut stats = SearchStats {
+        max_depth: current_path.len(),
+        ..Default::default()
+    };
     let mut this_best_mem =
         best_memory.lock().expect("failed to lock predicates").to_vec();
     // we usually start counting at 1, however, for the first round we have the special
     // case that we may not update at all, and we want to catch that case, which is
     // "encoded" by 0 here; cf. the conditional ...==0 below
     let mut update_counter = 0;
+    // the number of explored nodes that have been reported to the timer
+    let mut reported = 0;
 
     while let Some(step) = scheduler.next() {
         match step {
@@ -207,7 +285,9 @@ fn do_search(
                     measure,
                     &mut scheduler,
                     &this_best_mem,
+                    lower_bound.cost,
                     &mut current_path,
+                    &mut stats,
                 ) {
                     break;
                 }
@@ -218,48 +298,71 @@ fn do_search(
                     &mut current_path,
                     &mut this_best_mem,
                     &mut results,
+                    &mut stats,
+                    (timer, incumbent),
                 );
             },
         }
-        if update(best_memory, &mut this_best_mem, &mut update_counter, timer) {
-            tracing::info!("timer: timeout");
+        if update(
+            best_memory,
+            &mut this_best_mem,
+            &mut update_counter,
+            (&stats, &mut reported),
+            timer,
+        ) {
+            tracing::info!("timer: {:?}", timer.stop_reason());
+            stats.timed_out = timer.timed_out();
+            break;
+        }
+        if lower_bound.reached(&this_best_mem) {
+            stats.bound_reached = true;
             break;
         }
     }
 
+    // the task root may have no children because it is finished, or because it is a
+    // dead end, e.g., when all its children miss a deadline
     if update_counter == 0 && was_initialized {
         super::backward(
-            Some(scheduler.current().space().max_memory()),
+            scheduler.current().at_leaf(),
             &mut current_path,
             &mut this_best_mem,
             &mut results,
+            &mut stats,
+            (timer, incumbent),
         )
     }
 
-    (results, this_best_mem)
+    (results, this_best_mem, stats)
 }
 
-fn do_probabilistic_search(
+pub(super) fn do_probabilistic_search(
     mut scheduler: Sweep<Scheduler<Partition<Vec<usize>>>>,
     init_path: Option<Steps>,
     best_memory: &Mutex<Vec<usize>>,
-    timer: &Timer,
+    lower_bound: LowerBound,
+    (timer, incumbent): (&Timer, Option<&Incumbent>),
     (accept_func, seed): (&Accept, u64),
-) -> (MappedPaths, Vec<usize>) {
+) -> (MappedPaths, Vec<usize>, SearchStats) {
     let mut results = HashMap::new();
     let was_initialized = init_path.is_some();
     let mut current_path = init_path.unwrap_or_default();
+    let mut stats = SearchStats {
+        max_depth: current_path.len(),
+        ..Default::default()
+    };
     let mut this_best_mem =
         best_memory.lock().expect("failed to lock best_memory").to_vec();
     let mut update_counter = 0;
+    let mut reported = 0;
 
     let mut rng = Pcg64::seed_from_u64(seed);
     let dist = Uniform::new(0., 1.);
 
     loop {
-        let space = scheduler.current().space();
-        let last_cur_mem = space.current_memory();
-        let last_max_mem = space.max_memory();
+        let current = scheduler.current();
+        let last_cur_mem = super::current_cost(current);
+        let last_max_mem = current.cost_bound();
         if let Some(step) = scheduler.next() {
             match step {
                 Step::Forward(measure) => {
@@ -267,12 +370,14 @@ fn do_probabilistic_search(
                         measure,
                         &mut scheduler,
                         &this_best_mem,
+                        lower_bound.cost,
                         &mut current_path,
                         last_cur_mem,
                         last_max_mem,
                         &mut rng,
                         &dist,
                         accept_func,
+                        &mut stats,
                     ) {
                         break;
                     }
@@ -283,11 +388,24 @@ fn do_probabilistic_search(
                         &mut current_path,
                         &mut this_best_mem,
                         &mut results,
+                        &mut stats,
+                        (timer, incumbent),
                     );
                 },
             }
-            if update(best_memory, &mut this_best_mem, &mut update_counter, timer) {
-                tracing::info!("timer: timeout");
+            if update(
+                best_memory,
+                &mut this_best_mem,
+                &mut update_counter,
+                (&stats, &mut reported),
+                timer,
+            ) {
+                tracing::info!("timer: {:?}", timer.stop_reason());
+                stats.timed_out = timer.timed_out();
+                break;
+            }
+            if lower_bound.reached(&this_best_mem) {
+                stats.bound_reached = true;
                 break;
             }
         } else {
@@ -295,14 +413,18 @@ fn do_probabilistic_search(
         }
     }
 
+    // the task root may have no children because it is finished, or because it is a
+    // dead end, e.g., when all its children miss a deadline
     if update_counter == 0 && was_initialized {
         super::backward(
-            Some(scheduler.current().space().max_memory()),
+            scheduler.current().at_leaf(),
             &mut current_path,
             &mut this_best_mem,
             &mut results,
+            &mut stats,
+            (timer, incumbent),
         )
     }
 
-    (results, this_best_mem)
+    (results, this_best_mem, stats)
 }
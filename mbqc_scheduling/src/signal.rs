@@ -0,0 +1,86 @@
+// On SIGINT and SIGTERM, we stop the search gracefully, as if the timeout had passed, so
+// that the paths found so far are written; a second signal terminates the process as
+// usual.
+
+#[cfg(unix)]
+use std::sync::OnceLock;
+
+use mbqc_scheduling::timer::CancelFlag;
+
+// the flag that the handler sets; it is only initialized once, so that the handler can
+// read it without locking
+#[cfg(unix)]
+static CANCEL: OnceLock<CancelFlag> = OnceLock::new();
+
+// returns the flag that is set on the first signal, to be passed to the search
+#[cfg(unix)]
+pub fn install_handlers() -> CancelFlag {
+    extern "C" fn handler(signal: libc::c_int) {
+        // reading the initialized OnceLock and setting the flag are only atomic
+        // operations, and resetting the handler is async-signal-safe
+        if let Some(cancel) = CANCEL.get() {
+            cancel.cancel();
+        }
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+        }
+    }
+
+    let cancel = CANCEL.get_or_init(CancelFlag::new).clone();
+    for signal in [libc::SIGINT, libc::SIGTERM] {
+        // SAFETY: the handler only calls async-signal-safe functions
+        unsafe {
+            libc::signal(
+                signal,
+                handler as extern "C" fn(libc::c_int) as libc::sighandler_t,
+            );
+        }
+    }
+    cancel
+}
+
+#[cfg(not(unix))]
+pub fn install_handlers() -> CancelFlag {
+    CancelFlag::new()
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use mbqc_scheduling::timer::{StopReason, Timer};
+
+    use super::*;
+
+    // the current handler of the `signal`
+    fn handler(signal: libc::c_int) -> libc::sighandler_t {
+        // SAFETY: only reads the current action into a zeroed struct
+        unsafe {
+            let mut action: libc::sigaction = std::mem::zeroed();
+            assert_eq!(libc::sigaction(signal, std::ptr::null(), &mut action), 0);
+            action.sa_sigaction
+        }
+    }
+
+    #[test]
+    fn interrupt() {
+        let cancel = install_handlers();
+        assert_ne!(handler(libc::SIGINT), libc::SIG_DFL);
+        assert_ne!(handler(libc::SIGTERM), libc::SIG_DFL);
+        assert!(!Timer::new().with_cancel(Some(cancel.clone())).finished());
+        // SAFETY: the handler is installed, so this does not terminate the process
+        unsafe {
+            libc::raise(libc::SIGINT);
+        }
+        // the timers with the flag are stopped, also the ones that are started later,
+        // but not the others
+        let timer = Timer::new().with_cancel(Some(cancel));
+        assert!(timer.finished());
+        assert_eq!(timer.stop_reason(), Some(StopReason::Interrupted));
+        assert!(!Timer::new().finished());
+        // a second SIGINT terminates the process
+        assert_eq!(handler(libc::SIGINT), libc::SIG_DFL);
+        // SAFETY: restores the default action
+        unsafe {
+            libc::signal(libc::SIGTERM, libc::SIG_DFL);
+        }
+    }
+}
@@ -0,0 +1,289 @@
+/*!
+Automatically choose how to search, cf. [Mode::Auto](crate::interface::Mode::Auto).
+
+Depending on the instance and the time budget, we choose between the exact
+[search](crate::search::search), the probabilistic search and just taking the
+[time-optimal path](crate::search::get_time_optimal) (which is greedy with respect to
+the other costs):
+- Very small instances are always searched exactly.
+- If the widest layer of the dependency graph allows too many measurement steps, already
+  the first levels of the search tree are too large, so we take the time-optimal path.
+- Otherwise, we [estimate](crate::estimate) the runtime of the exact search; if it does
+  not fit into the budget, we estimate the probabilistic search with increasingly
+  restrictive accept functions and take the first one that fits. If none fits, we take
+  the time-optimal path.
+*/
+
+use std::{cmp, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    estimate::{self, Estimate},
+    interface::Constraints,
+    probabilistic::{Accept, AcceptFunc, HeavysideParameters},
+    scheduler::{
+        space::{Objective, RefSpacialGraph},
+        time::RefPartialOrderGraph,
+    },
+    search,
+};
+
+/// Instances with at most that many measured nodes are always searched exactly.
+pub const MAX_SMALL_NODES: usize = 8;
+/// If the logarithm (base 2) of the number of possible measurement steps in the widest
+/// layer is larger than that, the time-optimal path is taken.
+pub const MAX_LOG2_BRANCHING: f64 = 40.;
+/// The time budget if no timeout is given.
+pub const DEFAULT_BUDGET: Duration = Duration::from_secs(60);
+/// The number of probes for the [estimate](estimate::estimate)s.
+pub const PROBES: usize = 1000;
+/// Searches that are estimated to be faster than that many seconds are not
+/// multi-threaded.
+pub const MIN_THREADED_SECONDS: f64 = 1.;
+
+/// The strategy that is used to get the [Path](crate::interface::Path)s.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// Only the time-optimal path, without searching.
+    #[default]
+    TimeOptimal,
+    /// The deterministic search.
+    Exact,
+    /// The probabilistic search.
+    Probabilistic,
+    /// The windowed search, cf. [run_windowed](crate::interface::run_windowed) (never
+    /// chosen by [choose]).
+    Windowed,
+    /// A portfolio of strategies running in parallel, cf.
+    /// [Mode::Portfolio](crate::interface::Mode::Portfolio) (never chosen by [choose]).
+    Portfolio,
+}
+
+/// The result of [choose].
+pub struct Choice {
+    /// The chosen strategy.
+    pub strategy: Strategy,
+    /// The number of threads for the search.
+    pub nthreads: u16,
+    /// The accept function, if the strategy is [Strategy::Probabilistic].
+    pub accept_func: Option<AcceptFunc>,
+    /// The last estimate that was done, if any.
+    pub estimate: Option<Estimate>,
+}
+
+/// Choose the [Strategy], cf. the [module documentation](self). Regarding the
+/// parameters, cf. [interface::run](crate::interface::run); `timeout` is the time budget
+/// (defaulting to [DEFAULT_BUDGET]), `nthreads` is the maximum number of threads, and if
+/// an `accept_func` is given, it is the only one that is considered for the
+/// probabilistic search.
+#[allow(clippy::too_many_arguments)]
+pub fn choose(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    objective: Objective,
+    timeout: Option<Duration>,
+    nthreads: u16,
+    accept_func: Option<AcceptFunc>,
+) -> Choice {
+    let choice = |strategy, nthreads, accept_func, estimate| Choice {
+        strategy,
+        nthreads,
+        accept_func,
+        estimate,
+    };
+
+    let num_measured = spacial_graph.len()
+        - search::all_outputs(spacial_graph.len(), time_ordering, outputs).len();
+    if num_measured <= MAX_SMALL_NODES {
+        return choice(Strategy::Exact, 1, None, None);
+    }
+    let width = time_ordering.iter().map(|layer| layer.len()).max().unwrap_or(0);
+    if log2_num_steps(width, constraints.max_step_size) > MAX_LOG2_BRANCHING {
+        return choice(Strategy::TimeOptimal, 1, None, None);
+    }
+
+    let budget = timeout.unwrap_or(DEFAULT_BUDGET).as_secs_f64();
+    let estimate = |accept_func: Option<&Accept>| {
+        estimate::estimate(
+            spacial_graph,
+            weights,
+            outputs,
+            time_ordering,
+            constraints,
+            objective,
+            accept_func,
+            PROBES,
+            Some(0),
+        )
+    };
+    // one thread is only managing the others
+    let threads = |seconds: f64| {
+        if seconds > MIN_THREADED_SECONDS && nthreads > 1 {
+            (nthreads, (nthreads - 1) as f64)
+        } else {
+            (1, 1.)
+        }
+    };
+
+    let exact = estimate(None);
+    let (nthreads, speedup) = threads(exact.seconds);
+    if exact.seconds / speedup <= budget {
+        return choice(Strategy::Exact, nthreads, None, Some(exact));
+    }
+
+    let candidates = match accept_func {
+        Some(accept_func) => vec![accept_func],
+        None => accept_candidates(),
+    };
+    let mut last = exact;
+    for candidate in candidates {
+        // keep the description, so that the choice can be reported and replayed
+        let config = candidate.config();
+        let candidate = candidate.get_accept_func();
+        let estimate = estimate(Some(&*candidate));
+        let seconds = estimate.probabilistic_seconds.expect("accept_func is given");
+        let (nthreads, speedup) = threads(seconds);
+        if seconds / speedup <= budget {
+            return choice(
+                Strategy::Probabilistic,
+                nthreads,
+                Some(config.map_or(AcceptFunc::Custom(candidate), AcceptFunc::from)),
+                Some(estimate),
+            );
+        }
+        last = estimate;
+    }
+    choice(Strategy::TimeOptimal, 1, None, Some(last))
+}
+
+// log2(sum_{i=1}^{max_step_size} binomial(width, i))
+pub(crate) fn log2_num_steps(width: usize, max_step_size: Option<usize>) -> f64 {
+    let max = max_step_size.map_or(width, |max| cmp::min(max, width));
+    if max == width {
+        // 2^width - 1
+        return width as f64;
+    }
+    let mut log_binomial = 0.;
+    let mut log_max = 0.;
+    let mut logs = Vec::with_capacity(max);
+    for i in 1..=max {
+        log_binomial += ((width - i + 1) as f64).log2() - (i as f64).log2();
+        log_max = f64::max(log_max, log_binomial);
+        logs.push(log_binomial);
+    }
+    log_max + logs.iter().map(|l| (l - log_max).exp2()).sum::<f64>().log2()
+}
+
+// the builtin heavyside function, and increasingly restrictive versions of it
+fn accept_candidates() -> Vec<AcceptFunc> {
+    [2, 1, 0, -1, -2]
+        .into_iter()
+        .map(|lin_num_total_nodes_exp| AcceptFunc::ParametrizedHeavyside {
+            param: HeavysideParameters {
+                cutoff: 0.,
+                lin_num_total_nodes_exp,
+                exp_num_total_nodes_exp: 1,
+                exp_num_remaining_nodes_exp: 1,
+                exp_diff_exp: 3,
+                exp_num_measured_nodes_exp: 1,
+            },
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn choices() {
+        let graph = vec![vec![1], vec![0, 2], vec![1, 3], vec![2, 4], vec![3]];
+        let ordering = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(2, vec![0])],
+            vec![(4, vec![2])],
+        ];
+        let choice = choose(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Constraints::default(),
+            Objective::Space,
+            None,
+            4,
+            None,
+        );
+        assert_eq!(choice.strategy, Strategy::Exact);
+        assert_eq!(choice.nthreads, 1);
+
+        let num = 60;
+        let graph = vec![vec![]; num];
+        let ordering = vec![(0..num).map(|bit| (bit, vec![])).collect::<Vec<_>>()];
+        let choose = |max_step_size| {
+            choose(
+                &graph,
+                None,
+                &[],
+                &ordering,
+                &Constraints {
+                    max_step_size,
+                    ..Default::default()
+                },
+                Objective::Space,
+                Some(Duration::from_secs(1)),
+                1,
+                None,
+            )
+            .strategy
+        };
+        assert_eq!(choose(None), Strategy::TimeOptimal);
+        // the time-optimal path already reaches the lower bound
+        assert_eq!(choose(Some(1)), Strategy::Exact);
+    }
+
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn probabilistic_choice() {
+        // a ring whose second half depends on the first half; the exact search takes
+        // hours, but the restrictive accept functions take milliseconds
+        let num = 16;
+        let graph: Vec<Vec<usize>> = (0..num)
+            .map(|bit| vec![(bit + 1) % num, (bit + num - 1) % num])
+            .collect();
+        let ordering = vec![
+            (0..num / 2).map(|bit| (bit, vec![])).collect::<Vec<_>>(),
+            (num / 2..num).map(|bit| (bit, vec![bit - num / 2])).collect(),
+        ];
+        let choice = choose(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Constraints::default(),
+            Objective::Space,
+            Some(Duration::from_secs(10)),
+            1,
+            None,
+        );
+        assert_eq!(choice.strategy, Strategy::Probabilistic);
+        // the parameters are kept, so that the choice can be reported
+        let config = choice.accept_func.unwrap().config().unwrap();
+        assert!(
+            accept_candidates()
+                .into_iter()
+                .any(|candidate| candidate.config() == Some(config))
+        );
+    }
+
+    #[test]
+    fn num_steps() {
+        assert_eq!(log2_num_steps(10, None), 10.);
+        assert!((log2_num_steps(10, Some(2)) - 55f64.log2()).abs() < 1e-10);
+    }
+}
@@ -4,17 +4,95 @@ Basic timing functionality (not precise).
 
 use std::{
     sync::{
-        atomic::{AtomicBool, Ordering},
         Arc, Condvar, Mutex,
+        atomic::{AtomicBool, AtomicU8, AtomicU64, Ordering},
     },
     thread::{self, JoinHandle},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
+use serde::{Deserialize, Serialize};
+
+/// The reason why a [Timer] has finished.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[repr(u8)]
+pub enum StopReason {
+    /// The overall timeout has passed.
+    Timeout = 1,
+    /// There has been no [improvement](Timer::improved) for the stall timeout.
+    Stall,
+    /// There has been no [improvement](Timer::improved) at all until the first-solution
+    /// timeout.
+    NoSolution,
+    /// The node budget has been [exhausted](Timer::explored).
+    NodeBudget,
+    /// The timer has been [stopped](Timer::stop) manually.
+    Stopped,
+    /// The [CancelFlag] of the timer has been set, e.g., because the process has been
+    /// interrupted.
+    Interrupted,
+}
+
+impl StopReason {
+    fn from_u8(reason: u8) -> Option<Self> {
+        Some(match reason {
+            1 => Self::Timeout,
+            2 => Self::Stall,
+            3 => Self::NoSolution,
+            4 => Self::NodeBudget,
+            5 => Self::Stopped,
+            6 => Self::Interrupted,
+            _ => return None,
+        })
+    }
+}
+
+/// A flag to cancel the [Timer]s that have it, cf. [with_cancel](Timer::with_cancel);
+/// the clones of a flag share it.
+#[derive(Clone, Debug, Default)]
+pub struct CancelFlag(Arc<AtomicBool>);
+
+impl CancelFlag {
+    /// Create a new flag that is not set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Let the timers with this flag finish, now and in the future (there is no way to
+    /// undo this).
+    ///
+    /// This only stores to an atomic, so it is async-signal-safe, i.e., it can be called
+    /// from a signal handler to stop the searches gracefully, e.g., on SIGINT.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    /// Check whether the flag has been set.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+impl PartialEq for CancelFlag {
+    /// Flags are equal if they are shared.
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for CancelFlag {}
+
 /// A simple timer.
 ///
 /// Whether the timer has finished can be checked with the `finished` method.
 ///
+/// Besides the overall timeout, the timer can also finish if there has been no
+/// [improvement](Timer::improved) for some time, or none at all until a deadline, cf.
+/// [start_with](Timer::start_with), and when a budget of explored nodes is exhausted, cf.
+/// [with_node_budget](Timer::with_node_budget), and when it is cancelled, cf.
+/// [with_cancel](Timer::with_cancel).
+///
 /// **Note**: This should not be used for precise timing, since it is not precise.
 /// Instead, it should be rather used, for example, to signal a thread to stop after the
 /// timeout.
@@ -44,6 +122,14 @@ pub struct Timer {
     // logically, this information is redundant with pair.0, but since it is not behind a
     // mutex, it can be read without locking
     finished: Arc<AtomicBool>,
+    // the StopReason as u8; 0 means none
+    reason: Arc<AtomicU8>,
+    start: Instant,
+    // milliseconds since `start`, plus one (0 means that there has been no improvement)
+    last_improvement: Arc<AtomicU64>,
+    explored: AtomicU64,
+    node_budget: Option<u64>,
+    cancel: Option<CancelFlag>,
 }
 
 impl Timer {
@@ -53,34 +139,171 @@ impl Timer {
             pair: Arc::new((Mutex::new(false), Condvar::new())),
             handle: None,
             finished: Arc::new(AtomicBool::new(false)),
+            reason: Arc::new(AtomicU8::new(0)),
+            start: Instant::now(),
+            last_improvement: Arc::new(AtomicU64::new(0)),
+            explored: AtomicU64::new(0),
+            node_budget: None,
+            cancel: None,
         }
     }
 
+    /// Let the timer finish when more than `node_budget` nodes have been
+    /// [explored](Self::explored).
+    pub fn with_node_budget(mut self, node_budget: Option<u64>) -> Self {
+        self.node_budget = node_budget;
+        self
+    }
+
+    /// Let the timer finish when the `cancel` flag is set (also if it never started).
+    pub fn with_cancel(mut self, cancel: Option<CancelFlag>) -> Self {
+        self.cancel = cancel;
+        self
+    }
+
     /// Start the timer with the given duration.
     pub fn start(&mut self, duration: Duration) {
+        self.start_with(Some(duration), None, None);
+    }
+
+    /// Start the timer with an optional overall `timeout`, an optional `stall` timeout,
+    /// after which it finishes if there has been no [improvement](Self::improved) for
+    /// that long, and an optional `first_solution` timeout, after which it finishes if
+    /// there has been no improvement at all.
+    ///
+    /// # Panics
+    /// Panics if the timer has already been started.
+    pub fn start_with(
+        &mut self,
+        timeout: Option<Duration>,
+        stall: Option<Duration>,
+        first_solution: Option<Duration>,
+    ) {
+        assert!(self.handle.is_none(), "the timer has already been started");
+        if timeout.is_none() && stall.is_none() && first_solution.is_none() {
+            return;
+        }
         let pair = self.pair.clone();
-        let time = Arc::clone(&self.finished);
+        let finished = Arc::clone(&self.finished);
+        let reason = Arc::clone(&self.reason);
+        let last_improvement = Arc::clone(&self.last_improvement);
+        self.start = Instant::now();
+        let start = self.start;
 
         self.handle = Some(thread::spawn(move || {
             let (lock, cvar) = &*pair;
-            let (_lock, timeout) = cvar
-                .wait_timeout_while(
-                    lock.lock().expect("timer: locking at start failed"),
-                    duration,
-                    |&mut dropping_timer| !dropping_timer,
-                )
-                .expect("timer: re-acquiring lock after timeout/notifaction failed");
-            if timeout.timed_out() {
-                time.store(true, Ordering::Relaxed);
+            let mut dropping_timer = lock.lock().expect("timer: locking at start failed");
+            loop {
+                // the deadlines might change (when there is an improvement), so we wake up
+                // at the earliest one and check them again
+                let improvement = last_improvement.load(Ordering::Relaxed);
+                let stall_deadline = stall.map(|stall| {
+                    Duration::from_millis(improvement.saturating_sub(1)) + stall
+                });
+                let first_solution_deadline = first_solution.filter(|_| improvement == 0);
+                let Some(deadline) = [timeout, stall_deadline, first_solution_deadline]
+                    .into_iter()
+                    .flatten()
+                    .min()
+                else {
+                    return;
+                };
+                let (lock, _) = cvar
+                    .wait_timeout_while(
+                        dropping_timer,
+                        deadline.saturating_sub(start.elapsed()),
+                        |&mut dropping_timer| !dropping_timer,
+                    )
+                    .expect("timer: re-acquiring lock after timeout/notifaction failed");
+                dropping_timer = lock;
+                if *dropping_timer {
+                    return;
+                }
+                let elapsed = start.elapsed();
+                let improvement = last_improvement.load(Ordering::Relaxed);
+                let stop_reason = if timeout.is_some_and(|timeout| elapsed >= timeout) {
+                    StopReason::Timeout
+                } else if first_solution
+                    .is_some_and(|first| improvement == 0 && elapsed >= first)
+                {
+                    StopReason::NoSolution
+                } else if stall.is_some_and(|stall| {
+                    elapsed
+                        >= Duration::from_millis(improvement.saturating_sub(1)) + stall
+                }) {
+                    StopReason::Stall
+                } else {
+                    continue;
+                };
+                let _ = reason.compare_exchange(
+                    0,
+                    stop_reason as u8,
+                    Ordering::Relaxed,
+                    Ordering::Relaxed,
+                );
+                finished.store(true, Ordering::Relaxed);
+                return;
             }
         }));
     }
 
+    /// Signal that there has been an improvement, e.g., a better path has been found,
+    /// which resets the stall timeout, cf. [start_with](Self::start_with).
+    pub fn improved(&self) {
+        self.last_improvement
+            .store(self.start.elapsed().as_millis() as u64 + 1, Ordering::Relaxed);
+    }
+
+    /// Add `count` to the number of explored nodes; if the node budget is exhausted, the
+    /// timer finishes, cf. [with_node_budget](Self::with_node_budget). Returns whether
+    /// the timer has finished.
+    pub fn explored(&self, count: u64) -> bool {
+        if let Some(budget) = self.node_budget {
+            if self.explored.fetch_add(count, Ordering::Relaxed) + count > budget {
+                self.stop_with(StopReason::NodeBudget);
+            }
+        }
+        self.finished()
+    }
+
+    /// Stop the timer now, i.e., it is [finished](Self::finished) from now on (also if
+    /// it never started).
+    pub fn stop(&self) {
+        self.stop_with(StopReason::Stopped);
+    }
+
+    fn stop_with(&self, reason: StopReason) {
+        let _ = self.reason.compare_exchange(
+            0,
+            reason as u8,
+            Ordering::Relaxed,
+            Ordering::Relaxed,
+        );
+        self.finished.store(true, Ordering::Relaxed);
+    }
+
+    /// Get the reason why the timer has finished, if it has finished.
+    pub fn stop_reason(&self) -> Option<StopReason> {
+        StopReason::from_u8(self.reason.load(Ordering::Relaxed))
+            .or_else(|| self.cancelled().then_some(StopReason::Interrupted))
+    }
+
+    /// Checks whether the timer has been finished by one of its limits or by its
+    /// [CancelFlag], i.e., for any [StopReason] except [Stopped](StopReason::Stopped).
+    pub fn timed_out(&self) -> bool {
+        self.stop_reason().is_some_and(|reason| reason != StopReason::Stopped)
+    }
+
     /// Checks whether the timer has finished.
     ///
-    /// If the timer never started, this will always return `false`.
+    /// If the timer never started, this will always return `false`, unless it has been
+    /// cancelled, cf. [with_cancel](Self::with_cancel).
     pub fn finished(&self) -> bool {
-        self.finished.load(Ordering::Relaxed)
+        self.finished.load(Ordering::Relaxed) || self.cancelled()
+    }
+
+    fn cancelled(&self) -> bool {
+        self.cancel.as_ref().is_some_and(CancelFlag::is_cancelled)
     }
 }
 
@@ -123,6 +346,75 @@ mod tests {
         });
     }
 
+    // wait (generously) until the `timer` has finished
+    fn wait_until_finished(timer: &Timer) {
+        let start = Instant::now();
+        while !timer.finished() {
+            assert!(
+                start.elapsed() < Duration::from_secs(10),
+                "the timer did not finish"
+            );
+            thread::sleep(Duration::from_millis(1));
+        }
+    }
+
+    // the margins are large, so that a busy machine does not make this fail; that the
+    // timers do not finish too early does not depend on the scheduling at all
+    #[test]
+    #[cfg_attr(miri, ignore)]
+    fn stop_criteria() {
+        let stall = Duration::from_millis(500);
+        let mut timer = Timer::new();
+        timer.start_with(None, Some(stall), None);
+        let start = Instant::now();
+        let mut last_improvement = start;
+        // improve for longer than the stall timeout
+        while start.elapsed() < stall + Duration::from_millis(100) {
+            thread::sleep(Duration::from_millis(10));
+            last_improvement = Instant::now();
+            timer.improved();
+        }
+        assert!(!timer.finished());
+        wait_until_finished(&timer);
+        // the improvements are recorded with a precision of one millisecond
+        assert!(last_improvement.elapsed() + Duration::from_millis(1) >= stall);
+        assert_eq!(timer.stop_reason(), Some(StopReason::Stall));
+
+        let first_solution = Duration::from_millis(20);
+        let start = Instant::now();
+        let mut timer = Timer::new();
+        timer.start_with(Some(Duration::from_secs(100)), None, Some(first_solution));
+        wait_until_finished(&timer);
+        assert!(start.elapsed() >= first_solution);
+        assert_eq!(timer.stop_reason(), Some(StopReason::NoSolution));
+
+        let timer = Timer::new().with_node_budget(Some(10));
+        assert!(!timer.explored(10));
+        assert!(timer.explored(1));
+        assert_eq!(timer.stop_reason(), Some(StopReason::NodeBudget));
+        assert!(timer.timed_out());
+
+        let timer = Timer::new();
+        timer.stop();
+        assert!(timer.finished());
+        assert!(!timer.timed_out());
+    }
+
+    #[test]
+    fn cancel() {
+        let cancel = CancelFlag::new();
+        let timer = Timer::new().with_cancel(Some(cancel.clone()));
+        let other = Timer::new().with_cancel(Some(CancelFlag::new()));
+        assert!(!timer.finished());
+        cancel.cancel();
+        assert!(timer.finished());
+        assert!(timer.timed_out());
+        assert_eq!(timer.stop_reason(), Some(StopReason::Interrupted));
+        // only the timers with the same flag are cancelled
+        assert!(!other.finished());
+        assert!(!Timer::new().finished());
+    }
+
     #[test]
     fn miri() {
         let mut timer = Timer::new();
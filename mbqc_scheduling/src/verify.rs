@@ -0,0 +1,238 @@
+/*!
+Verify that a measurement pattern is valid, i.e., that it respects the time ordering and
+the [Constraints].
+*/
+
+use thiserror::Error;
+
+use crate::{
+    interface::{Constraints, Optimality, Path},
+    scheduler::{
+        space::{Graph, RefSpacialGraph, State},
+        time::{PathGenerator, RefPartialOrderGraph},
+        tree::Focus,
+    },
+    search,
+};
+
+/// A violation of the time ordering or the [Constraints] by a measurement pattern. The
+/// `step`s are counted from 0.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Error)]
+pub enum VerificationError {
+    /// The node is not part of the graph.
+    #[error("step {step}: the node {node} is not part of the graph")]
+    UnknownNode { step: usize, node: usize },
+    /// The node is an output node, which must not be measured.
+    #[error("step {step}: the node {node} is an output and cannot be measured")]
+    OutputMeasured { step: usize, node: usize },
+    /// The node has already been measured.
+    #[error("step {step}: the node {node} has already been measured")]
+    AlreadyMeasured { step: usize, node: usize },
+    /// The node is measured before its release time (or the release time of one of its
+    /// neighbors).
+    #[error(
+        "step {step}: the node {node} cannot be measured before step {release_time} \
+         (release time of itself or of a neighbor)"
+    )]
+    NotReleased {
+        step: usize,
+        node: usize,
+        release_time: usize,
+    },
+    /// The nodes are measured before their dependencies are measured (or before the
+    /// feed-forward latency has passed).
+    #[error("step {step}: the nodes {nodes:?} are not measurable yet")]
+    NotMeasurable { step: usize, nodes: Vec<usize> },
+    /// More nodes are measured in one step than allowed.
+    #[error("step {step}: {size} nodes are measured, but at most {max} are allowed")]
+    StepTooLarge { step: usize, size: usize, max: usize },
+    /// The node cannot be measured before its deadline anymore.
+    #[error(
+        "step {step}: the node {node} cannot be measured before its deadline {deadline}"
+    )]
+    DeadlineMissed {
+        step: usize,
+        node: usize,
+        deadline: usize,
+    },
+    /// The pattern ends before all (non-output) nodes are measured.
+    #[error("the nodes {0:?} are never measured")]
+    Unmeasured(Vec<usize>),
+}
+
+/// Verify that the measurement `steps` are a valid pattern for the given problem, and
+/// calculate its costs. Regarding the parameters, cf. [interface::run].
+///
+/// [interface::run]: crate::interface::run
+pub fn verify(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    steps: &[Vec<usize>],
+) -> Result<Path, VerificationError> {
+    let num_bits = spacial_graph.len();
+    let outputs = search::all_outputs(num_bits, time_ordering, outputs);
+    let mut dependency_buffer =
+        search::new_dependency_buffer(spacial_graph, &outputs, constraints);
+    let mut release_times = vec![0; num_bits];
+    for (bit, release_time) in
+        search::release_times(spacial_graph, &constraints.release_times)
+    {
+        release_times[bit] = release_time;
+    }
+
+    let mut time = PathGenerator::<Vec<usize>>::from_dependency_graph(
+        time_ordering,
+        &mut dependency_buffer,
+        None,
+    );
+    let mut space =
+        Graph::new(spacial_graph).with_weights(weights).with_outputs(&outputs);
+
+    for (step, measure_set) in steps.iter().enumerate() {
+        if let Some(max) = constraints.max_step_size {
+            if measure_set.len() > max {
+                return Err(VerificationError::StepTooLarge {
+                    step,
+                    size: measure_set.len(),
+                    max,
+                });
+            }
+        }
+        for node in measure_set.iter() {
+            let node = *node;
+            if node >= num_bits {
+                return Err(VerificationError::UnknownNode { step, node });
+            }
+            if outputs.contains(&node) {
+                return Err(VerificationError::OutputMeasured { step, node });
+            }
+            if space.nodes()[node].0 == State::Measured {
+                return Err(VerificationError::AlreadyMeasured { step, node });
+            }
+            if release_times[node] > step {
+                return Err(VerificationError::NotReleased {
+                    step,
+                    node,
+                    release_time: release_times[node],
+                });
+            }
+        }
+        if let Err(e) = time.focus_inplace(measure_set) {
+            return Err(VerificationError::NotMeasurable { step, nodes: e.0 });
+        }
+        space
+            .focus_inplace(measure_set)
+            .expect("already checked that the nodes are not measured yet");
+        if let Some((node, deadline)) = time.missed_deadline() {
+            return Err(VerificationError::DeadlineMissed { step, node, deadline });
+        }
+    }
+
+    let unmeasured = space
+        .nodes()
+        .iter()
+        .enumerate()
+        .filter(|(node, (state, _))| *state != State::Measured && !outputs.contains(node))
+        .map(|(node, _)| node)
+        .collect::<Vec<_>>();
+    if !unmeasured.is_empty() {
+        return Err(VerificationError::Unmeasured(unmeasured));
+    }
+
+    Ok(Path {
+        time: steps.len(),
+        space: space.max_memory(),
+        volume: space.volume(),
+        idle: space.idle(),
+        optimality: Optimality::BestFound,
+        steps: steps.to_vec(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+
+    use super::*;
+    use crate::scheduler::space::SpacialGraph;
+
+    // 0 - 1 - 2 - 3, with 3 depending on 0 and 2 depending on 1
+    fn problem() -> (SpacialGraph, PartialOrderGraph) {
+        (
+            vec![vec![1], vec![0, 2], vec![1, 3], vec![2]],
+            vec![vec![(0, vec![]), (1, vec![])], vec![(2, vec![1]), (3, vec![0])]],
+        )
+    }
+
+    #[test]
+    fn valid() {
+        let (graph, ordering) = problem();
+        let path = verify(
+            &graph,
+            None,
+            &[],
+            &ordering,
+            &Constraints::default(),
+            &[vec![0, 1], vec![2, 3]],
+        )
+        .unwrap();
+        assert_eq!((path.time, path.space, path.volume, path.idle), (2, 3, 5, 1));
+    }
+
+    #[test]
+    fn violations() {
+        let (graph, ordering) = problem();
+        let default = Constraints::default();
+        let check = |constraints: &Constraints, steps: &[Vec<usize>]| {
+            verify(&graph, None, &[], &ordering, constraints, steps).unwrap_err()
+        };
+
+        assert_eq!(
+            check(&default, &[vec![0, 2]]),
+            VerificationError::NotMeasurable { step: 0, nodes: vec![2] }
+        );
+        assert_eq!(
+            check(&default, &[vec![0, 1], vec![0]]),
+            VerificationError::AlreadyMeasured { step: 1, node: 0 }
+        );
+        assert_eq!(
+            check(&default, &[vec![0, 1], vec![2]]),
+            VerificationError::Unmeasured(vec![3])
+        );
+
+        let constraints = Constraints {
+            release_times: vec![(2, 2)],
+            ..Default::default()
+        };
+        // 1 is a neighbor of 2
+        assert_eq!(
+            check(&constraints, &[vec![0, 1]]),
+            VerificationError::NotReleased {
+                step: 0,
+                node: 1,
+                release_time: 2
+            }
+        );
+
+        let constraints = Constraints {
+            deadlines: vec![(3, 1)],
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&constraints, &[vec![1], vec![0], vec![2, 3]]),
+            VerificationError::DeadlineMissed { step: 0, node: 3, deadline: 1 }
+        );
+
+        let constraints = Constraints {
+            max_step_size: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            check(&constraints, &[vec![0, 1]]),
+            VerificationError::StepTooLarge { step: 0, size: 2, max: 1 }
+        );
+    }
+}
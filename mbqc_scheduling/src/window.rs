@@ -0,0 +1,452 @@
+/*!
+Windowed scheduling for instances that are too large to be searched as a whole, cf.
+[interface::run_windowed](crate::interface::run_windowed).
+
+Instead of searching the whole instance at once, the [scheduler](schedule) repeatedly
+takes the next [layers](Window::layers) of the time ordering that still contain
+unmeasured nodes, searches the schedule of this window, [commits](Window::commit) the
+first steps of the best found path, and slides forward (a rolling horizon). The window
+is [restricted](Instance::restrict) to its nodes, their unmeasured neighbors, which are
+initialized when the window's nodes are measured, and the frontier, i.e., the nodes that
+are already in memory because of the committed steps. The frontier is carried over as
+the initial state of the window's search, so that its costs include the qubits that are
+still waiting in memory, cf. [Graph::with_initialized].
+
+Of the paths that the search finds for a window, the one with the smallest cost so far,
+that is, of the committed steps together with the path, is committed (ties are broken by
+the time). The dependencies on committed nodes whose latency has not passed yet (also
+the implied ones with a specific latency) become release times of the window; since
+release times also block the neighbors, this is a bit conservative. Similarly, deadlines
+are only seen when their node is in the window.
+
+The windows are much smaller than the whole instance, so this scales to instances with
+thousands of nodes, however, the committed steps cannot take the later layers into
+account, so the resulting path is not optimal in general. The larger the windows and
+the shorter the committed part, the better the path, but the longer the runtime.
+*/
+
+use std::{cmp, collections::HashMap};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    decompose::Instance,
+    interface::{Constraints, Path},
+    scheduler::{
+        space::{Graph, Objective, RefSpacialGraph, State},
+        time::RefPartialOrderGraph,
+        tree::Focus,
+    },
+    search::{self, Steps},
+    verify,
+};
+
+/// The configuration of the windows, cf. the [module](self) documentation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Window {
+    /// The number of layers of the time ordering in each window. Has to be at least 1.
+    pub layers: usize,
+    /// The number of steps that are committed from the path of each window (all steps
+    /// are committed in the last window). Has to be at least 1.
+    pub commit: usize,
+}
+
+/// Schedule the instance window by window, cf. the [module](self) documentation. The
+/// `search` gets the restricted instance of a window together with the nodes (in the
+/// restricted ids) that are already in memory, and returns the found paths, cf.
+/// [search::search_from]. Regarding the other parameters, cf.
+/// [interface::run](crate::interface::run).
+///
+/// Returns the verified path of the whole instance, or None if the search of a window
+/// finds no path or if the committed steps miss a deadline. Its
+/// [Optimality](crate::interface::Optimality) is
+/// [BestFound](crate::interface::Optimality::BestFound).
+///
+/// # Panics
+/// Panics if the `layers` or the `commit` of the `window` are 0.
+#[allow(clippy::too_many_arguments)]
+pub fn schedule(
+    spacial_graph: RefSpacialGraph,
+    weights: Option<&[usize]>,
+    outputs: &[usize],
+    time_ordering: RefPartialOrderGraph,
+    constraints: &Constraints,
+    objective: Objective,
+    window: Window,
+    mut search: impl FnMut(&Instance, &[usize]) -> Vec<Path>,
+) -> Option<Path> {
+    assert!(
+        window.layers > 0 && window.commit > 0,
+        "the window needs at least one layer and one committed step"
+    );
+    let num_bits = spacial_graph.len();
+    let all_outputs = search::all_outputs(num_bits, time_ordering, outputs);
+    let mut is_output = vec![false; num_bits];
+    for bit in all_outputs.iter() {
+        is_output[*bit] = true;
+    }
+    let latencies: HashMap<(usize, usize), usize> = constraints
+        .edge_latencies
+        .iter()
+        .map(|(dependency, dependent, latency)| ((*dependency, *dependent), *latency))
+        .collect();
+
+    // the state of the whole instance after the committed steps
+    let mut graph = Graph::new(spacial_graph)
+        .with_weights(weights)
+        .with_outputs(&all_outputs);
+    let mut measured_in = vec![None; num_bits];
+    let mut steps: Steps = Vec::new();
+    let mut first = 0;
+    loop {
+        let is_done = |bit: &usize| is_output[*bit] || measured_in[*bit].is_some();
+        while first < time_ordering.len()
+            && time_ordering[first].iter().map(|(bit, _)| bit).all(is_done)
+        {
+            first += 1;
+        }
+        if first == time_ordering.len() {
+            break;
+        }
+        let end = cmp::min(first + window.layers, time_ordering.len());
+        let offset = steps.len();
+
+        let mut in_window = vec![false; num_bits];
+        let mut release_times: Vec<(usize, usize)> = constraints
+            .release_times
+            .iter()
+            .filter(|(_, step)| *step > offset)
+            .map(|(bit, step)| (*bit, step - offset))
+            .collect();
+        let window_ordering: Vec<Vec<(usize, Vec<usize>)>> = time_ordering[first..end]
+            .iter()
+            .map(|layer| {
+                layer
+                    .iter()
+                    .filter(|(bit, _)| !is_done(bit))
+                    .map(|(bit, deps)| {
+                        in_window[*bit] = true;
+                        for dep in deps {
+                            let Some(step) = measured_in[*dep] else {
+                                continue;
+                            };
+                            let latency = latencies
+                                .get(&(*dep, *bit))
+                                .copied()
+                                .unwrap_or(constraints.latency);
+                            if step + 1 + latency > offset {
+                                release_times.push((*bit, step + 1 + latency - offset));
+                            }
+                        }
+                        let deps = deps
+                            .iter()
+                            .filter(|dep| measured_in[**dep].is_none())
+                            .copied()
+                            .collect();
+                        (*bit, deps)
+                    })
+                    .collect()
+            })
+            .collect();
+        for (dependency, dependent, latency) in constraints.edge_latencies.iter() {
+            let Some(step) = measured_in[*dependency] else {
+                continue;
+            };
+            if in_window[*dependent] && step + 1 + latency > offset {
+                release_times.push((*dependent, step + 1 + latency - offset));
+            }
+        }
+        let mut deadlines = Vec::new();
+        for (bit, step) in constraints.deadlines.iter() {
+            if in_window[*bit] {
+                deadlines.push((*bit, step.checked_sub(offset)?));
+            }
+        }
+        let window_constraints = Constraints {
+            max_step_size: constraints.max_step_size,
+            latency: constraints.latency,
+            edge_latencies: constraints.edge_latencies.clone(),
+            release_times,
+            deadlines,
+        };
+
+        let frontier: Vec<usize> = graph
+            .nodes()
+            .iter()
+            .enumerate()
+            .filter(|(_, (state, _))| *state == State::InMemory)
+            .map(|(bit, _)| bit)
+            .collect();
+        let mut nodes = frontier.clone();
+        for bit in (0..num_bits).filter(|bit| in_window[*bit]) {
+            nodes.push(bit);
+            nodes.extend(
+                spacial_graph[bit]
+                    .iter()
+                    .filter(|neighbor| measured_in[**neighbor].is_none()),
+            );
+        }
+        nodes.sort_unstable();
+        nodes.dedup();
+        let instance = Instance::restrict(
+            spacial_graph,
+            weights,
+            outputs,
+            &window_ordering,
+            &window_constraints,
+            &nodes,
+        );
+        let initialized: Vec<usize> = frontier
+            .iter()
+            .map(|bit| nodes.binary_search(bit).expect("the frontier is in the window"))
+            .collect();
+
+        let (_, best) = search(&instance, &initialized)
+            .into_iter()
+            .map(|path| {
+                let path = instance.to_original(path);
+                let mut state = graph.clone();
+                for step in path.steps.iter() {
+                    state.focus_inplace(step).expect("the steps come from the search");
+                }
+                ((state.cost(objective), path.time), path.steps)
+            })
+            .min_by_key(|(key, _)| *key)?;
+        let commit = if end == time_ordering.len() {
+            best.len()
+        } else {
+            window.commit
+        };
+        for step in best.into_iter().take(commit) {
+            graph.focus_inplace(&step).expect("the steps come from the search");
+            for bit in step.iter() {
+                measured_in[*bit] = Some(steps.len());
+            }
+            steps.push(step);
+        }
+    }
+
+    verify::verify(spacial_graph, weights, outputs, time_ordering, constraints, &steps)
+        .map_err(|e| tracing::info!("the windowed path is invalid: {e}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use pauli_tracker::tracker::frames::induced_order::PartialOrderGraph;
+    use rand::{Rng, SeedableRng};
+    use rand_pcg::Pcg64;
+
+    use super::*;
+    use crate::{
+        interface::{Limits, edges},
+        scheduler::space::SpacialGraph,
+    };
+
+    fn exact(objective: Objective) -> impl FnMut(&Instance, &[usize]) -> Vec<Path> {
+        move |instance, initialized| {
+            search::search_from(
+                &instance.spacial_graph,
+                instance.weights.as_deref(),
+                &instance.outputs,
+                &instance.time_ordering,
+                &Limits::default(),
+                1,
+                None,
+                100000,
+                &instance.constraints,
+                objective,
+                initialized,
+                None,
+            )
+            .0
+        }
+    }
+
+    // 0 - 1 - ... - 19, where each node depends on the previous one
+    fn chain() -> (SpacialGraph, PartialOrderGraph) {
+        let graph = (0..20)
+            .map(|i: usize| {
+                [i.checked_sub(1), Some(i + 1).filter(|j| *j < 20)]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            })
+            .collect();
+        let order = (0..20)
+            .map(|i: usize| vec![(i, i.checked_sub(1).into_iter().collect())])
+            .collect();
+        (graph, order)
+    }
+
+    #[test]
+    fn chain_with_latency() {
+        let (graph, order) = chain();
+        let window = Window { layers: 3, commit: 1 };
+        let path = schedule(
+            &graph,
+            None,
+            &[],
+            &order,
+            &Constraints::default(),
+            Objective::Space,
+            window,
+            exact(Objective::Space),
+        )
+        .unwrap();
+        assert_eq!((path.time, path.space), (20, 2));
+
+        // the latency of the committed nodes has to be respected in the next windows
+        let constraints = Constraints {
+            latency: 1,
+            ..Default::default()
+        };
+        let path = schedule(
+            &graph,
+            None,
+            &[],
+            &order,
+            &constraints,
+            Objective::Space,
+            window,
+            exact(Objective::Space),
+        )
+        .unwrap();
+        assert_eq!((path.time, path.space), (39, 2));
+    }
+
+    #[test]
+    fn whole_window() {
+        // 0 - 1 - 2
+        // |   |   |
+        // 3 - 4 - 5, with 4 and 5 depending on 0, and 2 depending on 4
+        let graph = vec![
+            vec![1, 3],
+            vec![0, 2, 4],
+            vec![1, 5],
+            vec![0, 4],
+            vec![1, 3, 5],
+            vec![2, 4],
+        ];
+        let order = vec![
+            vec![(0, vec![]), (1, vec![]), (3, vec![])],
+            vec![(4, vec![0]), (5, vec![0])],
+            vec![(2, vec![4])],
+        ];
+        for objective in [Objective::Space, Objective::Volume, Objective::Idle] {
+            let constraints = Constraints::default();
+            let (paths, _) = search::search(
+                &graph,
+                None,
+                &[],
+                &order,
+                &Limits::default(),
+                1,
+                None,
+                100000,
+                &constraints,
+                objective,
+                None,
+            );
+            let best = paths
+                .iter()
+                .map(|path| (path.cost(objective), path.time))
+                .min()
+                .unwrap();
+            let window = Window { layers: order.len(), commit: 1 };
+            let path = schedule(
+                &graph,
+                None,
+                &[],
+                &order,
+                &constraints,
+                objective,
+                window,
+                exact(objective),
+            )
+            .unwrap();
+            assert_eq!((path.cost(objective), path.time), best);
+        }
+    }
+
+    #[test]
+    fn random() {
+        let mut rng = Pcg64::seed_from_u64(42);
+        for _ in 0..200 {
+            let num_bits = rng.gen_range(1..=7);
+            let mut graph = vec![Vec::new(); num_bits];
+            let mut dependencies = Vec::new();
+            for i in 0..num_bits {
+                for j in i + 1..num_bits {
+                    if rng.gen_bool(0.35) {
+                        graph[i].push(j);
+                        graph[j].push(i);
+                    }
+                    if rng.gen_bool(0.25) {
+                        dependencies.push((i, j));
+                    }
+                }
+            }
+            let order = edges::get_order(num_bits, &dependencies).unwrap();
+            let weights = rng
+                .gen_bool(0.5)
+                .then(|| (0..num_bits).map(|_| rng.gen_range(0..=2)).collect::<Vec<_>>());
+            let release_times = (0..num_bits)
+                .filter(|_| rng.gen_bool(0.2))
+                .collect::<Vec<_>>()
+                .into_iter()
+                .map(|bit| (bit, rng.gen_range(1..4)))
+                .collect();
+            let constraints = Constraints {
+                max_step_size: rng.gen_bool(0.3).then_some(2),
+                latency: rng.gen_range(0..2),
+                edge_latencies: dependencies
+                    .iter()
+                    .filter(|_| rng.gen_bool(0.2))
+                    .map(|(dependency, dependent)| (*dependency, *dependent, 2))
+                    .collect(),
+                release_times,
+                deadlines: Vec::new(),
+            };
+
+            for objective in [Objective::Space, Objective::Volume, Objective::Idle] {
+                let mut exact = exact(objective);
+                let best = search::search(
+                    &graph,
+                    weights.as_deref(),
+                    &[],
+                    &order,
+                    &Limits::default(),
+                    1,
+                    None,
+                    100000,
+                    &constraints,
+                    objective,
+                    None,
+                )
+                .0
+                .iter()
+                .map(|path| (path.cost(objective), path.time))
+                .min();
+                let mut windowed = |layers| {
+                    schedule(
+                        &graph,
+                        weights.as_deref(),
+                        &[],
+                        &order,
+                        &constraints,
+                        objective,
+                        Window { layers, commit: 1 },
+                        &mut exact,
+                    )
+                    .map(|path| (path.cost(objective), path.time))
+                };
+                let info = format!("{graph:?}, {weights:?}, {order:?}, {constraints:?}");
+                // with only one window, we get the best path of the whole search
+                assert_eq!(windowed(order.len().max(1)), best, "{info}");
+                // without deadlines, there is always a path
+                assert_eq!(windowed(1).is_some(), best.is_some(), "{info}");
+            }
+        }
+    }
+}
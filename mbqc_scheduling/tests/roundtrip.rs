@@ -4,10 +4,10 @@ use std::{cmp, mem, thread};
 
 use hashbrown::HashSet;
 use mbqc_scheduling::scheduler::{
+    Scheduler,
     space::{Graph, GraphBuffer},
     time::{DependencyBuffer, Partitioner, PathGenerator},
     tree::{Focus, FocusIterator, Step},
-    Scheduler,
 };
 use proptest::strategy::Just;
 
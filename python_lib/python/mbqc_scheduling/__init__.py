@@ -5,4 +5,14 @@ Wrapper around the essential functionality  the `mbqc_scheduling crate`_.
    https://github.com/taeruh/mbqc_scheduling/blob/main/mbqc_scheduling
 """
 
-from mbqc_scheduling._lib import SpacialGraph, PartialOrderGraph, Paths, Path, run
+from mbqc_scheduling._lib import (
+    SpacialGraph,
+    PartialOrderGraph,
+    Paths,
+    Path,
+    SearchStats,
+    run,
+    run_with_stats,
+    run_from_frames,
+    run_labeled,
+)
@@ -1,10 +1,10 @@
-use std::{ffi::CString, mem, time::Duration};
+use std::{ffi::CString, fmt, mem, time::Duration};
 
 use lib::interface::{self};
 use pauli_tracker_pyo3::{Module, frames::PartialOrderGraph};
 use probabilistic::AcceptFunc;
 use pyo3::{
-    Bound, PyAny, PyErr, PyRef, PyResult, Python,
+    Bound, FromPyObject, IntoPyObject, PyAny, PyErr, PyRef, PyResult, Python,
     exceptions::{PyValueError, PyWarning},
     types::{PyAnyMethods, PyModule, PyModuleMethods},
 };
@@ -52,7 +52,7 @@ impl SpacialGraph {
 
 pauli_tracker_pyo3::serde!(SpacialGraph);
 
-#[pyo3::pyclass(subclass,from_py_object)]
+#[pyo3::pyclass(subclass, from_py_object)]
 /// Opaque Rust object. The information returned from the scheduling algorithm (`run`)
 /// describing valid initalization-measurement paths.
 ///
@@ -69,16 +69,36 @@ pub struct Paths(pub Vec<interface::Path>);
 #[pyo3::pymethods]
 impl Paths {
     #[new]
-    fn __new__(paths: Vec<Path>) -> Self {
-        Self(
-            paths
-                .into_iter()
-                .map(|Path { time, space, steps }| interface::Path { time, space, steps })
-                .collect(),
-        )
+    fn __new__(paths: Vec<Path>) -> PyResult<Self> {
+        paths
+            .into_iter()
+            .map(
+                |Path {
+                     time,
+                     space,
+                     volume,
+                     idle,
+                     optimality,
+                     lower_bound,
+                     steps,
+                 }| {
+                    Ok(interface::Path {
+                        time,
+                        space,
+                        volume,
+                        idle,
+                        optimality: to_optimality(&optimality, lower_bound)?,
+                        steps: steps
+                            .into_iter()
+                            .map(|step| step.into_iter().map(Label::into_id).collect())
+                            .collect::<PyResult<_>>()?,
+                    })
+                },
+            )
+            .collect::<PyResult<_>>()
+            .map(Self)
     }
 
-
     #[doc = pauli_tracker_pyo3::transform!()]
     ///
     /// Returns:
@@ -102,14 +122,97 @@ impl Paths {
     fn transformation(paths: Vec<interface::Path>) -> Vec<Path> {
         paths
             .into_iter()
-            .map(|interface::Path { time, space, steps }| Path { time, space, steps })
+            .map(|path| Path::from(path.map_nodes(Label::Int)))
             .collect()
     }
 }
 
+/// A node label, i.e., an int or a str.
+#[derive(
+    Clone, PartialEq, Eq, Hash, FromPyObject, IntoPyObject, Serialize, Deserialize,
+)]
+#[serde(untagged)]
+pub enum Label {
+    Int(usize),
+    Str(String),
+}
+
+impl Label {
+    fn into_id(self) -> PyResult<usize> {
+        match self {
+            Label::Int(id) => Ok(id),
+            Label::Str(name) => Err(PyValueError::new_err(format!(
+                "the label {name:?} is not a node id; only paths with int steps can be \
+                 converted into Paths"
+            ))),
+        }
+    }
+}
+
+impl fmt::Display for Label {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Label::Int(id) => write!(f, "{id}"),
+            Label::Str(name) => write!(f, "{name:?}"),
+        }
+    }
+}
+
+impl From<interface::Path<Label>> for Path {
+    fn from(
+        interface::Path {
+            time,
+            space,
+            volume,
+            idle,
+            optimality,
+            steps,
+        }: interface::Path<Label>,
+    ) -> Self {
+        let (optimality, lower_bound) = from_optimality(optimality);
+        Self {
+            time,
+            space,
+            volume,
+            idle,
+            optimality,
+            lower_bound,
+            steps,
+        }
+    }
+}
+
+fn to_optimality(
+    optimality: &str,
+    lower_bound: Option<usize>,
+) -> PyResult<interface::Optimality> {
+    match (optimality, lower_bound) {
+        ("optimal", _) => Ok(interface::Optimality::Optimal),
+        ("best_found", _) => Ok(interface::Optimality::BestFound),
+        ("gap", Some(lower_bound)) => Ok(interface::Optimality::Gap { lower_bound }),
+        ("gap", None) => {
+            Err(PyValueError::new_err("the 'gap' status needs a lower_bound"))
+        },
+        _ => Err(PyValueError::new_err(format!(
+            "unknown optimality: {optimality}; expected one of 'optimal', 'best_found' \
+             and 'gap'"
+        ))),
+    }
+}
+
+fn from_optimality(optimality: interface::Optimality) -> (String, Option<usize>) {
+    match optimality {
+        interface::Optimality::Optimal => ("optimal".to_string(), None),
+        interface::Optimality::BestFound => ("best_found".to_string(), None),
+        interface::Optimality::Gap { lower_bound } => {
+            ("gap".to_string(), Some(lower_bound))
+        },
+    }
+}
+
 pauli_tracker_pyo3::serde!(Paths);
 
-#[pyo3::pyclass(subclass,from_py_object)]
+#[pyo3::pyclass(subclass, from_py_object)]
 /// The information describing a valid initalization-measurement path.
 ///
 /// **Constructor:**
@@ -117,7 +220,11 @@ pauli_tracker_pyo3::serde!(Paths);
 /// Args:
 ///     time (int): :attr:`time`
 ///     space (int): :attr:`space`
-///     steps (list[list[int]]): :attr:`steps`
+///     steps (list[list[int | str]]): :attr:`steps`
+///     volume (int): :attr:`volume`
+///     idle (int): :attr:`idle`
+///     optimality (str): :attr:`optimality`
+///     lower_bound (Optional[int]): :attr:`lower_bound`
 ///
 /// Returns:
 ///     Path:
@@ -132,21 +239,164 @@ pub struct Path {
     /// certain point in time.
     pub space: usize,
     #[pyo3(get)]
-    /// The measurement pattern, consisting of a list of parallel measurement steps.
-    pub steps: Vec<Vec<usize>>,
+    /// The space-time volume, i.e., the sum of the number of qubits in memory over all
+    /// :attr:`steps`.
+    #[serde(default)]
+    pub volume: usize,
+    #[pyo3(get)]
+    /// The qubit idle time, i.e., the sum over all qubits of the number of steps in
+    /// which they have been in memory without being measured.
+    #[serde(default)]
+    pub idle: usize,
+    #[pyo3(get)]
+    /// Whether the path is proven to be optimal (with respect to the objective of the
+    /// search), i.e., whether there is no path with the same or a smaller time cost that
+    /// has a smaller cost; one of "optimal", "best_found" (not known whether it is
+    /// optimal) and "gap" (not known whether it is optimal, but the optimal cost is at
+    /// least :attr:`lower_bound`).
+    #[serde(default = "best_found")]
+    pub optimality: String,
+    #[pyo3(get)]
+    /// The lower bound on the optimal cost if :attr:`optimality` is "gap", otherwise
+    /// None.
+    #[serde(default)]
+    pub lower_bound: Option<usize>,
+    #[pyo3(get)]
+    /// The measurement pattern, consisting of a list of parallel measurement steps. The
+    /// nodes are ints, except for paths from :func:`run_labeled`, where they are the
+    /// labels.
+    pub steps: Vec<Vec<Label>>,
+}
+
+fn best_found() -> String {
+    "best_found".to_string()
 }
 
 #[pyo3::pymethods]
 impl Path {
     #[new]
-    fn __new__(time: usize, space: usize, steps: Vec<Vec<usize>>) -> Self {
-        Self { time, space, steps }
+    #[pyo3(signature = (
+        time,
+        space,
+        steps,
+        volume=0,
+        idle=0,
+        optimality="best_found",
+        lower_bound=None,
+    ))]
+    fn __new__(
+        time: usize,
+        space: usize,
+        steps: Vec<Vec<Label>>,
+        volume: usize,
+        idle: usize,
+        optimality: &str,
+        lower_bound: Option<usize>,
+    ) -> PyResult<Self> {
+        // validate it
+        let (optimality, lower_bound) =
+            from_optimality(to_optimality(optimality, lower_bound)?);
+        Ok(Self {
+            time,
+            space,
+            volume,
+            idle,
+            optimality,
+            lower_bound,
+            steps,
+        })
     }
-
 }
 
 pauli_tracker_pyo3::serde!(Path, plain);
 
+#[pyo3::pyclass(subclass, from_py_object)]
+/// Statistics about the search, e.g., to tune the parameters of the
+/// :class:`~mbqc_scheduling.probabilistic.AcceptFunc`.
+///
+/// A forward step in the search tree is :attr:`explored` and then either :attr:`pruned`,
+/// because it cannot lead to a better path than one already found, :attr:`rejected` by
+/// the probabilistic accept function, or :attr:`accepted`.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SearchStats {
+    #[pyo3(get)]
+    /// The number of forward steps (nodes in the search tree) that have been explored.
+    pub explored: u64,
+    #[pyo3(get)]
+    /// The number of explored steps that have been skipped because of the bound given by
+    /// the best paths found so far.
+    pub pruned: u64,
+    #[pyo3(get)]
+    /// The number of explored steps that have been skipped by the probabilistic accept
+    /// function.
+    pub rejected: u64,
+    #[pyo3(get)]
+    /// The number of explored steps that have been accepted, i.e., the search went into
+    /// them.
+    pub accepted: u64,
+    #[pyo3(get)]
+    /// The number of complete paths that have been found.
+    pub solutions: u64,
+    #[pyo3(get)]
+    /// The maximum depth, i.e., path length, that the search has reached.
+    pub max_depth: usize,
+    #[pyo3(get)]
+    /// Whether the search has been stopped by one of the limits, e.g., the timeout, i.e.,
+    /// it did not finish.
+    pub timed_out: bool,
+    #[pyo3(get)]
+    /// Which limit stopped the search, if :attr:`timed_out`; one of "timeout", "stall",
+    /// "no_solution", "node_budget", "stopped" and "interrupted".
+    pub stop_reason: Option<String>,
+    #[pyo3(get)]
+    /// Whether the search did not find any path before it was stopped, so that the time
+    /// optimal path has been taken instead.
+    pub fallback: bool,
+    #[pyo3(get)]
+    /// The seed of the random number generator, if the search was probabilistic; if no
+    /// seed was given, this is the randomly drawn one.
+    pub seed: Option<u64>,
+    #[pyo3(get)]
+    /// Whether the search has been stopped early because a path reaching the lower
+    /// bounds on the time and the cost has been found.
+    pub bound_reached: bool,
+    #[pyo3(get)]
+    /// If the search was multi-threaded, the statistics of the single threads;
+    /// otherwise empty.
+    pub threads: Vec<SearchStats>,
+}
+
+impl From<interface::SearchStats> for SearchStats {
+    fn from(stats: interface::SearchStats) -> Self {
+        Self {
+            explored: stats.explored,
+            pruned: stats.pruned,
+            rejected: stats.rejected,
+            accepted: stats.accepted,
+            solutions: stats.solutions,
+            max_depth: stats.max_depth,
+            timed_out: stats.timed_out,
+            stop_reason: stats.stop_reason.map(|reason| {
+                match reason {
+                    interface::StopReason::Timeout => "timeout",
+                    interface::StopReason::Stall => "stall",
+                    interface::StopReason::NoSolution => "no_solution",
+                    interface::StopReason::NodeBudget => "node_budget",
+                    interface::StopReason::Stopped => "stopped",
+                    interface::StopReason::Interrupted => "interrupted",
+                }
+                .to_string()
+            }),
+            fallback: stats.fallback,
+            seed: stats.seed,
+            bound_reached: stats.bound_reached,
+            threads: stats.threads.into_iter().map(Self::from).collect(),
+        }
+    }
+}
+
+pauli_tracker_pyo3::serde!(SearchStats, plain);
+
 /// Search for optimal initalization-measurement paths.
 ///
 /// Note that the algorithm always first tries the more time optimal patterns, however,
@@ -163,10 +413,9 @@ pauli_tracker_pyo3::serde!(Path, plain);
 ///     timeout (Optional[int]): A timeout for the search. You'll probably want to set
 ///         this, because if the run is cancelled by some other reason, the results are
 ///         generally lost, but when the run cancelled because of a timeout, the function
-///         returns as normally with the results obtained so far. However, note that is
-///         timeout is too short, i.e., shorter than how long it would take to get the
-///         first path (which depends potentially `probabilistic`), then the function will
-///         return an empty list.
+///         returns as normally with the results obtained so far. If the search is
+///         stopped before it has found any path, the time optimal path is returned
+///         instead.
 ///     nthreads (int): The number of threads to use for the search. If `nthreads` is
 ///         below 3, it will not multithread. Otherwise it will start a threadpool
 ///         (where one thread is used to manage shared data). The tasks for the
@@ -180,14 +429,44 @@ pauli_tracker_pyo3::serde!(Path, plain);
 ///         deterministic. For larger problems, you will want to do it probabilistically,
 ///         with a relatively low accept rate, because otherwise it takes forever (scaling
 ///         is in the worst something between factorial and double exponential). The
-///         second tuple element is an optional seed for the random number generator.
-///         However, note that if multithreaded, i.e., `nthreads > 1`, fixing the seed
-///         does not ensure reproducibibility (the threads communicate the results with
+///         second tuple element is an optional seed for the random number generator;
+///         if None, a random seed is drawn, cf. :attr:`SearchStats.seed`. However, note that if multithreaded, i.e., `nthreads > 1`, fixing the seed
+///         does not ensure reproducibility (the threads communicate the results with
 ///         each other, and depending on that they adjust the search; this communication
 ///         is not deterministic (on this level here) since it depends on how the threads
 ///         are scheduled).
 ///     task_bound (int): The maximum number of tasks to start in the search, cf.
 ///         `nthreads`.
+///     max_step_size (Optional[int]): The maximum number of qubits that can be measured
+///         in parallel in one step. If None, the steps are not bounded.
+///     latency (int): The classical feed-forward latency, i.e., the number of additional
+///         steps that have to pass after measuring a qubit before its dependents can be
+///         measured.
+///     edge_latencies (Optional[list[tuple[int, int, int]]]): Latencies for specific
+///         dependency edges, given as `(dependency, dependent, latency)`, overwriting
+///         `latency` for these edges.
+///     release_times (Optional[list[tuple[int, int]]]): Release times, given as
+///         `(node, step)`, i.e., the node and its neighbors (since they initialize the
+///         node) cannot be measured before `step` (counted from 0).
+///     deadlines (Optional[list[tuple[int, int]]]): Deadlines, given as `(node, step)`,
+///         i.e., the node has to be measured in `step` (counted from 0) at the latest.
+///         If the deadlines cannot be met, no path is returned.
+///     weights (Optional[list[int]]): Memory weights for the nodes in the
+///         `spacial_graph`, i.e., how many memory slots a node occupies. If None, every
+///         node occupies one slot.
+///     outputs (Optional[list[int]]): The output nodes, which are never measured, but
+///         which are initialized as usual and then stay in memory until the end. Nodes
+///         that are not in `time_order` are treated as outputs, too.
+///     objective (str): The cost that is optimized, besides the time, when searching;
+///         one of "space" (the maximum memory), "volume" (the space-time volume) and
+///         "idle" (the qubit idle time).
+///     stall_timeout (Optional[int]): Stop the search if no better path has been found
+///         for that many seconds.
+///     first_solution_timeout (Optional[int]): Stop the search if no path has been
+///         found after that many seconds (then the time optimal path is returned).
+///     node_budget (Optional[int]): Stop the search after exploring that many nodes in
+///         the search tree; in contrast to the timeouts, this does not depend on the
+///         speed of the machine.
 ///
 /// Returns:
 ///     Paths: A list of the optimal paths. Turn it into the corresponding Python object
@@ -206,9 +485,91 @@ pauli_tracker_pyo3::serde!(Path, plain);
     nthreads=1,
     probabilistic=None,
     task_bound=None,
+    max_step_size=None,
+    latency=0,
+    edge_latencies=None,
+    release_times=None,
+    deadlines=None,
+    weights=None,
+    outputs=None,
+    objective="space",
+    stall_timeout=None,
+    first_solution_timeout=None,
+    node_budget=None,
 ))]
 #[allow(clippy::too_many_arguments)]
 fn run(
+    spacial_graph: &SpacialGraph,
+    // cf. run_with_stats
+    time_order: Bound<'_, PyAny>,
+    do_search: bool,
+    timeout: Option<u32>,
+    nthreads: u16,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    task_bound: Option<u32>,
+    max_step_size: Option<usize>,
+    latency: usize,
+    edge_latencies: Option<Vec<(usize, usize, usize)>>,
+    release_times: Option<Vec<(usize, usize)>>,
+    deadlines: Option<Vec<(usize, usize)>>,
+    weights: Option<Vec<usize>>,
+    outputs: Option<Vec<usize>>,
+    objective: &str,
+    stall_timeout: Option<u32>,
+    first_solution_timeout: Option<u32>,
+    node_budget: Option<u64>,
+) -> PyResult<Paths> {
+    run_with_stats(
+        spacial_graph,
+        time_order,
+        do_search,
+        timeout,
+        nthreads,
+        probabilistic,
+        task_bound,
+        max_step_size,
+        latency,
+        edge_latencies,
+        release_times,
+        deadlines,
+        weights,
+        outputs,
+        objective,
+        stall_timeout,
+        first_solution_timeout,
+        node_budget,
+    )
+    .map(|(paths, _)| paths)
+}
+
+/// Same as :func:`run`, but additionally returns statistics about the search (if
+/// `do_search` is True, otherwise None).
+///
+/// Returns:
+///     tuple[Paths, Optional[SearchStats]]:
+#[pyo3::pyfunction]
+#[pyo3(signature = (
+    spacial_graph,
+    time_order,
+    do_search=false,
+    timeout=None,
+    nthreads=1,
+    probabilistic=None,
+    task_bound=None,
+    max_step_size=None,
+    latency=0,
+    edge_latencies=None,
+    release_times=None,
+    deadlines=None,
+    weights=None,
+    outputs=None,
+    objective="space",
+    stall_timeout=None,
+    first_solution_timeout=None,
+    node_budget=None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn run_with_stats(
     spacial_graph: &SpacialGraph,
     // cf. https://github.com/PyO3/pyo3/issues/1444: when the PartialOrderGraph comes
     // directly from pauli_tracker, it errors because they are different python types
@@ -221,18 +582,18 @@ fn run(
     nthreads: u16,
     probabilistic: Option<(AcceptFunc, Option<u64>)>,
     task_bound: Option<u32>,
-) -> PyResult<Paths> {
-    // GIL problems ... (it completely locks the execution)
-    if let Some((AcceptFunc(AcceptFuncBase::Custom(_)), _)) = probabilistic {
-        if nthreads > 1 {
-            return Err(PyValueError::new_err(
-                r"
-    multi-threading with a custom Python callback is not supported; set `nthreads=1` or do
-    not use a custom callback",
-            ));
-        }
-    }
-
+    max_step_size: Option<usize>,
+    latency: usize,
+    edge_latencies: Option<Vec<(usize, usize, usize)>>,
+    release_times: Option<Vec<(usize, usize)>>,
+    deadlines: Option<Vec<(usize, usize)>>,
+    weights: Option<Vec<usize>>,
+    outputs: Option<Vec<usize>>,
+    objective: &str,
+    stall_timeout: Option<u32>,
+    first_solution_timeout: Option<u32>,
+    node_budget: Option<u64>,
+) -> PyResult<(Paths, Option<SearchStats>)> {
     let mut _cloned: Vec<Vec<(usize, Vec<usize>)>>;
     let mut _by_ref: PyRef<'_, PartialOrderGraph>;
     let time_order = if time_order.is_instance_of::<PartialOrderGraph>() {
@@ -261,15 +622,336 @@ fn run(
         &_cloned
     };
 
-    Ok(Paths(interface::run(
+    search(
+        spacial_graph,
+        time_order,
+        do_search,
+        timeout,
+        nthreads,
+        probabilistic,
+        task_bound,
+        max_step_size,
+        latency,
+        edge_latencies,
+        release_times,
+        deadlines,
+        weights,
+        outputs,
+        objective,
+        stall_timeout,
+        first_solution_timeout,
+        node_budget,
+    )
+}
+
+// the actual work of run_with_stats and run_from_frames, once we have the time order
+#[allow(clippy::too_many_arguments)]
+fn search(
+    spacial_graph: &SpacialGraph,
+    time_order: &[Vec<(usize, Vec<usize>)>],
+    do_search: bool,
+    timeout: Option<u32>,
+    nthreads: u16,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    task_bound: Option<u32>,
+    max_step_size: Option<usize>,
+    latency: usize,
+    edge_latencies: Option<Vec<(usize, usize, usize)>>,
+    release_times: Option<Vec<(usize, usize)>>,
+    deadlines: Option<Vec<(usize, usize)>>,
+    weights: Option<Vec<usize>>,
+    outputs: Option<Vec<usize>>,
+    objective: &str,
+    stall_timeout: Option<u32>,
+    first_solution_timeout: Option<u32>,
+    node_budget: Option<u64>,
+) -> PyResult<(Paths, Option<SearchStats>)> {
+    check_arguments(
+        &probabilistic,
+        nthreads,
+        max_step_size,
+        weights.as_deref(),
+        spacial_graph.0.len(),
+    )?;
+
+    let outputs = outputs.unwrap_or_default();
+    if let Some(output) = outputs.iter().find(|output| **output >= spacial_graph.0.len())
+    {
+        return Err(PyValueError::new_err(format!(
+            "the output {output} is not a node of the spacial graph"
+        )));
+    }
+
+    let objective = to_objective(objective)?;
+
+    let seconds = |t: Option<u32>| t.map(|t| Duration::from_secs(t.into()));
+    let (paths, stats) = interface::run_with_stats(
         &spacial_graph.0,
+        weights.as_deref(),
+        &outputs,
         time_order,
         do_search,
-        timeout.map(|t| Duration::from_secs(t.into())),
+        interface::Limits {
+            timeout: seconds(timeout),
+            stall_timeout: seconds(stall_timeout),
+            first_solution_timeout: seconds(first_solution_timeout),
+            node_budget,
+            ..Default::default()
+        },
         nthreads,
         task_bound,
         probabilistic.map(|(func, seed)| (func.to_real(), seed)),
-    )))
+        &interface::Constraints {
+            max_step_size,
+            latency,
+            edge_latencies: edge_latencies.unwrap_or_default(),
+            release_times: release_times.unwrap_or_default(),
+            deadlines: deadlines.unwrap_or_default(),
+        },
+        objective,
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((Paths(paths), stats.map(SearchStats::from)))
+}
+
+/// Same as :func:`run_with_stats`, but instead of the `time_order`, the time order is
+/// computed from the frames of a Pauli tracker.
+///
+/// The other arguments are the same as for :func:`run`.
+///
+/// Args:
+///     frames (str): The path to a file with the serialized `Frames`_ of the
+///         `pauli_tracker`_ package (with the map storage), or only their storage.
+///     frame_map (list[int]): The qubits whose measurements induced the frames, i.e.,
+///         frame `i` belongs to the measurement of qubit `frame_map[i]`.
+///     frames_format (str): The serialization format of the `frames` file; "auto"
+///         detects it from the file extension (default is JSON).
+///
+/// Returns:
+///     tuple[Paths, Optional[SearchStats]]:
+///
+/// Raises:
+///     ValueError: If the frames cannot be read or if they are not valid, e.g., if the
+///         induced dependencies are cyclic.
+#[pyo3::pyfunction]
+#[pyo3(signature = (
+    spacial_graph,
+    frames,
+    frame_map,
+    frames_format="auto",
+    do_search=false,
+    timeout=None,
+    nthreads=1,
+    probabilistic=None,
+    task_bound=None,
+    max_step_size=None,
+    latency=0,
+    edge_latencies=None,
+    release_times=None,
+    deadlines=None,
+    weights=None,
+    outputs=None,
+    objective="space",
+    stall_timeout=None,
+    first_solution_timeout=None,
+    node_budget=None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn run_from_frames(
+    spacial_graph: &SpacialGraph,
+    frames: &str,
+    frame_map: Vec<usize>,
+    frames_format: &str,
+    do_search: bool,
+    timeout: Option<u32>,
+    nthreads: u16,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    task_bound: Option<u32>,
+    max_step_size: Option<usize>,
+    latency: usize,
+    edge_latencies: Option<Vec<(usize, usize, usize)>>,
+    release_times: Option<Vec<(usize, usize)>>,
+    deadlines: Option<Vec<(usize, usize)>>,
+    weights: Option<Vec<usize>>,
+    outputs: Option<Vec<usize>>,
+    objective: &str,
+    stall_timeout: Option<u32>,
+    first_solution_timeout: Option<u32>,
+    node_budget: Option<u64>,
+) -> PyResult<(Paths, Option<SearchStats>)> {
+    let storage = interface::read_frames(frames, frames_format)
+        .map_err(|e| PyValueError::new_err(format!("cannot read the frames: {e}")))?;
+    let time_order = interface::frames::get_order(
+        storage.iter().map(|(qubit, stack)| (*qubit, stack)),
+        &frame_map,
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    search(
+        spacial_graph,
+        &time_order,
+        do_search,
+        timeout,
+        nthreads,
+        probabilistic,
+        task_bound,
+        max_step_size,
+        latency,
+        edge_latencies,
+        release_times,
+        deadlines,
+        weights,
+        outputs,
+        objective,
+        stall_timeout,
+        first_solution_timeout,
+        node_budget,
+    )
+}
+
+/// Same as :func:`run_with_stats`, but the nodes can have arbitrary labels, i.e., ints
+/// or strs, instead of being numbered from 0.
+///
+/// The `spacial_graph` is a list of the nodes together with their neighbors, and the
+/// `weights` are given in the same order. All the other arguments refer to the nodes by
+/// their labels, and the steps of the returned paths consist of the labels, too. The
+/// other arguments are the same as for :func:`run`.
+///
+/// Args:
+///     spacial_graph (list[tuple[int | str, list[int | str]]]): The labeled spacial
+///         graph.
+///     time_order (list[list[tuple[int | str, list[int | str]]]]): The labeled
+///         dependency graph, i.e., a list of layers, where each layer is a list of the
+///         nodes together with their dependencies.
+///
+/// Returns:
+///     tuple[list[Path], Optional[SearchStats]]:
+#[pyo3::pyfunction]
+#[pyo3(signature = (
+    spacial_graph,
+    time_order,
+    do_search=false,
+    timeout=None,
+    nthreads=1,
+    probabilistic=None,
+    task_bound=None,
+    max_step_size=None,
+    latency=0,
+    edge_latencies=None,
+    release_times=None,
+    deadlines=None,
+    weights=None,
+    outputs=None,
+    objective="space",
+    stall_timeout=None,
+    first_solution_timeout=None,
+    node_budget=None,
+))]
+#[allow(clippy::too_many_arguments)]
+fn run_labeled(
+    spacial_graph: Vec<(Label, Vec<Label>)>,
+    time_order: Vec<Vec<(Label, Vec<Label>)>>,
+    do_search: bool,
+    timeout: Option<u32>,
+    nthreads: u16,
+    probabilistic: Option<(AcceptFunc, Option<u64>)>,
+    task_bound: Option<u32>,
+    max_step_size: Option<usize>,
+    latency: usize,
+    edge_latencies: Option<Vec<(Label, Label, usize)>>,
+    release_times: Option<Vec<(Label, usize)>>,
+    deadlines: Option<Vec<(Label, usize)>>,
+    weights: Option<Vec<usize>>,
+    outputs: Option<Vec<Label>>,
+    objective: &str,
+    stall_timeout: Option<u32>,
+    first_solution_timeout: Option<u32>,
+    node_budget: Option<u64>,
+) -> PyResult<(Vec<Path>, Option<SearchStats>)> {
+    check_arguments(
+        &probabilistic,
+        nthreads,
+        max_step_size,
+        weights.as_deref(),
+        spacial_graph.len(),
+    )?;
+    let objective = to_objective(objective)?;
+
+    let seconds = |t: Option<u32>| t.map(|t| Duration::from_secs(t.into()));
+    let (paths, metadata) = interface::run_labeled(
+        &spacial_graph,
+        weights.as_deref(),
+        &outputs.unwrap_or_default(),
+        &time_order,
+        do_search,
+        interface::Limits {
+            timeout: seconds(timeout),
+            stall_timeout: seconds(stall_timeout),
+            first_solution_timeout: seconds(first_solution_timeout),
+            node_budget,
+            ..Default::default()
+        },
+        nthreads,
+        task_bound,
+        probabilistic.map(|(func, seed)| (func.to_real(), seed)),
+        &interface::Constraints {
+            max_step_size,
+            latency,
+            edge_latencies: edge_latencies.unwrap_or_default(),
+            release_times: release_times.unwrap_or_default(),
+            deadlines: deadlines.unwrap_or_default(),
+        },
+        objective,
+    )
+    .map_err(|e| PyValueError::new_err(e.to_string()))?;
+    Ok((
+        paths.into_iter().map(Path::from).collect(),
+        metadata.stats.map(SearchStats::from),
+    ))
+}
+
+// the checks that are shared by search and run_labeled
+fn check_arguments(
+    probabilistic: &Option<(AcceptFunc, Option<u64>)>,
+    nthreads: u16,
+    max_step_size: Option<usize>,
+    weights: Option<&[usize]>,
+    num_nodes: usize,
+) -> PyResult<()> {
+    // GIL problems ... (it completely locks the execution)
+    if let Some((AcceptFunc(AcceptFuncBase::Custom(_)), _)) = probabilistic {
+        if nthreads > 1 {
+            return Err(PyValueError::new_err(
+                r"
+    multi-threading with a custom Python callback is not supported; set `nthreads=1` or do
+    not use a custom callback",
+            ));
+        }
+    }
+
+    if max_step_size == Some(0) {
+        return Err(PyValueError::new_err("max_step_size has to be at least 1"));
+    }
+
+    if let Some(weights) = weights {
+        if weights.len() != num_nodes {
+            return Err(PyValueError::new_err(
+                "the number of weights does not match the number of nodes",
+            ));
+        }
+    }
+    Ok(())
+}
+
+fn to_objective(objective: &str) -> PyResult<interface::Objective> {
+    match objective {
+        "space" => Ok(interface::Objective::Space),
+        "volume" => Ok(interface::Objective::Volume),
+        "idle" => Ok(interface::Objective::Idle),
+        _ => Err(PyValueError::new_err(format!(
+            "unknown objective: {objective}; expected one of 'space', 'volume' and \
+             'idle'"
+        ))),
+    }
 }
 
 mod probabilistic;
@@ -284,9 +966,19 @@ fn _lib(py: Python, module: Bound<'_, PyModule>) -> PyResult<()> {
     module.pymodule.add_class::<PartialOrderGraph>()?;
     module.pymodule.add_class::<Paths>()?;
     module.pymodule.add_class::<Path>()?;
+    module.pymodule.add_class::<SearchStats>()?;
     module
         .pymodule
         .add_function(pyo3::wrap_pyfunction!(run, &module.pymodule)?)?;
+    module
+        .pymodule
+        .add_function(pyo3::wrap_pyfunction!(run_with_stats, &module.pymodule)?)?;
+    module
+        .pymodule
+        .add_function(pyo3::wrap_pyfunction!(run_from_frames, &module.pymodule)?)?;
+    module
+        .pymodule
+        .add_function(pyo3::wrap_pyfunction!(run_labeled, &module.pymodule)?)?;
     probabilistic::add_module(py, &module)?;
     Ok(())
 }
@@ -9,7 +9,7 @@ use crate::Module;
 //                          exp_num_total_nodes_exp, exp_num_remaining_nodes_exp, \
 //                          exp_diff_exp, exp_num_measured_nodes_exp)")]
 
-#[pyo3::pyclass(subclass,from_py_object)]
+#[pyo3::pyclass(subclass, from_py_object)]
 /// **Constructor:**
 /// Args:
 ///     cutoff (float)
@@ -53,7 +53,7 @@ pub enum AcceptFuncBase {
     Custom(Py<PyAny>),
 }
 
-#[pyo3::pyclass(subclass,from_py_object)]
+#[pyo3::pyclass(subclass, from_py_object)]
 /// Compare the corresponding documentation in the `mbqc_scheduling crate
 /// <https://github.com/taeruh/mbqc_scheduling/tree/main/mbqc_scheduling>`_.
 ///
@@ -1,7 +1,10 @@
 use std::time::{Duration, Instant};
 
 use bitvec::vec::BitVec;
-use mbqc_scheduling::{interface, probabilistic::AcceptFunc};
+use mbqc_scheduling::{
+    interface::{self, Constraints, Objective},
+    probabilistic::AcceptFunc,
+};
 use pauli_tracker::{
     collection::{Base, Iterable, NaiveVector},
     pauli::PauliStack,
@@ -43,22 +46,53 @@ fn do_it(
         let order = get_order(correction_density, size, rng);
 
         let mut time = Instant::now();
-        let time_optimal = interface::run(&graph, &order, false, None, 1, None, None);
+        let time_optimal = interface::run(
+            &graph,
+            None,
+            &[],
+            &order,
+            false,
+            None,
+            1,
+            None,
+            None,
+            &Constraints::default(),
+            Objective::Space,
+        )
+        .unwrap();
         time_optimal_time += time.elapsed();
         time = Instant::now();
         let space_optimal_approx = interface::run(
             &graph,
+            None,
+            &[],
             &order,
             true,
             Some(timeout),
             NCPUS,
             None,
             Some((AcceptFunc::BuiltinHeavyside, Some(rng.gen()))),
-        );
+            &Constraints::default(),
+            Objective::Space,
+        )
+        .unwrap();
         space_optimal_approximated_time += time.elapsed();
         let full = if get_full {
             time = Instant::now();
-            let full = interface::run(&graph, &order, true, None, NCPUS, None, None);
+            let full = interface::run(
+                &graph,
+                None,
+                &[],
+                &order,
+                true,
+                None,
+                NCPUS,
+                None,
+                None,
+                &Constraints::default(),
+                Objective::Space,
+            )
+            .unwrap();
             full_time += time.elapsed();
             Some(full)
         } else {